@@ -1,10 +1,53 @@
-use std::borrow::Cow;
+use std::fmt;
 
-use crate::types::{AttrMacro, Attribute, State};
+use super::encode::{write_astring, write_list_mailbox, write_literal, write_string};
+use super::fetch::Fetch;
+use crate::types::{
+    AclRights, AttrMacro, Attribute, Capability, CatenatePart, CompressionAlgorithm, DateTime,
+    Flag, ListReturnOption, ListSelectOption, MailboxName, PartialRange, QuotaResourceName,
+    RedactedArgs, SearchCriteria, SearchReturn, SelectParam, Seq, SequenceSet, SortCriterion,
+    SortKey, State, StatusItem, StoreMode, ThreadAlgorithm, Uid,
+};
 
 pub struct CommandBuilder {}
 
 impl CommandBuilder {
+    /// Appends a message to `mailbox`. The message is sent as a literal,
+    /// so the command has to be sent in parts, as returned by
+    /// `Request::segments`.
+    pub fn append<'a>(mailbox: impl Into<MailboxName<'a>>, message: &[u8]) -> AppendCommand {
+        let mut args = b"APPEND ".to_vec();
+        write_astring(&mut args, mailbox.into().as_str().as_bytes());
+        AppendCommand {
+            args,
+            messages: vec![AppendMessage::new(message)],
+        }
+    }
+
+    /// Appends a message that the server puts together from `parts`,
+    /// which the server must support with the CATENATE capability
+    /// (RFC 4469). If a URL can't be resolved, the command fails with
+    /// a `BADURL` response code.
+    pub fn append_catenate<'a>(
+        mailbox: impl Into<MailboxName<'a>>,
+        parts: &[CatenatePart<'_>],
+    ) -> AppendCommand {
+        let mut args = b"APPEND ".to_vec();
+        write_astring(&mut args, mailbox.into().as_str().as_bytes());
+        AppendCommand {
+            args,
+            messages: vec![AppendMessage::catenate(parts)],
+        }
+    }
+
+    pub fn capability() -> Command {
+        let args = b"CAPABILITY".to_vec();
+        Command {
+            args,
+            next_state: None,
+        }
+    }
+
     pub fn check() -> Command {
         let args = b"CHECK".to_vec();
         Command {
@@ -13,6 +56,21 @@ impl CommandBuilder {
         }
     }
 
+    /// Identifies the client to the server before authentication, as some
+    /// providers require (draft-yu-imap-client-id). `token_type` is an atom
+    /// such as `UUID`; anything else is quoted, and the server will refuse
+    /// it.
+    pub fn client_id(token_type: &str, token: &str) -> Command {
+        let mut args = b"CLIENTID ".to_vec();
+        write_astring(&mut args, token_type.as_bytes());
+        args.push(b' ');
+        write_string(&mut args, token.as_bytes());
+        Command {
+            args,
+            next_state: None,
+        }
+    }
+
     pub fn close() -> Command {
         let args = b"CLOSE".to_vec();
         Command {
@@ -21,11 +79,78 @@ impl CommandBuilder {
         }
     }
 
-    pub fn examine(mailbox: &str) -> Command {
-        let args = format!("EXAMINE \"{}\"", quoted_string(mailbox).unwrap()).into_bytes();
+    /// Compresses the connection from the server's tagged OK onwards, in
+    /// both directions (RFC 4978). The server must advertise the algorithm
+    /// as a `COMPRESS=` capability, and refuses with `COMPRESSIONACTIVE`
+    /// if compression is already on, such as through TLS.
+    ///
+    /// ```text
+    /// compress = "COMPRESS" SP algorithm
+    /// ```
+    pub fn compress(algorithm: CompressionAlgorithm) -> Command {
+        let args = format!("COMPRESS {}", algorithm.as_str()).into_bytes();
+        Command {
+            args,
+            next_state: None,
+        }
+    }
+
+    pub fn copy<'a>(set: &SequenceSet, mailbox: impl Into<MailboxName<'a>>) -> Command {
+        copy_command("COPY", set, &mailbox.into())
+    }
+
+    pub fn create<'a>(mailbox: impl Into<MailboxName<'a>>) -> Command {
+        mailbox_command("CREATE", &mailbox.into())
+    }
+
+    pub fn delete<'a>(mailbox: impl Into<MailboxName<'a>>) -> Command {
+        mailbox_command("DELETE", &mailbox.into())
+    }
+
+    /// Removes the rights of `identifier` on `mailbox` (RFC 4314).
+    ///
+    /// ```text
+    /// deleteacl = "DELETEACL" SP mailbox SP identifier
+    /// ```
+    pub fn delete_acl<'a>(mailbox: impl Into<MailboxName<'a>>, identifier: &str) -> Command {
+        identifier_command("DELETEACL", &mailbox.into(), identifier)
+    }
+
+    /// Turns on extensions that change how the server behaves, such as
+    /// `CONDSTORE`, `QRESYNC` or `UTF8=ACCEPT` (RFC 5161). The server
+    /// answers with an ENABLED response listing those it turned on.
+    ///
+    /// ```text
+    /// command-any =/ "ENABLE" 1*(SP capability)
+    /// ```
+    pub fn enable(caps: &[Capability<'_>]) -> Command {
+        let mut args = b"ENABLE".to_vec();
+        for cap in caps {
+            args.extend(format!(" {}", cap).as_bytes());
+        }
+        Command {
+            args,
+            next_state: None,
+        }
+    }
+
+    pub fn examine<'a>(mailbox: impl Into<MailboxName<'a>>) -> Command {
+        select_command("EXAMINE", &mailbox.into(), &[])
+    }
+
+    /// Like `examine`, with CONDSTORE or QRESYNC parameters.
+    pub fn examine_with<'a>(
+        mailbox: impl Into<MailboxName<'a>>,
+        params: &[SelectParam],
+    ) -> Command {
+        select_command("EXAMINE", &mailbox.into(), params)
+    }
+
+    pub fn expunge() -> Command {
+        let args = b"EXPUNGE".to_vec();
         Command {
             args,
-            next_state: Some(State::Selected),
+            next_state: None,
         }
     }
 
@@ -34,276 +159,1779 @@ impl CommandBuilder {
         FetchCommandEmpty { args }
     }
 
-    pub fn list(reference: &str, glob: &str) -> Command {
-        let args = format!(
-            "LIST \"{}\" \"{}\"",
-            quoted_string(reference).unwrap(),
-            quoted_string(glob).unwrap()
-        )
-        .into_bytes();
+    /// The access control list of `mailbox` (RFC 4314).
+    pub fn get_acl<'a>(mailbox: impl Into<MailboxName<'a>>) -> Command {
+        mailbox_command("GETACL", &mailbox.into())
+    }
+
+    /// The usage and limits of a quota root (RFC 9208).
+    ///
+    /// ```text
+    /// getquota = "GETQUOTA" SP quota-root-name
+    /// ```
+    pub fn get_quota(root: &str) -> Command {
+        let mut args = b"GETQUOTA ".to_vec();
+        write_astring(&mut args, root.as_bytes());
         Command {
             args,
             next_state: None,
         }
     }
 
-    pub fn login(user_name: &str, password: &str) -> Command {
-        let args = format!(
-            "LOGIN \"{}\" \"{}\"",
-            quoted_string(user_name).unwrap(),
-            quoted_string(password).unwrap()
-        )
-        .into_bytes();
+    /// The quota roots of `mailbox`, along with their quotas (RFC 9208).
+    pub fn get_quota_root<'a>(mailbox: impl Into<MailboxName<'a>>) -> Command {
+        mailbox_command("GETQUOTAROOT", &mailbox.into())
+    }
+
+    /// Tells the server about the client, such as `[("name", Some("mycrate"))]`,
+    /// and asks it to identify itself in an ID response (RFC 2971). Without
+    /// any parameters, `NIL` is sent. The RFC allows at most 30 parameters,
+    /// with field names of up to 30 bytes and values of up to 1024.
+    ///
+    /// ```text
+    /// id             = "ID" SP id-params-list
+    /// id-params-list = "(" *(string SP nstring) ")" / nil
+    /// ```
+    pub fn id(params: &[(&str, Option<&str>)]) -> Command {
+        let mut args = b"ID ".to_vec();
+        if params.is_empty() {
+            args.extend(b"NIL");
+        } else {
+            args.push(b'(');
+            for (i, (field, value)) in params.iter().enumerate() {
+                if i > 0 {
+                    args.push(b' ');
+                }
+                write_string(&mut args, field.as_bytes());
+                args.push(b' ');
+                match value {
+                    Some(value) => write_string(&mut args, value.as_bytes()),
+                    None => args.extend(b"NIL"),
+                }
+            }
+            args.push(b')');
+        }
         Command {
             args,
-            next_state: Some(State::Authenticated),
+            next_state: None,
         }
     }
 
-    pub fn select(mailbox: &str) -> Command {
-        let args = format!("SELECT \"{}\"", quoted_string(mailbox).unwrap()).into_bytes();
+    /// Waits for the server to report changes as they happen (RFC 2177).
+    /// Once the server has sent its continuation request, the client must
+    /// not send any command but only `IDLE_DONE`, after which the server
+    /// completes the IDLE command.
+    pub fn idle() -> Command {
+        let args = b"IDLE".to_vec();
         Command {
             args,
-            next_state: Some(State::Selected),
+            next_state: None,
         }
     }
 
-    pub fn uid_fetch() -> FetchCommandEmpty {
-        let args = b"UID FETCH ".to_vec();
-        FetchCommandEmpty { args }
+    pub fn list<'a>(reference: impl Into<MailboxName<'a>>, glob: &str) -> Command {
+        let mut args = b"LIST ".to_vec();
+        write_astring(&mut args, reference.into().as_str().as_bytes());
+        args.push(b' ');
+        write_list_mailbox(&mut args, glob.as_bytes());
+        Command {
+            args,
+            next_state: None,
+        }
     }
-}
 
-pub struct Command {
-    args: Vec<u8>,
-    next_state: Option<State>,
-}
+    /// The rights that can be granted to `identifier` on `mailbox`
+    /// (RFC 4314).
+    ///
+    /// ```text
+    /// listrights = "LISTRIGHTS" SP mailbox SP identifier
+    /// ```
+    pub fn list_rights<'a>(mailbox: impl Into<MailboxName<'a>>, identifier: &str) -> Command {
+        identifier_command("LISTRIGHTS", &mailbox.into(), identifier)
+    }
 
-impl Command {
-    pub fn into_parts(self) -> (Vec<u8>, Option<State>) {
-        let Command { args, next_state } = self;
-        (args, next_state)
+    /// A LIST command with several patterns and selection or return options,
+    /// which the server must support with the LIST-EXTENDED capability
+    /// (RFC 5258). Without patterns, `""` is sent, which only asks for the
+    /// hierarchy delimiter.
+    pub fn list_extended<'a>(
+        reference: impl Into<MailboxName<'a>>,
+        patterns: &[&str],
+    ) -> ListCommand {
+        let mut mailboxes = vec![];
+        write_astring(&mut mailboxes, reference.into().as_str().as_bytes());
+        mailboxes.push(b' ');
+        match patterns {
+            [] => mailboxes.extend(b"\"\""),
+            [pattern] => write_list_mailbox(&mut mailboxes, pattern.as_bytes()),
+            _ => {
+                mailboxes.push(b'(');
+                for (i, pattern) in patterns.iter().enumerate() {
+                    if i > 0 {
+                        mailboxes.push(b' ');
+                    }
+                    write_list_mailbox(&mut mailboxes, pattern.as_bytes());
+                }
+                mailboxes.push(b')');
+            }
+        }
+        ListCommand {
+            select: vec![],
+            mailboxes,
+            returns: vec![],
+        }
     }
-}
 
-pub struct FetchCommandEmpty {
-    args: Vec<u8>,
-}
+    pub fn login(user_name: &str, password: &str) -> Command {
+        let mut args = b"LOGIN ".to_vec();
+        write_astring(&mut args, user_name.as_bytes());
+        args.push(b' ');
+        write_astring(&mut args, password.as_bytes());
+        Command {
+            args,
+            next_state: Some(State::Authenticated),
+        }
+    }
 
-impl FetchBuilderMessages for FetchCommandEmpty {
-    fn prepare(self) -> FetchCommandMessages {
-        FetchCommandMessages { args: self.args }
+    pub fn logout() -> Command {
+        let args = b"LOGOUT".to_vec();
+        Command {
+            args,
+            next_state: Some(State::Logout),
+        }
     }
-}
 
-pub struct FetchCommandMessages {
-    args: Vec<u8>,
-}
+    pub fn lsub<'a>(reference: impl Into<MailboxName<'a>>, glob: &str) -> Command {
+        let mut args = b"LSUB ".to_vec();
+        write_astring(&mut args, reference.into().as_str().as_bytes());
+        args.push(b' ');
+        write_list_mailbox(&mut args, glob.as_bytes());
+        Command {
+            args,
+            next_state: None,
+        }
+    }
 
-impl FetchBuilderMessages for FetchCommandMessages {
-    fn prepare(self) -> FetchCommandMessages {
-        let FetchCommandMessages { mut args } = self;
-        args.push(b',');
-        FetchCommandMessages { args }
+    /// Moves the messages in `set` to `mailbox` at once, which the server
+    /// must support with the MOVE capability (RFC 6851). It sends EXPUNGE
+    /// responses for the moved messages. Named `mv` since `move` is a
+    /// keyword.
+    pub fn mv<'a>(set: &SequenceSet, mailbox: impl Into<MailboxName<'a>>) -> Command {
+        copy_command("MOVE", set, &mailbox.into())
     }
-}
 
-impl FetchCommandMessages {
-    pub fn attr_macro(self, named: AttrMacro) -> FetchCommand {
-        let FetchCommandMessages { mut args } = self;
-        args.push(b' ');
-        args.extend(
-            match named {
-                AttrMacro::All => "ALL",
-                AttrMacro::Fast => "FAST",
-                AttrMacro::Full => "FULL",
-            }
-            .as_bytes(),
-        );
-        FetchCommand { args }
+    /// The rights the user has on `mailbox` (RFC 4314).
+    pub fn my_rights<'a>(mailbox: impl Into<MailboxName<'a>>) -> Command {
+        mailbox_command("MYRIGHTS", &mailbox.into())
     }
-}
 
-pub trait FetchBuilderMessages
-where
-    Self: Sized,
-{
-    fn prepare(self) -> FetchCommandMessages;
+    pub fn noop() -> Command {
+        let args = b"NOOP".to_vec();
+        Command {
+            args,
+            next_state: None,
+        }
+    }
 
-    fn num(self, num: u32) -> FetchCommandMessages {
-        let FetchCommandMessages { mut args } = self.prepare();
-        args.extend(num.to_string().as_bytes());
-        FetchCommandMessages { args }
+    pub fn rename<'a, 'b>(
+        from: impl Into<MailboxName<'a>>,
+        to: impl Into<MailboxName<'b>>,
+    ) -> Command {
+        let mut args = b"RENAME ".to_vec();
+        write_astring(&mut args, from.into().as_str().as_bytes());
+        args.push(b' ');
+        write_astring(&mut args, to.into().as_str().as_bytes());
+        Command {
+            args,
+            next_state: None,
+        }
     }
 
-    fn range(self, start: u32, stop: u32) -> FetchCommandMessages {
-        let FetchCommandMessages { mut args } = self.prepare();
-        args.extend(start.to_string().as_bytes());
-        args.push(b':');
-        args.extend(stop.to_string().as_bytes());
-        FetchCommandMessages { args }
+    /// Replaces the message with the given sequence number by a new message
+    /// appended to `mailbox` (RFC 8508).
+    ///
+    /// The message is sent as a synchronizing literal. It only becomes
+    /// `{N+}` if the request is made non-synchronizing with
+    /// `Request::with_literal_mode`, for servers with LITERAL+ or LITERAL-
+    /// (RFC 7888).
+    pub fn replace<'a>(
+        seq: Seq,
+        mailbox: impl Into<MailboxName<'a>>,
+        flags: &[Flag<'_>],
+        message: &[u8],
+    ) -> Command {
+        replace_command("REPLACE", seq.0, &mailbox.into(), flags, message)
     }
 
-    fn all_after(self, start: u32) -> FetchCommandMessages {
-        let FetchCommandMessages { mut args } = self.prepare();
-        args.extend(start.to_string().as_bytes());
-        args.extend(b":*");
-        FetchCommandMessages { args }
+    /// Like `replace`, but identifies the message to replace by UID.
+    pub fn uid_replace<'a>(
+        uid: Uid,
+        mailbox: impl Into<MailboxName<'a>>,
+        flags: &[Flag<'_>],
+        message: &[u8],
+    ) -> Command {
+        replace_command("UID REPLACE", uid.0, &mailbox.into(), flags, message)
     }
-}
 
-pub struct FetchCommandAttributes {
-    args: Vec<u8>,
-}
+    /// Searches the selected mailbox for messages that match `criteria`.
+    pub fn search(criteria: &SearchCriteria<'_>) -> SearchCommand {
+        SearchCommand::new("SEARCH", criteria)
+    }
 
-impl FetchBuilderAttributes for FetchCommandMessages {
-    fn prepare(self) -> FetchCommandAttributes {
-        let FetchCommandMessages { mut args } = self;
-        args.extend(b" (");
-        FetchCommandAttributes { args }
+    pub fn select<'a>(mailbox: impl Into<MailboxName<'a>>) -> Command {
+        select_command("SELECT", &mailbox.into(), &[])
     }
-}
 
-impl FetchBuilderAttributes for FetchCommandAttributes {
-    fn prepare(self) -> FetchCommandAttributes {
-        let FetchCommandAttributes { mut args } = self;
-        args.push(b' ');
-        FetchCommandAttributes { args }
+    /// Like `select`, with CONDSTORE or QRESYNC parameters.
+    pub fn select_with<'a>(mailbox: impl Into<MailboxName<'a>>, params: &[SelectParam]) -> Command {
+        select_command("SELECT", &mailbox.into(), params)
     }
-}
 
-pub trait FetchBuilderAttributes
-where
-    Self: Sized,
-{
-    fn prepare(self) -> FetchCommandAttributes;
-    fn attr(self, attr: Attribute) -> FetchCommandAttributes {
-        let FetchCommandAttributes { mut args } = self.prepare();
-        args.extend(
-            match attr {
-                Attribute::Body => "BODY",
-                Attribute::Envelope => "ENVELOPE",
-                Attribute::Flags => "FLAGS",
-                Attribute::InternalDate => "INTERNALDATE",
-                Attribute::ModSeq => "MODSEQ",
-                Attribute::Rfc822 => "RFC822",
-                Attribute::Rfc822Size => "RFC822.SIZE",
-                Attribute::Rfc822Text => "RFC822.TEXT",
-                Attribute::Uid => "UID",
-            }
-            .as_bytes(),
+    /// Changes the rights of `identifier` on `mailbox` (RFC 4314). With
+    /// `StoreMode::Replace` and no rights, the identifier is left in the
+    /// list without any rights; `delete_acl` removes it.
+    ///
+    /// ```text
+    /// setacl     = "SETACL" SP mailbox SP identifier SP mod-rights
+    /// mod-rights = astring
+    ///              ;; +rights to add, -rights to remove
+    ///              ;; rights to replace
+    /// ```
+    pub fn set_acl<'a>(
+        mailbox: impl Into<MailboxName<'a>>,
+        identifier: &str,
+        mode: StoreMode,
+        rights: AclRights,
+    ) -> Command {
+        let mut command = identifier_command("SETACL", &mailbox.into(), identifier);
+        let prefix = match mode {
+            StoreMode::Replace => "",
+            StoreMode::Add => "+",
+            StoreMode::Remove => "-",
+        };
+        command.args.push(b' ');
+        write_astring(
+            &mut command.args,
+            format!("{}{}", prefix, rights).as_bytes(),
         );
-        FetchCommandAttributes { args }
+        command
     }
-}
 
-pub struct FetchCommand {
-    args: Vec<u8>,
-}
+    /// Sets the limits of a quota root, usually only allowed for
+    /// administrators (RFC 9208). Resources that are left out have no
+    /// limit afterwards.
+    ///
+    /// ```text
+    /// setquota          = "SETQUOTA" SP quota-root-name SP setquota-list
+    /// setquota-list     = "(" [setquota-resource *(SP setquota-resource)] ")"
+    /// setquota-resource = resource-name SP resource-limit
+    /// ```
+    pub fn set_quota(root: &str, limits: &[(QuotaResourceName<'_>, u64)]) -> Command {
+        let mut args = b"SETQUOTA ".to_vec();
+        write_astring(&mut args, root.as_bytes());
+        let limits = limits
+            .iter()
+            .map(|(name, limit)| format!("{} {}", name.as_str(), limit))
+            .collect::<Vec<_>>();
+        args.extend(format!(" ({})", limits.join(" ")).as_bytes());
+        Command {
+            args,
+            next_state: None,
+        }
+    }
 
-pub trait FetchBuilderModifiers
-where
-    Self: Sized,
-{
-    fn prepare(self) -> FetchCommand;
-    fn build(self) -> Command {
-        let FetchCommand { args } = self.prepare();
+    /// Searches like `search`, and returns the results ordered by
+    /// `criteria` (RFC 5256). Later keys break ties between earlier ones.
+    /// At least one key must be sent, so `ARRIVAL` is used if `criteria` is
+    /// empty.
+    pub fn sort(criteria: &[SortCriterion], search: &SearchCriteria<'_>) -> Command {
+        sort_command("SORT", criteria, search)
+    }
+
+    /// Asks for the status of `mailbox` without selecting it. At least one
+    /// item must be asked for, so `MESSAGES` is sent if `items` is empty.
+    pub fn status<'a>(mailbox: impl Into<MailboxName<'a>>, items: &[StatusItem]) -> Command {
+        let mut args = b"STATUS ".to_vec();
+        write_astring(&mut args, mailbox.into().as_str().as_bytes());
+        args.push(b' ');
+        args.extend(status_items(items).as_bytes());
         Command {
             args,
             next_state: None,
         }
     }
-    fn changed_since(self, seq: u64) -> FetchCommand {
-        let FetchCommand { mut args } = self.prepare();
-        args.extend(b" (CHANGEDSINCE ");
-        args.extend(seq.to_string().as_bytes());
-        args.push(b')');
-        FetchCommand { args }
+
+    /// Changes the flags of the messages in `set`. The server sends the
+    /// new flags back unless the command is made `silent`. Flags that
+    /// can't be sent, such as keywords that aren't atoms, are left out.
+    pub fn store(set: &SequenceSet, mode: StoreMode, flags: &[Flag<'_>]) -> StoreCommand {
+        StoreCommand::new("STORE", set, mode, flags)
     }
-}
 
-impl FetchBuilderModifiers for FetchCommandAttributes {
-    fn prepare(self) -> FetchCommand {
-        let FetchCommandAttributes { mut args, .. } = self;
-        args.push(b')');
-        FetchCommand { args }
+    pub fn subscribe<'a>(mailbox: impl Into<MailboxName<'a>>) -> Command {
+        mailbox_command("SUBSCRIBE", &mailbox.into())
     }
-}
 
-impl FetchBuilderModifiers for FetchCommand {
-    fn prepare(self) -> FetchCommand {
-        self
+    /// Searches like `search`, and returns the results grouped into threads
+    /// (RFC 5256).
+    pub fn thread(algorithm: ThreadAlgorithm, search: &SearchCriteria<'_>) -> Command {
+        thread_command("THREAD", algorithm, search)
     }
-}
 
-/// Returns an escaped string if necessary for use as a "quoted" string per
-/// the IMAPv4 RFC. Return value does not include surrounding quote characters.
-/// Will return Err if the argument contains illegal characters.
-///
-/// Relevant definitions from RFC 3501 formal syntax:
-///
-/// string = quoted / literal [literal elided here]
-/// quoted = DQUOTE *QUOTED-CHAR DQUOTE
-/// QUOTED-CHAR = <any TEXT-CHAR except quoted-specials> / "\" quoted-specials
-/// quoted-specials = DQUOTE / "\"
-/// TEXT-CHAR = <any CHAR except CR and LF>
-fn quoted_string(s: &str) -> Result<Cow<str>, &'static str> {
-    let bytes = s.as_bytes();
-    let (mut start, mut new) = (0, Vec::<u8>::new());
-    for (i, b) in bytes.iter().enumerate() {
-        match *b {
-            b'\r' | b'\n' => {
-                return Err("CR and LF not allowed in quoted strings");
-            }
-            b'\\' | b'"' => {
-                if start < i {
-                    new.extend(&bytes[start..i]);
-                }
-                new.push(b'\\');
-                new.push(*b);
-                start = i + 1;
-            }
-            _ => {}
-        };
+    pub fn uid_copy<'a>(set: &SequenceSet, mailbox: impl Into<MailboxName<'a>>) -> Command {
+        copy_command("UID COPY", set, &mailbox.into())
     }
-    if start == 0 {
-        Ok(Cow::Borrowed(s))
-    } else {
-        if start < bytes.len() {
-            new.extend(&bytes[start..]);
+
+    /// Expunges only the deleted messages with the given UIDs, so that
+    /// messages deleted by other clients are kept (RFC 4315, UIDPLUS).
+    pub fn uid_expunge(set: &SequenceSet) -> Command {
+        let args = format!("UID EXPUNGE {}", set).into_bytes();
+        Command {
+            args,
+            next_state: None,
         }
-        // Since the argument is a str, it must contain valid UTF-8. Since
-        // this function's transformation preserves the UTF-8 validity,
-        // unwrapping here should be okay.
-        Ok(Cow::Owned(String::from_utf8(new).unwrap()))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::quoted_string;
-    use super::CommandBuilder;
+    pub fn uid_fetch() -> FetchCommandEmpty {
+        let args = b"UID FETCH ".to_vec();
+        FetchCommandEmpty { args }
+    }
 
-    #[test]
-    fn login() {
-        assert_eq!(
-            CommandBuilder::login("djc", "s3cr3t").into_parts().0,
-            b"LOGIN \"djc\" \"s3cr3t\""
-        );
-        assert_eq!(
-            CommandBuilder::login("djc", "domain\\password")
-                .into_parts()
-                .0,
-            b"LOGIN \"djc\" \"domain\\\\password\""
-        );
+    /// Like `mv`, but `set` holds UIDs.
+    pub fn uid_mv<'a>(set: &SequenceSet, mailbox: impl Into<MailboxName<'a>>) -> Command {
+        copy_command("UID MOVE", set, &mailbox.into())
     }
 
-    #[test]
-    fn test_quoted_string() {
-        assert_eq!(quoted_string("a").unwrap(), "a");
-        assert_eq!(quoted_string("").unwrap(), "");
-        assert_eq!(quoted_string("a\"b\\c").unwrap(), "a\\\"b\\\\c");
-        assert_eq!(quoted_string("\"foo\\").unwrap(), "\\\"foo\\\\");
-        assert!(quoted_string("\n").is_err());
+    /// Like `search`, but the results are UIDs.
+    pub fn uid_search(criteria: &SearchCriteria<'_>) -> SearchCommand {
+        SearchCommand::new("UID SEARCH", criteria)
+    }
+
+    /// Like `sort`, but the results are UIDs.
+    pub fn uid_sort(criteria: &[SortCriterion], search: &SearchCriteria<'_>) -> Command {
+        sort_command("UID SORT", criteria, search)
+    }
+
+    /// Like `store`, but `set` holds UIDs.
+    pub fn uid_store(set: &SequenceSet, mode: StoreMode, flags: &[Flag<'_>]) -> StoreCommand {
+        StoreCommand::new("UID STORE", set, mode, flags)
+    }
+
+    /// Like `thread`, but the results are UIDs.
+    pub fn uid_thread(algorithm: ThreadAlgorithm, search: &SearchCriteria<'_>) -> Command {
+        thread_command("UID THREAD", algorithm, search)
+    }
+
+    /// Leaves the selected mailbox like `close`, but without expunging
+    /// the deleted messages (RFC 3691).
+    pub fn unselect() -> Command {
+        let args = b"UNSELECT".to_vec();
+        Command {
+            args,
+            next_state: Some(State::Authenticated),
+        }
+    }
+
+    pub fn unsubscribe<'a>(mailbox: impl Into<MailboxName<'a>>) -> Command {
+        mailbox_command("UNSUBSCRIBE", &mailbox.into())
+    }
+}
+
+// create          = "CREATE" SP mailbox
+// delete          = "DELETE" SP mailbox
+// subscribe       = "SUBSCRIBE" SP mailbox
+// unsubscribe     = "UNSUBSCRIBE" SP mailbox
+fn mailbox_command(name: &str, mailbox: &MailboxName<'_>) -> Command {
+    let mut args = format!("{} ", name).into_bytes();
+    write_astring(&mut args, mailbox.as_str().as_bytes());
+    Command {
+        args,
+        next_state: None,
+    }
+}
+
+// sort            = ["UID" SP] "SORT" SP sort-criteria SP search-criteria
+// sort-criteria   = "(" sort-criterion *(SP sort-criterion) ")"
+// sort-criterion  = ["REVERSE" SP] sort-key
+fn sort_command(name: &str, criteria: &[SortCriterion], search: &SearchCriteria<'_>) -> Command {
+    let criteria = if criteria.is_empty() {
+        vec![SortKey::Arrival.as_str().to_string()]
+    } else {
+        criteria
+            .iter()
+            .map(|criterion| {
+                if criterion.reverse {
+                    format!("REVERSE {}", criterion.key.as_str())
+                } else {
+                    criterion.key.as_str().to_string()
+                }
+            })
+            .collect()
+    };
+    search_criteria_command(format!("{} ({})", name, criteria.join(" ")), search)
+}
+
+// thread          = ["UID" SP] "THREAD" SP thread-alg SP search-criteria
+fn thread_command(name: &str, algorithm: ThreadAlgorithm, search: &SearchCriteria<'_>) -> Command {
+    search_criteria_command(format!("{} {}", name, algorithm.as_str()), search)
+}
+
+// search-criteria = charset 1*(SP search-key)
+// The charset is not optional here; every server that supports SORT and
+// THREAD has to accept UTF-8.
+fn search_criteria_command(prefix: String, search: &SearchCriteria<'_>) -> Command {
+    let mut args = format!("{} UTF-8 ", prefix).into_bytes();
+    search.encode(&mut args);
+    Command {
+        args,
+        next_state: None,
+    }
+}
+
+// select          = "SELECT" SP mailbox [select-params]
+// select-params   = SP "(" select-param *(SP select-param) ")"
+// select-param    =/ "QRESYNC" SP "(" uidvalidity SP mod-sequence-value
+//                    [SP known-uids] [SP seq-match-data] ")"
+// seq-match-data  = "(" known-sequence-set SP known-uid-set ")"
+fn select_command(name: &str, mailbox: &MailboxName<'_>, params: &[SelectParam]) -> Command {
+    let mut command = mailbox_command(name, mailbox);
+    if !params.is_empty() {
+        let params = params
+            .iter()
+            .map(|param| match param {
+                SelectParam::Condstore => "CONDSTORE".to_string(),
+                SelectParam::Qresync(qresync) => {
+                    let mut param =
+                        format!("QRESYNC ({} {}", qresync.uid_validity, qresync.mod_seq);
+                    if let Some(uids) = &qresync.known_uids {
+                        param.push_str(&format!(" {}", uids));
+                    }
+                    if let Some((seqs, uids)) = &qresync.seq_match {
+                        param.push_str(&format!(" ({} {})", seqs, uids));
+                    }
+                    param.push(')');
+                    param
+                }
+            })
+            .collect::<Vec<_>>();
+        command
+            .args
+            .extend(format!(" ({})", params.join(" ")).as_bytes());
+    }
+    command.next_state = Some(State::Selected);
+    command
+}
+
+// identifier      = astring
+fn identifier_command(name: &str, mailbox: &MailboxName<'_>, identifier: &str) -> Command {
+    let mut command = mailbox_command(name, mailbox);
+    command.args.push(b' ');
+    write_astring(&mut command.args, identifier.as_bytes());
+    command
+}
+
+// copy            = "COPY" SP sequence-set SP mailbox
+// move            = "MOVE" SP sequence-set SP mailbox
+fn copy_command(name: &str, set: &SequenceSet, mailbox: &MailboxName<'_>) -> Command {
+    let mut args = format!("{} {} ", name, set).into_bytes();
+    write_astring(&mut args, mailbox.as_str().as_bytes());
+    Command {
+        args,
+        next_state: None,
+    }
+}
+
+// replace         = "REPLACE" SP seq-number SP mailbox [SP flag-list]
+//                   [SP date-time] SP append-data
+fn replace_command(
+    name: &str,
+    num: u32,
+    mailbox: &MailboxName<'_>,
+    flags: &[Flag<'_>],
+    message: &[u8],
+) -> Command {
+    let mut args = format!("{} {} ", name, num).into_bytes();
+    write_astring(&mut args, mailbox.as_str().as_bytes());
+    let flags = flag_list(flags);
+    if !flags.is_empty() {
+        args.extend(format!(" ({})", flags.join(" ")).as_bytes());
+    }
+    args.push(b' ');
+    write_literal(&mut args, message);
+    Command {
+        args,
+        next_state: None,
+    }
+}
+
+// flag-list = "(" [flag *(SP flag)] ")"
+// Flags that can't be sent, such as keywords that aren't atoms, are left
+// out rather than written raw.
+fn flag_list(flags: &[Flag<'_>]) -> Vec<String> {
+    flags
+        .iter()
+        .filter(|flag| flag.is_valid())
+        .map(|flag| flag.as_str().to_string())
+        .collect()
+}
+
+// "(" status-att *(SP status-att) ")"
+fn status_items(items: &[StatusItem]) -> String {
+    let items = if items.is_empty() {
+        vec![StatusItem::Messages.as_str()]
+    } else {
+        items.iter().map(|item| item.as_str()).collect()
+    };
+    format!("({})", items.join(" "))
+}
+
+/// The line that ends an IDLE command. It is sent untagged, as is.
+///
+/// ```text
+/// idle = "IDLE" CRLF "DONE"
+/// ```
+pub const IDLE_DONE: &[u8] = b"DONE\r\n";
+
+pub struct Command {
+    args: Vec<u8>,
+    next_state: Option<State>,
+}
+
+/// Formats the arguments like `Request::redacted`, so that credentials
+/// don't end up in logs.
+impl fmt::Debug for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Command")
+            .field("args", &format_args!("{}", RedactedArgs(&self.args)))
+            .field("next_state", &self.next_state)
+            .finish()
+    }
+}
+
+impl Command {
+    pub fn into_parts(self) -> (Vec<u8>, Option<State>) {
+        let Command { args, next_state } = self;
+        (args, next_state)
+    }
+
+    /// Whether this is an IDLE command, which has to be ended with
+    /// `IDLE_DONE` before another command can be sent.
+    pub fn is_idle(&self) -> bool {
+        self.args == b"IDLE"
+    }
+}
+
+pub struct SearchCommand {
+    name: &'static str,
+    returns: Option<Vec<SearchReturn>>,
+    charset: Option<String>,
+    keys: Vec<u8>,
+}
+
+impl SearchCommand {
+    fn new(name: &'static str, criteria: &SearchCriteria<'_>) -> Self {
+        let mut keys = vec![];
+        criteria.encode(&mut keys);
+        SearchCommand {
+            name,
+            returns: None,
+            charset: if criteria.is_ascii() {
+                None
+            } else {
+                Some("UTF-8".to_string())
+            },
+            keys,
+        }
+    }
+
+    /// Sets the charset of the strings in the criteria. It defaults to
+    /// `UTF-8` if they are not all ASCII, and is left out otherwise.
+    pub fn charset(mut self, charset: &str) -> Self {
+        self.charset = Some(charset.to_string());
+        self
+    }
+
+    /// Asks for the results in an ESEARCH response, with only the data
+    /// given by `options` (RFC 4731). Without any options, the server
+    /// returns all matching messages, as with `SearchReturn::All`.
+    pub fn returns(mut self, options: &[SearchReturn]) -> Self {
+        self.returns = Some(options.to_vec());
+        self
+    }
+
+    // search             = "SEARCH" [search-return-opts]
+    //                      [SP "CHARSET" SP astring] 1*(SP search-key)
+    // search-return-opts = SP "RETURN" SP "(" [search-return-opt
+    //                      *(SP search-return-opt)] ")"
+    pub fn build(self) -> Command {
+        let mut args = self.name.as_bytes().to_vec();
+        if let Some(returns) = self.returns {
+            let returns = returns.into_iter().map(search_return).collect::<Vec<_>>();
+            args.extend(format!(" RETURN ({})", returns.join(" ")).as_bytes());
+        }
+        if let Some(charset) = self.charset {
+            args.extend(b" CHARSET ");
+            write_astring(&mut args, charset.as_bytes());
+        }
+        args.push(b' ');
+        args.extend(self.keys);
+        Command {
+            args,
+            next_state: None,
+        }
+    }
+}
+
+pub struct ListCommand {
+    select: Vec<ListSelectOption>,
+    // The reference name and the patterns, encoded.
+    mailboxes: Vec<u8>,
+    returns: Vec<ListReturnOption>,
+}
+
+impl ListCommand {
+    /// Adds selection options, which decide which mailboxes are listed.
+    pub fn select(mut self, options: &[ListSelectOption]) -> Self {
+        self.select.extend_from_slice(options);
+        self
+    }
+
+    /// Adds return options, which ask for more data about each mailbox.
+    pub fn returns(mut self, options: &[ListReturnOption]) -> Self {
+        self.returns.extend_from_slice(options);
+        self
+    }
+
+    // list             = "LIST" [SP list-select-opts] SP mailbox SP mbox-or-pat
+    //                    [SP list-return-opts]
+    // list-select-opts = "(" [list-select-option *(SP list-select-option)] ")"
+    // mbox-or-pat      = list-mailbox / patterns
+    // patterns         = "(" list-mailbox *(SP list-mailbox) ")"
+    // list-return-opts = "RETURN" SP "(" [return-option *(SP return-option)] ")"
+    pub fn build(self) -> Command {
+        let mut args = b"LIST ".to_vec();
+        if !self.select.is_empty() {
+            let select = self.select.iter().map(|option| option.as_str());
+            args.extend(format!("({}) ", select.collect::<Vec<_>>().join(" ")).as_bytes());
+        }
+        args.extend(self.mailboxes);
+        if !self.returns.is_empty() {
+            let returns = self.returns.iter().map(list_return_option);
+            args.extend(format!(" RETURN ({})", returns.collect::<Vec<_>>().join(" ")).as_bytes());
+        }
+        Command {
+            args,
+            next_state: None,
+        }
+    }
+}
+
+// return-option   = "SUBSCRIBED" / "CHILDREN" / "SPECIAL-USE" /
+//                   "STATUS" SP "(" status-att *(SP status-att) ")"
+fn list_return_option(option: &ListReturnOption) -> String {
+    match option {
+        ListReturnOption::Subscribed => "SUBSCRIBED".to_string(),
+        ListReturnOption::Children => "CHILDREN".to_string(),
+        ListReturnOption::SpecialUse => "SPECIAL-USE".to_string(),
+        ListReturnOption::Status(items) => format!("STATUS {}", status_items(items)),
+    }
+}
+
+pub struct AppendCommand {
+    args: Vec<u8>,
+    // Never empty.
+    messages: Vec<AppendMessage>,
+}
+
+struct AppendMessage {
+    flags: Vec<String>,
+    internal_date: Option<DateTime>,
+    // The encoded append-data.
+    data: Vec<u8>,
+}
+
+impl AppendMessage {
+    fn new(message: &[u8]) -> Self {
+        let mut data = vec![];
+        write_literal(&mut data, message);
+        AppendMessage::with_data(data)
+    }
+
+    // append-data  =/ "CATENATE" SP "(" cat-part *(SP cat-part) ")"
+    // cat-part     = text-literal / url
+    // text-literal = "TEXT" SP literal
+    // url          = "URL" SP astring
+    fn catenate(parts: &[CatenatePart<'_>]) -> Self {
+        let mut data = b"CATENATE (".to_vec();
+        for (i, part) in parts.iter().enumerate() {
+            if i > 0 {
+                data.push(b' ');
+            }
+            match part {
+                CatenatePart::Url(url) => {
+                    data.extend(b"URL ");
+                    write_astring(&mut data, url.as_bytes());
+                }
+                CatenatePart::Text(text) => {
+                    data.extend(b"TEXT ");
+                    write_literal(&mut data, text);
+                }
+            }
+        }
+        data.push(b')');
+        AppendMessage::with_data(data)
+    }
+
+    fn with_data(data: Vec<u8>) -> Self {
+        AppendMessage {
+            flags: vec![],
+            internal_date: None,
+            data,
+        }
+    }
+}
+
+impl AppendCommand {
+    /// Sets the flags of the last message added, leaving out those that
+    /// can't be sent.
+    pub fn flags(mut self, flags: &[Flag<'_>]) -> Self {
+        self.last().flags = flag_list(flags);
+        self
+    }
+
+    /// Sets the internal date of the last message added, which otherwise
+    /// is the time it is appended.
+    pub fn internal_date(mut self, date: DateTime) -> Self {
+        self.last().internal_date = Some(date);
+        self
+    }
+
+    /// Adds another message to append in the same command, which the
+    /// server must support with the MULTIAPPEND capability (RFC 3502).
+    /// Either all messages are appended or none.
+    pub fn message(mut self, message: &[u8]) -> Self {
+        self.messages.push(AppendMessage::new(message));
+        self
+    }
+
+    /// Like `message`, but for a message put together from `parts`, as
+    /// with `CommandBuilder::append_catenate`.
+    pub fn catenate(mut self, parts: &[CatenatePart<'_>]) -> Self {
+        self.messages.push(AppendMessage::catenate(parts));
+        self
+    }
+
+    fn last(&mut self) -> &mut AppendMessage {
+        self.messages.last_mut().unwrap() // safe: never empty
+    }
+
+    // append          = "APPEND" SP mailbox 1*append-message
+    // append-message  = append-opts SP append-data
+    // append-opts     = [SP flag-list] [SP date-time] *(SP append-ext)
+    // append-data     = literal / literal8 / append-data-ext
+    pub fn build(self) -> Command {
+        let AppendCommand { mut args, messages } = self;
+        for message in messages {
+            if !message.flags.is_empty() {
+                args.extend(format!(" ({})", message.flags.join(" ")).as_bytes());
+            }
+            if let Some(date) = message.internal_date {
+                args.extend(format!(" \"{}\"", date).as_bytes());
+            }
+            args.push(b' ');
+            args.extend(message.data);
+        }
+        Command {
+            args,
+            next_state: None,
+        }
+    }
+}
+
+pub struct StoreCommand {
+    name: &'static str,
+    set: String,
+    mode: StoreMode,
+    flags: Vec<String>,
+    silent: bool,
+    unchanged_since: Option<u64>,
+}
+
+impl StoreCommand {
+    fn new(name: &'static str, set: &SequenceSet, mode: StoreMode, flags: &[Flag<'_>]) -> Self {
+        StoreCommand {
+            name,
+            set: set.to_string(),
+            mode,
+            flags: flag_list(flags),
+            silent: false,
+            unchanged_since: None,
+        }
+    }
+
+    /// Uses the `.SILENT` form, so that the server doesn't send a FETCH
+    /// response with the new flags for each message.
+    pub fn silent(mut self) -> Self {
+        self.silent = true;
+        self
+    }
+
+    /// Only changes the messages whose mod-sequence is at most `seq`
+    /// (RFC 7162). The server lists the others in a MODIFIED response
+    /// code instead.
+    pub fn unchanged_since(mut self, seq: u64) -> Self {
+        self.unchanged_since = Some(seq);
+        self
+    }
+
+    // store           = "STORE" SP sequence-set [store-modifiers] SP
+    //                   store-att-flags
+    // store-modifiers = SP "(" "UNCHANGEDSINCE" SP mod-sequence-valzer ")"
+    // store-att-flags = (["+" / "-"] "FLAGS" [".SILENT"]) SP
+    //                   (flag-list / (flag *(SP flag)))
+    pub fn build(self) -> Command {
+        let item = match self.mode {
+            StoreMode::Replace => "FLAGS",
+            StoreMode::Add => "+FLAGS",
+            StoreMode::Remove => "-FLAGS",
+        };
+        let silent = if self.silent { ".SILENT" } else { "" };
+        let modifiers = match self.unchanged_since {
+            Some(seq) => format!(" (UNCHANGEDSINCE {})", seq),
+            None => String::new(),
+        };
+        let args = format!(
+            "{} {}{} {}{} ({})",
+            self.name,
+            self.set,
+            modifiers,
+            item,
+            silent,
+            self.flags.join(" ")
+        )
+        .into_bytes();
+        Command {
+            args,
+            next_state: None,
+        }
+    }
+}
+
+pub struct FetchCommandEmpty {
+    args: Vec<u8>,
+}
+
+impl FetchBuilderMessages for FetchCommandEmpty {
+    fn prepare(self) -> FetchCommandMessages {
+        FetchCommandMessages { args: self.args }
+    }
+}
+
+pub struct FetchCommandMessages {
+    args: Vec<u8>,
+}
+
+impl FetchBuilderMessages for FetchCommandMessages {
+    fn prepare(self) -> FetchCommandMessages {
+        let FetchCommandMessages { mut args } = self;
+        args.push(b',');
+        FetchCommandMessages { args }
+    }
+}
+
+impl FetchCommandMessages {
+    /// Fetches the items of a `Fetch`, which can include body sections.
+    pub fn items(self, items: &Fetch) -> FetchCommand {
+        let FetchCommandMessages { mut args } = self;
+        args.push(b' ');
+        items.encode(&mut args);
+        FetchCommand::new(args)
+    }
+
+    pub fn attr_macro(self, named: AttrMacro) -> FetchCommand {
+        let FetchCommandMessages { mut args } = self;
+        args.push(b' ');
+        args.extend(
+            match named {
+                AttrMacro::All => "ALL",
+                AttrMacro::Fast => "FAST",
+                AttrMacro::Full => "FULL",
+            }
+            .as_bytes(),
+        );
+        FetchCommand::new(args)
+    }
+}
+
+pub trait FetchBuilderMessages
+where
+    Self: Sized,
+{
+    fn prepare(self) -> FetchCommandMessages;
+
+    fn num(self, num: u32) -> FetchCommandMessages {
+        let FetchCommandMessages { mut args } = self.prepare();
+        args.extend(num.to_string().as_bytes());
+        FetchCommandMessages { args }
+    }
+
+    fn range(self, start: u32, stop: u32) -> FetchCommandMessages {
+        let FetchCommandMessages { mut args } = self.prepare();
+        args.extend(start.to_string().as_bytes());
+        args.push(b':');
+        args.extend(stop.to_string().as_bytes());
+        FetchCommandMessages { args }
+    }
+
+    fn all_after(self, start: u32) -> FetchCommandMessages {
+        let FetchCommandMessages { mut args } = self.prepare();
+        args.extend(start.to_string().as_bytes());
+        args.extend(b":*");
+        FetchCommandMessages { args }
+    }
+
+    fn set(self, set: &SequenceSet) -> FetchCommandMessages {
+        let FetchCommandMessages { mut args } = self.prepare();
+        args.extend(set.to_string().as_bytes());
+        FetchCommandMessages { args }
+    }
+}
+
+pub struct FetchCommandAttributes {
+    args: Vec<u8>,
+}
+
+impl FetchBuilderAttributes for FetchCommandMessages {
+    fn prepare(self) -> FetchCommandAttributes {
+        let FetchCommandMessages { mut args } = self;
+        args.extend(b" (");
+        FetchCommandAttributes { args }
+    }
+}
+
+impl FetchBuilderAttributes for FetchCommandAttributes {
+    fn prepare(self) -> FetchCommandAttributes {
+        let FetchCommandAttributes { mut args } = self;
+        args.push(b' ');
+        FetchCommandAttributes { args }
+    }
+}
+
+pub trait FetchBuilderAttributes
+where
+    Self: Sized,
+{
+    fn prepare(self) -> FetchCommandAttributes;
+    fn attr(self, attr: Attribute) -> FetchCommandAttributes {
+        let FetchCommandAttributes { mut args } = self.prepare();
+        args.extend(attr.as_str().as_bytes());
+        FetchCommandAttributes { args }
+    }
+}
+
+pub struct FetchCommand {
+    args: Vec<u8>,
+    modifiers: Vec<String>,
+}
+
+impl FetchCommand {
+    fn new(args: Vec<u8>) -> Self {
+        FetchCommand {
+            args,
+            modifiers: vec![],
+        }
+    }
+
+    fn modifier(mut self, modifier: String) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+}
+
+// fetch           = "FETCH" SP sequence-set SP ("ALL" / "FULL" / "FAST" /
+//                   fetch-att / "(" fetch-att *(SP fetch-att) ")")
+//                   [fetch-modifiers]
+// fetch-modifiers = SP "(" fetch-modifier *(SP fetch-modifier) ")"
+pub trait FetchBuilderModifiers
+where
+    Self: Sized,
+{
+    fn prepare(self) -> FetchCommand;
+    fn build(self) -> Command {
+        let FetchCommand {
+            mut args,
+            modifiers,
+        } = self.prepare();
+        if !modifiers.is_empty() {
+            args.extend(format!(" ({})", modifiers.join(" ")).as_bytes());
+        }
+        Command {
+            args,
+            next_state: None,
+        }
+    }
+    /// Only messages whose mod-sequence is greater than `seq` (RFC 7162).
+    fn changed_since(self, seq: u64) -> FetchCommand {
+        self.prepare().modifier(format!("CHANGEDSINCE {}", seq))
+    }
+    /// Also reports the messages in the UID set that were expunged since
+    /// the `changed_since` mod-sequence, in a VANISHED (EARLIER) response.
+    /// Only valid for UID FETCH with `changed_since`, once QRESYNC has
+    /// been enabled (RFC 7162, section 3.2.6).
+    fn vanished(self) -> FetchCommand {
+        self.prepare().modifier("VANISHED".to_string())
+    }
+    fn partial(self, range: PartialRange) -> FetchCommand {
+        self.prepare()
+            .modifier(format!("PARTIAL {}", partial_range(range)))
+    }
+}
+
+impl FetchBuilderModifiers for FetchCommandAttributes {
+    fn prepare(self) -> FetchCommand {
+        let FetchCommandAttributes { mut args, .. } = self;
+        args.push(b')');
+        FetchCommand::new(args)
+    }
+}
+
+impl FetchBuilderModifiers for FetchCommand {
+    fn prepare(self) -> FetchCommand {
+        self
+    }
+}
+
+// search-return-opt = "MIN" / "MAX" / "ALL" / "COUNT" / "SAVE" /
+//                     "PARTIAL" SP partial-range
+fn search_return(option: SearchReturn) -> String {
+    match option {
+        SearchReturn::Min => "MIN".to_string(),
+        SearchReturn::Max => "MAX".to_string(),
+        SearchReturn::All => "ALL".to_string(),
+        SearchReturn::Count => "COUNT".to_string(),
+        SearchReturn::Save => "SAVE".to_string(),
+        SearchReturn::Partial(range) => format!("PARTIAL {}", partial_range(range)),
+    }
+}
+
+// partial-range-first = nz-number ":" nz-number
+// partial-range-last  = MINUS nz-number ":" MINUS nz-number
+fn partial_range(range: PartialRange) -> String {
+    match range {
+        PartialRange::First(first, last) => format!("{}:{}", first, last),
+        PartialRange::Last(first, last) => format!("-{}:-{}", first, last),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{
+        CommandBuilder, FetchBuilderAttributes, FetchBuilderMessages, FetchBuilderModifiers,
+    };
+    use crate::builders::fetch::Fetch;
+    use crate::builders::search::SearchBuilder;
+    use crate::types::{
+        AclRights, Attribute, Capability, CatenatePart, CompressionAlgorithm, Date, DateTime, Flag,
+        ListReturnOption, ListSelectOption, MailboxName, PartialRange, QresyncParams,
+        QuotaResourceName, SearchCriteria, SearchReturn, SectionPath, SelectParam, Seq,
+        SequenceSet, SortKey, State, StatusItem, StoreMode, ThreadAlgorithm, Uid,
+    };
+
+    #[test]
+    fn simple() {
+        assert_eq!(
+            CommandBuilder::capability().into_parts(),
+            (b"CAPABILITY".to_vec(), None)
+        );
+        assert_eq!(
+            CommandBuilder::noop().into_parts(),
+            (b"NOOP".to_vec(), None)
+        );
+        assert_eq!(
+            CommandBuilder::logout().into_parts(),
+            (b"LOGOUT".to_vec(), Some(State::Logout))
+        );
+        assert_eq!(
+            CommandBuilder::unselect().into_parts(),
+            (b"UNSELECT".to_vec(), Some(State::Authenticated))
+        );
+        assert_eq!(
+            CommandBuilder::expunge().into_parts(),
+            (b"EXPUNGE".to_vec(), None)
+        );
+        assert_eq!(
+            CommandBuilder::compress(CompressionAlgorithm::Deflate).into_parts(),
+            (b"COMPRESS DEFLATE".to_vec(), None)
+        );
+    }
+
+    #[test]
+    fn enable() {
+        let caps = [
+            Capability::Condstore,
+            Capability::Qresync,
+            Capability::Atom(Cow::Borrowed("UTF8=ACCEPT")),
+        ];
+        assert_eq!(
+            CommandBuilder::enable(&caps).into_parts().0,
+            b"ENABLE CONDSTORE QRESYNC UTF8=ACCEPT"
+        );
+    }
+
+    #[test]
+    fn id() {
+        assert_eq!(CommandBuilder::id(&[]).into_parts().0, b"ID NIL");
+        assert_eq!(
+            CommandBuilder::id(&[
+                ("name", Some("mycrate")),
+                ("version", Some("1.0")),
+                ("support-url", None),
+                ("os", Some("say \"hi\"")),
+            ])
+            .into_parts()
+            .0,
+            &b"ID (\"name\" \"mycrate\" \"version\" \"1.0\" \"support-url\" NIL \
+               \"os\" \"say \\\"hi\\\"\")"[..]
+        );
+    }
+
+    #[test]
+    fn quota() {
+        assert_eq!(
+            CommandBuilder::get_quota("").into_parts().0,
+            b"GETQUOTA \"\""
+        );
+        assert_eq!(
+            CommandBuilder::get_quota_root("INBOX").into_parts().0,
+            b"GETQUOTAROOT INBOX"
+        );
+        assert_eq!(
+            CommandBuilder::set_quota(
+                "user.alice",
+                &[
+                    (QuotaResourceName::Storage, 512),
+                    (QuotaResourceName::Atom(Cow::Borrowed("X-FOO")), 0)
+                ]
+            )
+            .into_parts()
+            .0,
+            b"SETQUOTA user.alice (STORAGE 512 X-FOO 0)"
+        );
+        assert_eq!(
+            CommandBuilder::set_quota("", &[]).into_parts().0,
+            b"SETQUOTA \"\" ()"
+        );
+    }
+
+    #[test]
+    fn acl() {
+        let rights = AclRights::LOOKUP | AclRights::READ | AclRights::SEEN;
+        assert_eq!(
+            CommandBuilder::set_acl("Shared/Team", "bob", StoreMode::Replace, rights)
+                .into_parts()
+                .0,
+            b"SETACL Shared/Team bob lrs"
+        );
+        assert_eq!(
+            CommandBuilder::set_acl("INBOX", "anyone", StoreMode::Add, AclRights::POST)
+                .into_parts()
+                .0,
+            b"SETACL INBOX anyone +p"
+        );
+        assert_eq!(
+            CommandBuilder::set_acl("INBOX", "-bob", StoreMode::Remove, AclRights::WRITE)
+                .into_parts()
+                .0,
+            b"SETACL INBOX -bob -w"
+        );
+        assert_eq!(
+            CommandBuilder::set_acl("INBOX", "bob", StoreMode::Replace, AclRights::empty())
+                .into_parts()
+                .0,
+            b"SETACL INBOX bob \"\""
+        );
+        assert_eq!(
+            CommandBuilder::delete_acl("INBOX", "Fred Smith")
+                .into_parts()
+                .0,
+            b"DELETEACL INBOX \"Fred Smith\""
+        );
+        assert_eq!(
+            CommandBuilder::get_acl("INBOX").into_parts().0,
+            b"GETACL INBOX"
+        );
+        assert_eq!(
+            CommandBuilder::list_rights("Archive", "smith")
+                .into_parts()
+                .0,
+            b"LISTRIGHTS Archive smith"
+        );
+        assert_eq!(
+            CommandBuilder::my_rights("Archive").into_parts().0,
+            b"MYRIGHTS Archive"
+        );
+    }
+
+    #[test]
+    fn idle() {
+        assert!(CommandBuilder::idle().is_idle());
+        assert!(!CommandBuilder::noop().is_idle());
+        assert_eq!(
+            CommandBuilder::idle().into_parts(),
+            (b"IDLE".to_vec(), None)
+        );
+    }
+
+    #[test]
+    fn mailboxes() {
+        assert_eq!(
+            CommandBuilder::create("Archive/2020").into_parts().0,
+            b"CREATE Archive/2020"
+        );
+        assert_eq!(
+            CommandBuilder::delete("Old Stuff").into_parts().0,
+            b"DELETE \"Old Stuff\""
+        );
+        assert_eq!(
+            CommandBuilder::rename("Drafts", "Old Drafts")
+                .into_parts()
+                .0,
+            b"RENAME Drafts \"Old Drafts\""
+        );
+        assert_eq!(
+            CommandBuilder::subscribe("Lists").into_parts().0,
+            b"SUBSCRIBE Lists"
+        );
+        assert_eq!(
+            CommandBuilder::unsubscribe("Lists").into_parts().0,
+            b"UNSUBSCRIBE Lists"
+        );
+        assert_eq!(
+            CommandBuilder::lsub("", "Lists/%").into_parts().0,
+            b"LSUB \"\" Lists/%"
+        );
+        assert_eq!(
+            CommandBuilder::status("INBOX", &[StatusItem::Messages, StatusItem::UidNext])
+                .into_parts()
+                .0,
+            b"STATUS INBOX (MESSAGES UIDNEXT)"
+        );
+        assert_eq!(
+            CommandBuilder::status("INBOX", &[]).into_parts().0,
+            b"STATUS INBOX (MESSAGES)"
+        );
+    }
+
+    #[test]
+    fn messages() {
+        let set: SequenceSet = vec![1, 2, 3, 7].into_iter().collect();
+        assert_eq!(
+            CommandBuilder::copy(&set, "Saved Messages").into_parts().0,
+            b"COPY 1:3,7 \"Saved Messages\""
+        );
+        assert_eq!(
+            CommandBuilder::uid_copy(&set, "Trash").into_parts().0,
+            b"UID COPY 1:3,7 Trash"
+        );
+        assert_eq!(
+            CommandBuilder::mv(&set, "Archive/2020").into_parts().0,
+            b"MOVE 1:3,7 Archive/2020"
+        );
+        assert_eq!(
+            CommandBuilder::uid_mv(&SequenceSet::LastResult, "Junk Mail")
+                .into_parts()
+                .0,
+            b"UID MOVE $ \"Junk Mail\""
+        );
+        assert_eq!(
+            CommandBuilder::uid_expunge(&set).into_parts().0,
+            b"UID EXPUNGE 1:3,7"
+        );
+    }
+
+    #[test]
+    fn store() {
+        let set: SequenceSet = vec![1, 2, 3, 7].into_iter().collect();
+        assert_eq!(
+            CommandBuilder::store(&set, StoreMode::Add, &[Flag::Deleted])
+                .build()
+                .into_parts()
+                .0,
+            b"STORE 1:3,7 +FLAGS (\\Deleted)"
+        );
+        assert_eq!(
+            CommandBuilder::store(
+                &set,
+                StoreMode::Remove,
+                &[Flag::Seen, Flag::Keyword("$Junk".into())]
+            )
+            .silent()
+            .build()
+            .into_parts()
+            .0,
+            b"STORE 1:3,7 -FLAGS.SILENT (\\Seen $Junk)"
+        );
+        assert_eq!(
+            CommandBuilder::uid_store(&set, StoreMode::Replace, &[])
+                .build()
+                .into_parts()
+                .0,
+            b"UID STORE 1:3,7 FLAGS ()"
+        );
+        assert_eq!(
+            CommandBuilder::store(&set, StoreMode::Add, &[Flag::Seen])
+                .unchanged_since(320_162_338)
+                .silent()
+                .build()
+                .into_parts()
+                .0,
+            b"STORE 1:3,7 (UNCHANGEDSINCE 320162338) +FLAGS.SILENT (\\Seen)"
+        );
+        assert_eq!(
+            CommandBuilder::store(
+                &set,
+                StoreMode::Add,
+                &[
+                    Flag::Keyword("$Junk)\r\nA1 LOGOUT".into()),
+                    Flag::Extension("Seen".into()),
+                    Flag::Extension("\\Phishing".into()),
+                    Flag::MayCreate,
+                ]
+            )
+            .build()
+            .into_parts()
+            .0,
+            b"STORE 1:3,7 +FLAGS (\\Phishing)"
+        );
+        assert_eq!(
+            CommandBuilder::uid_store(&SequenceSet::LastResult, StoreMode::Add, &[Flag::Flagged])
+                .silent()
+                .build()
+                .into_parts()
+                .0,
+            b"UID STORE $ +FLAGS.SILENT (\\Flagged)"
+        );
+    }
+
+    #[test]
+    fn sort_and_thread() {
+        let criteria = [SortKey::Date.into(), SortKey::Subject.reverse()];
+        assert_eq!(
+            CommandBuilder::sort(&criteria, &SearchCriteria::All)
+                .into_parts()
+                .0,
+            b"SORT (DATE REVERSE SUBJECT) UTF-8 ALL"
+        );
+        let search = SearchCriteria::Unseen.and(SearchCriteria::Since(Date {
+            year: 2020,
+            month: 2,
+            day: 1,
+        }));
+        assert_eq!(
+            CommandBuilder::uid_sort(&[SortKey::Arrival.into()], &search)
+                .into_parts()
+                .0,
+            b"UID SORT (ARRIVAL) UTF-8 UNSEEN SINCE 1-Feb-2020"
+        );
+        assert_eq!(
+            CommandBuilder::sort(&[], &SearchCriteria::All)
+                .into_parts()
+                .0,
+            b"SORT (ARRIVAL) UTF-8 ALL"
+        );
+        assert_eq!(
+            CommandBuilder::thread(ThreadAlgorithm::References, &SearchCriteria::All)
+                .into_parts()
+                .0,
+            b"THREAD REFERENCES UTF-8 ALL"
+        );
+        assert_eq!(
+            CommandBuilder::uid_thread(ThreadAlgorithm::OrderedSubject, &search)
+                .into_parts()
+                .0,
+            b"UID THREAD ORDEREDSUBJECT UTF-8 UNSEEN SINCE 1-Feb-2020"
+        );
+    }
+
+    #[test]
+    fn search() {
+        let date = Date {
+            year: 1994,
+            month: 2,
+            day: 1,
+        };
+        let criteria = SearchBuilder::new()
+            .unseen()
+            .from("Terry Gray")
+            .since(date)
+            .header("List-Id", "")
+            .or(
+                SearchCriteria::Larger(1000),
+                SearchBuilder::new().flagged().seen().build(),
+            )
+            .not(SearchCriteria::Keyword("$Junk".into()).or(SearchCriteria::Deleted))
+            .build();
+        assert_eq!(
+            CommandBuilder::search(&criteria).build().into_parts().0,
+            &b"SEARCH UNSEEN FROM \"Terry Gray\" SINCE 1-Feb-1994 HEADER List-Id \"\" \
+               OR LARGER 1000 (FLAGGED SEEN) NOT OR KEYWORD $Junk DELETED"[..]
+        );
+
+        let set: SequenceSet = vec![1, 2, 3].into_iter().collect();
+        let criteria = SearchCriteria::Uid(set).and(SearchCriteria::Subject("Grüße".into()));
+        assert_eq!(
+            CommandBuilder::uid_search(&criteria).build().into_parts().0,
+            "UID SEARCH CHARSET UTF-8 UID 1:3 SUBJECT {7}\r\nGrüße".as_bytes()
+        );
+        let criteria = SearchCriteria::Keyword("$Junk\r\nA1 LOGOUT".into())
+            .or(SearchCriteria::Unkeyword("a b".into()));
+        assert_eq!(
+            CommandBuilder::search(&criteria).build().into_parts().0,
+            b"SEARCH OR NOT ALL ALL"
+        );
+        assert_eq!(
+            CommandBuilder::search(&SearchBuilder::new().build())
+                .charset("US-ASCII")
+                .build()
+                .into_parts()
+                .0,
+            b"SEARCH CHARSET US-ASCII ALL"
+        );
+        assert_eq!(
+            CommandBuilder::search(&!SearchCriteria::And(vec![]))
+                .build()
+                .into_parts()
+                .0,
+            b"SEARCH NOT ALL"
+        );
+    }
+
+    #[test]
+    fn search_return() {
+        let returns = [SearchReturn::Min, SearchReturn::Count, SearchReturn::Save];
+        assert_eq!(
+            CommandBuilder::uid_search(&SearchCriteria::Deleted)
+                .returns(&returns)
+                .build()
+                .into_parts()
+                .0,
+            b"UID SEARCH RETURN (MIN COUNT SAVE) DELETED"
+        );
+        let criteria = SearchBuilder::new().saved_result().subject("Grüße").build();
+        assert_eq!(
+            CommandBuilder::search(&criteria)
+                .returns(&[SearchReturn::Partial(PartialRange::First(1, 100))])
+                .build()
+                .into_parts()
+                .0,
+            "SEARCH RETURN (PARTIAL 1:100) CHARSET UTF-8 $ SUBJECT {7}\r\nGrüße".as_bytes()
+        );
+        assert_eq!(
+            CommandBuilder::search(&SearchCriteria::All)
+                .returns(&[])
+                .build()
+                .into_parts()
+                .0,
+            b"SEARCH RETURN () ALL"
+        );
+    }
+
+    #[test]
+    fn client_id() {
+        assert_eq!(
+            CommandBuilder::client_id("UUID", "6f5e9a4c-0c1b-4b8e-9d3a-25c3a0d1f2e7")
+                .into_parts()
+                .0,
+            b"CLIENTID UUID \"6f5e9a4c-0c1b-4b8e-9d3a-25c3a0d1f2e7\""
+        );
+        assert_eq!(
+            CommandBuilder::client_id("UUID\r\nA1 LOGOUT", "x")
+                .into_parts()
+                .0,
+            b"CLIENTID {15}\r\nUUID\r\nA1 LOGOUT \"x\""
+        );
+    }
+
+    #[test]
+    fn select() {
+        assert_eq!(
+            CommandBuilder::select("INBOX").into_parts().0,
+            b"SELECT INBOX"
+        );
+        assert_eq!(
+            CommandBuilder::select("Sent Items").into_parts().0,
+            b"SELECT \"Sent Items\""
+        );
+        assert_eq!(
+            CommandBuilder::examine(MailboxName::encode("Entwürfe"))
+                .into_parts()
+                .0,
+            b"EXAMINE Entw&APw-rfe"
+        );
+        assert_eq!(CommandBuilder::list("", "*").into_parts().0, b"LIST \"\" *");
+    }
+
+    #[test]
+    fn list_extended() {
+        assert_eq!(
+            CommandBuilder::list_extended("", &["*"])
+                .select(&[
+                    ListSelectOption::Subscribed,
+                    ListSelectOption::RecursiveMatch
+                ])
+                .returns(&[
+                    ListReturnOption::Children,
+                    ListReturnOption::SpecialUse,
+                    ListReturnOption::Status(vec![StatusItem::Messages, StatusItem::Unseen]),
+                ])
+                .build()
+                .into_parts()
+                .0,
+            &b"LIST (SUBSCRIBED RECURSIVEMATCH) \"\" * \
+               RETURN (CHILDREN SPECIAL-USE STATUS (MESSAGES UNSEEN))"[..]
+        );
+        assert_eq!(
+            CommandBuilder::list_extended("", &["INBOX", "Drafts", "Sent/%"])
+                .build()
+                .into_parts()
+                .0,
+            b"LIST \"\" (INBOX Drafts Sent/%)"
+        );
+        assert_eq!(
+            CommandBuilder::list_extended("Archive", &[])
+                .select(&[ListSelectOption::SpecialUse])
+                .build()
+                .into_parts()
+                .0,
+            b"LIST (SPECIAL-USE) Archive \"\""
+        );
+        assert_eq!(
+            CommandBuilder::list_extended("", &["*"])
+                .returns(&[ListReturnOption::Status(vec![])])
+                .build()
+                .into_parts()
+                .0,
+            b"LIST \"\" * RETURN (STATUS (MESSAGES))"
+        );
+    }
+
+    #[test]
+    fn select_params() {
+        assert_eq!(
+            CommandBuilder::select_with("INBOX", &[SelectParam::Condstore]).into_parts(),
+            (b"SELECT INBOX (CONDSTORE)".to_vec(), Some(State::Selected))
+        );
+        let qresync = QresyncParams {
+            uid_validity: 67_890_007,
+            mod_seq: 90_060_115_194_045_000,
+            known_uids: None,
+            seq_match: None,
+        };
+        assert_eq!(
+            CommandBuilder::examine_with("INBOX", &[SelectParam::Qresync(qresync.clone())])
+                .into_parts()
+                .0,
+            b"EXAMINE INBOX (QRESYNC (67890007 90060115194045000))"
+        );
+        let qresync = QresyncParams {
+            known_uids: Some(vec![41, 43, 44, 45].into_iter().collect()),
+            seq_match: Some((
+                vec![1, 2].into_iter().collect(),
+                vec![41, 43].into_iter().collect(),
+            )),
+            ..qresync
+        };
+        assert_eq!(
+            CommandBuilder::select_with("INBOX", &[SelectParam::Qresync(qresync)])
+                .into_parts()
+                .0,
+            &b"SELECT INBOX (QRESYNC (67890007 90060115194045000 41,43:45 \
+               (1:2 41,43)))"[..]
+        );
+    }
+
+    #[test]
+    fn partial() {
+        assert_eq!(
+            CommandBuilder::uid_fetch()
+                .all_after(1)
+                .attr(Attribute::Flags)
+                .partial(PartialRange::Last(1, 30))
+                .build()
+                .into_parts()
+                .0,
+            b"UID FETCH 1:* (FLAGS) (PARTIAL -1:-30)"
+        );
+        assert_eq!(
+            CommandBuilder::fetch()
+                .range(1, 500)
+                .attr(Attribute::Uid)
+                .partial(PartialRange::First(1, 100))
+                .build()
+                .into_parts()
+                .0,
+            b"FETCH 1:500 (UID) (PARTIAL 1:100)"
+        );
+    }
+
+    #[test]
+    fn fetch_items() {
+        assert_eq!(
+            CommandBuilder::uid_fetch()
+                .range(1, 10)
+                .items(&Fetch::new().uid().body_peek(SectionPath::part([2]), None))
+                .changed_since(7)
+                .build()
+                .into_parts()
+                .0,
+            b"UID FETCH 1:10 (UID BODY.PEEK[2]) (CHANGEDSINCE 7)"
+        );
+        assert_eq!(
+            CommandBuilder::uid_fetch()
+                .all_after(300)
+                .attr(Attribute::Flags)
+                .changed_since(12_345)
+                .vanished()
+                .build()
+                .into_parts()
+                .0,
+            b"UID FETCH 300:* (FLAGS) (CHANGEDSINCE 12345 VANISHED)"
+        );
+        assert_eq!(
+            CommandBuilder::fetch()
+                .num(1)
+                .items(&Fetch::all())
+                .build()
+                .into_parts()
+                .0,
+            b"FETCH 1 ALL"
+        );
+    }
+
+    #[test]
+    fn fetch_set() {
+        let set: SequenceSet = vec![1, 2, 3, 9].into_iter().collect();
+        assert_eq!(
+            CommandBuilder::fetch()
+                .set(&set)
+                .all_after(20)
+                .attr(Attribute::Flags)
+                .build()
+                .into_parts()
+                .0,
+            b"FETCH 1:3,9,20:* (FLAGS)"
+        );
+    }
+
+    #[test]
+    fn login() {
+        assert_eq!(
+            CommandBuilder::login("djc", "s3cr3t").into_parts().0,
+            b"LOGIN djc s3cr3t"
+        );
+        assert_eq!(
+            CommandBuilder::login("djc", "domain\\password")
+                .into_parts()
+                .0,
+            b"LOGIN djc \"domain\\\\password\""
+        );
+        assert_eq!(
+            CommandBuilder::login("djc", "\"s3\r\ncr3t").into_parts().0,
+            b"LOGIN djc {9}\r\n\"s3\r\ncr3t"
+        );
+        assert_eq!(
+            format!("{:?}", CommandBuilder::login("djc", "s3cr3t")),
+            "Command { args: LOGIN <redacted>, next_state: Some(Authenticated) }"
+        );
+    }
+
+    #[test]
+    fn append() {
+        assert_eq!(
+            CommandBuilder::append("Sent Items", b"hello")
+                .build()
+                .into_parts()
+                .0,
+            b"APPEND \"Sent Items\" {5}\r\nhello"
+        );
+        let date = DateTime::parse(" 7-Feb-1994 21:52:25 -0800").unwrap();
+        assert_eq!(
+            CommandBuilder::append("INBOX", b"hello")
+                .flags(&[Flag::Seen, Flag::Keyword("$Forwarded".into())])
+                .internal_date(date)
+                .build()
+                .into_parts()
+                .0,
+            &b"APPEND INBOX (\\Seen $Forwarded) \" 7-Feb-1994 21:52:25 -0800\" \
+               {5}\r\nhello"[..]
+        );
+        assert_eq!(
+            CommandBuilder::append("INBOX", b"hello")
+                .flags(&[Flag::Keyword("".into()), Flag::Keyword("a b".into())])
+                .build()
+                .into_parts()
+                .0,
+            b"APPEND INBOX {5}\r\nhello"
+        );
+    }
+
+    #[test]
+    fn catenate() {
+        let parts = [
+            CatenatePart::Text(Cow::Borrowed(b"Subject: Fwd\r\n\r\n")),
+            CatenatePart::Url(Cow::Borrowed("/INBOX;UIDVALIDITY=385759045/;UID=20")),
+        ];
+        let command = CommandBuilder::append_catenate("Drafts", &parts)
+            .flags(&[Flag::Draft])
+            .catenate(&parts[1..])
+            .build();
+        assert_eq!(
+            command.into_parts().0,
+            &b"APPEND Drafts (\\Draft) CATENATE (TEXT {16}\r\nSubject: Fwd\r\n\r\n \
+               URL /INBOX;UIDVALIDITY=385759045/;UID=20) \
+               CATENATE (URL /INBOX;UIDVALIDITY=385759045/;UID=20)"[..]
+        );
+    }
+
+    #[test]
+    fn multiappend() {
+        let date = DateTime::parse("17-Jul-1996 02:44:25 -0700").unwrap();
+        assert_eq!(
+            CommandBuilder::append("saved-messages", b"one")
+                .flags(&[Flag::Seen])
+                .message(b"two")
+                .message(b"three")
+                .internal_date(date)
+                .flags(&[Flag::Draft])
+                .build()
+                .into_parts()
+                .0,
+            &b"APPEND saved-messages (\\Seen) {3}\r\none {3}\r\ntwo \
+               (\\Draft) \"17-Jul-1996 02:44:25 -0700\" {5}\r\nthree"[..]
+        );
+    }
+
+    #[test]
+    fn replace() {
+        assert_eq!(
+            CommandBuilder::replace(Seq(4), "Drafts", &[], b"foo")
+                .into_parts()
+                .0,
+            b"REPLACE 4 Drafts {3}\r\nfoo"
+        );
+        assert_eq!(
+            CommandBuilder::uid_replace(Uid(25), "Drafts", &[Flag::Seen, Flag::Draft], b"foo")
+                .into_parts()
+                .0,
+            b"UID REPLACE 25 Drafts (\\Seen \\Draft) {3}\r\nfoo"
+        );
+        assert_eq!(
+            CommandBuilder::replace(Seq(1), MailboxName::encode("Entwürfe"), &[], b"foo")
+                .into_parts()
+                .0,
+            b"REPLACE 1 Entw&APw-rfe {3}\r\nfoo"
+        );
     }
 }
@@ -0,0 +1,139 @@
+//! Encoding of string arguments in commands.
+//!
+//! Each function picks the most compact form the grammar allows for the
+//! value: an atom, a quoted string, or a literal for values that can't be
+//! quoted, such as those with CR, LF or 8-bit bytes. Literals are written
+//! in their synchronizing form, `{N}\r\n` followed by the data, which the
+//! client may only send on after the server's continuation request;
+//! `Request::with_literal_mode` makes them non-synchronizing if the server
+//! supports LITERAL+ or LITERAL-.
+
+use crate::parser::core::{is_astring_char, is_atom_char, is_list_wildcards, is_quoted_specials};
+
+/// Writes an `astring`, as used for mailbox names, user names and
+/// passwords.
+///
+/// ```text
+/// astring = 1*ASTRING-CHAR / string
+/// ```
+pub fn write_astring(out: &mut Vec<u8>, value: &[u8]) {
+    if !value.is_empty() && value.iter().all(|&c| is_astring_char(c)) {
+        out.extend_from_slice(value);
+    } else {
+        write_string(out, value);
+    }
+}
+
+/// Whether `value` can be sent as an `atom`, as keywords must be. Atoms
+/// can't be quoted, so other values can't be sent at all.
+///
+/// ```text
+/// atom = 1*ATOM-CHAR
+/// ```
+pub(crate) fn is_atom(value: &[u8]) -> bool {
+    !value.is_empty() && value.iter().all(|&c| is_atom_char(c))
+}
+
+/// Writes a `list-mailbox`, the pattern of a LIST command, in which the
+/// wildcards `*` and `%` may be sent unquoted.
+///
+/// ```text
+/// list-mailbox = 1*list-char / string
+/// list-char    = ATOM-CHAR / list-wildcards / resp-specials
+/// ```
+pub fn write_list_mailbox(out: &mut Vec<u8>, value: &[u8]) {
+    if !value.is_empty()
+        && value
+            .iter()
+            .all(|&c| is_astring_char(c) || is_list_wildcards(c))
+    {
+        out.extend_from_slice(value);
+    } else {
+        write_string(out, value);
+    }
+}
+
+/// Writes a `string`, as a quoted string if the value only has 7-bit text
+/// characters, and as a literal otherwise.
+///
+/// NUL bytes can't be sent in either form; they are written in a literal,
+/// which the server will reject. Binary data needs a `literal8`, which
+/// only some commands accept (RFC 3516).
+///
+/// ```text
+/// string = quoted / literal
+/// quoted = DQUOTE *QUOTED-CHAR DQUOTE
+/// QUOTED-CHAR = <any TEXT-CHAR except quoted-specials> / "\" quoted-specials
+/// literal = "{" number "}" CRLF *CHAR8
+/// ```
+pub fn write_string(out: &mut Vec<u8>, value: &[u8]) {
+    if value
+        .iter()
+        .all(|&c| matches!(c, 0x01..=0x7f) && c != b'\r' && c != b'\n')
+    {
+        write_quoted(out, value);
+    } else {
+        write_literal(out, value);
+    }
+}
+
+fn write_quoted(out: &mut Vec<u8>, value: &[u8]) {
+    out.push(b'"');
+    for &c in value {
+        if is_quoted_specials(c) {
+            out.push(b'\\');
+        }
+        out.push(c);
+    }
+    out.push(b'"');
+}
+
+/// Writes a synchronizing literal, as needed for message data even if it
+/// could be quoted.
+///
+/// ```text
+/// literal = "{" number "}" CRLF *CHAR8
+/// ```
+pub fn write_literal(out: &mut Vec<u8>, value: &[u8]) {
+    out.extend_from_slice(format!("{{{}}}\r\n", value.len()).as_bytes());
+    out.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(write: fn(&mut Vec<u8>, &[u8]), value: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        write(&mut out, value);
+        out
+    }
+
+    #[test]
+    fn test_astring() {
+        assert_eq!(encode(write_astring, b"INBOX"), b"INBOX");
+        assert_eq!(encode(write_astring, b"a]b"), b"a]b");
+        assert_eq!(encode(write_astring, b""), b"\"\"");
+        assert_eq!(encode(write_astring, b"Sent Items"), b"\"Sent Items\"");
+        assert_eq!(encode(write_astring, b"a\"b\\c"), b"\"a\\\"b\\\\c\"");
+        assert_eq!(encode(write_astring, b"50%"), b"\"50%\"");
+    }
+
+    #[test]
+    fn test_list_mailbox() {
+        assert_eq!(encode(write_list_mailbox, b"*"), b"*");
+        assert_eq!(encode(write_list_mailbox, b"INBOX/%"), b"INBOX/%");
+        assert_eq!(encode(write_list_mailbox, b""), b"\"\"");
+        assert_eq!(encode(write_list_mailbox, b"a b*"), b"\"a b*\"");
+    }
+
+    #[test]
+    fn test_string() {
+        assert_eq!(encode(write_string, b"atom"), b"\"atom\"");
+        assert_eq!(encode(write_string, b"a\r\nb"), b"{4}\r\na\r\nb");
+        assert_eq!(
+            encode(write_string, "Grüße".as_bytes()),
+            b"{7}\r\nGr\xc3\xbc\xc3\x9fe"
+        );
+    }
+}
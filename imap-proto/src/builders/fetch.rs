@@ -0,0 +1,285 @@
+//! The data items of a FETCH command.
+//!
+//! A `Fetch` collects the items to request, such as
+//! `Fetch::new().uid().flags().body_peek(SectionPath::part([1]).text(), None)`,
+//! and is passed to `FetchCommandMessages::items`. It may also be one of the
+//! macros `ALL`, `FAST` or `FULL`; adding items to a macro replaces it with
+//! the items it stands for. A `Fetch` without any items is sent as `FAST`.
+
+use std::borrow::Cow;
+
+use super::encode::write_astring;
+use crate::types::{AttrMacro, Attribute, MessageSection, SectionPath};
+
+#[derive(Clone, Debug, Default)]
+pub struct Fetch {
+    named: Option<AttrMacro>,
+    items: Vec<Vec<u8>>,
+}
+
+impl Fetch {
+    pub fn new() -> Self {
+        Fetch {
+            named: None,
+            items: vec![],
+        }
+    }
+
+    /// The `ALL` macro: `FLAGS INTERNALDATE RFC822.SIZE ENVELOPE`.
+    pub fn all() -> Self {
+        Fetch::named(AttrMacro::All)
+    }
+
+    /// The `FAST` macro: `FLAGS INTERNALDATE RFC822.SIZE`.
+    pub fn fast() -> Self {
+        Fetch::named(AttrMacro::Fast)
+    }
+
+    /// The `FULL` macro: `FLAGS INTERNALDATE RFC822.SIZE ENVELOPE BODY`.
+    pub fn full() -> Self {
+        Fetch::named(AttrMacro::Full)
+    }
+
+    fn named(named: AttrMacro) -> Self {
+        Fetch {
+            named: Some(named),
+            items: vec![],
+        }
+    }
+
+    pub fn attr(self, attr: Attribute) -> Self {
+        self.item(attr.as_str().as_bytes().to_vec())
+    }
+
+    pub fn uid(self) -> Self {
+        self.attr(Attribute::Uid)
+    }
+
+    pub fn flags(self) -> Self {
+        self.attr(Attribute::Flags)
+    }
+
+    pub fn envelope(self) -> Self {
+        self.attr(Attribute::Envelope)
+    }
+
+    pub fn internal_date(self) -> Self {
+        self.attr(Attribute::InternalDate)
+    }
+
+    pub fn size(self) -> Self {
+        self.attr(Attribute::Rfc822Size)
+    }
+
+    pub fn body_structure(self) -> Self {
+        self.attr(Attribute::BodyStructure)
+    }
+
+    /// A body section, `BODY[section]`, which also sets `\Seen`. `None` is
+    /// the whole message. `partial` is the offset and maximum length of
+    /// the data to return.
+    pub fn body<'s>(
+        self,
+        section: impl Into<Option<SectionPath<'s>>>,
+        partial: Option<(u32, u32)>,
+    ) -> Self {
+        self.section("BODY", section.into(), partial)
+    }
+
+    /// Like `body`, but as `BODY.PEEK`, which doesn't set `\Seen`.
+    pub fn body_peek<'s>(
+        self,
+        section: impl Into<Option<SectionPath<'s>>>,
+        partial: Option<(u32, u32)>,
+    ) -> Self {
+        self.section("BODY.PEEK", section.into(), partial)
+    }
+
+    /// The part with the content transfer encoding removed, which is
+    /// empty for the whole message (RFC 3516).
+    pub fn binary_peek(self, part: &[u32], partial: Option<(u32, u32)>) -> Self {
+        let mut item = format!("BINARY.PEEK[{}]", part_number(part));
+        if let Some((offset, len)) = partial {
+            item.push_str(&format!("<{}.{}>", offset, len));
+        }
+        self.item(item.into_bytes())
+    }
+
+    /// The size of the part with the content transfer encoding removed
+    /// (RFC 3516).
+    pub fn binary_size(self, part: &[u32]) -> Self {
+        self.item(format!("BINARY.SIZE[{}]", part_number(part)).into_bytes())
+    }
+
+    // section         = "[" [section-spec] "]"
+    // section-spec    = section-msgtext / (section-part ["." section-text])
+    // section-msgtext = "HEADER" / "HEADER.FIELDS" [".NOT"] SP header-list /
+    //                   "TEXT"
+    // section-text    = section-msgtext / "MIME"
+    // header-list     = "(" header-fld-name *(SP header-fld-name) ")"
+    fn section(
+        self,
+        name: &str,
+        section: Option<SectionPath<'_>>,
+        partial: Option<(u32, u32)>,
+    ) -> Self {
+        let mut item = format!("{}[", name).into_bytes();
+        let text = match &section {
+            None => None,
+            Some(SectionPath::Full(text)) => Some(text),
+            Some(SectionPath::Part(path, text)) => {
+                item.extend(part_number(path).as_bytes());
+                if !path.is_empty() && text.is_some() {
+                    item.push(b'.');
+                }
+                text.as_ref()
+            }
+        };
+        match text {
+            None => {}
+            Some(MessageSection::Header) => item.extend(b"HEADER"),
+            Some(MessageSection::HeaderFields(fields)) => {
+                item.extend(b"HEADER.FIELDS ");
+                header_list(&mut item, fields);
+            }
+            Some(MessageSection::HeaderFieldsNot(fields)) => {
+                item.extend(b"HEADER.FIELDS.NOT ");
+                header_list(&mut item, fields);
+            }
+            Some(MessageSection::Mime) => item.extend(b"MIME"),
+            Some(MessageSection::Text) => item.extend(b"TEXT"),
+        }
+        item.push(b']');
+        if let Some((offset, len)) = partial {
+            item.extend(format!("<{}.{}>", offset, len).as_bytes());
+        }
+        self.item(item)
+    }
+
+    fn item(mut self, item: Vec<u8>) -> Self {
+        if let Some(named) = self.named.take() {
+            let expanded: &[Attribute] = match named {
+                AttrMacro::All => &ALL,
+                AttrMacro::Fast => &ALL[..3],
+                AttrMacro::Full => &FULL,
+            };
+            self.items = expanded
+                .iter()
+                .map(|attr| attr.as_str().as_bytes().to_vec())
+                .collect();
+        }
+        self.items.push(item);
+        self
+    }
+
+    // fetch = "FETCH" SP sequence-set SP ("ALL" / "FULL" / "FAST" /
+    //         fetch-att / "(" fetch-att *(SP fetch-att) ")")
+    // At least one item has to be sent, so no items at all is sent as FAST.
+    pub(crate) fn encode(&self, out: &mut Vec<u8>) {
+        match self.named {
+            Some(AttrMacro::All) => out.extend(b"ALL"),
+            Some(AttrMacro::Fast) => out.extend(b"FAST"),
+            Some(AttrMacro::Full) => out.extend(b"FULL"),
+            None if self.items.is_empty() => out.extend(b"FAST"),
+            None => {
+                out.push(b'(');
+                out.extend(self.items.join(&b' '));
+                out.push(b')');
+            }
+        }
+    }
+}
+
+const ALL: [Attribute; 4] = [
+    Attribute::Flags,
+    Attribute::InternalDate,
+    Attribute::Rfc822Size,
+    Attribute::Envelope,
+];
+
+const FULL: [Attribute; 5] = [
+    Attribute::Flags,
+    Attribute::InternalDate,
+    Attribute::Rfc822Size,
+    Attribute::Envelope,
+    Attribute::Body,
+];
+
+// section-part    = nz-number *("." nz-number)
+fn part_number(path: &[u32]) -> String {
+    let parts = path.iter().map(u32::to_string).collect::<Vec<_>>();
+    parts.join(".")
+}
+
+fn header_list(out: &mut Vec<u8>, fields: &[Cow<'_, str>]) {
+    out.push(b'(');
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(b' ');
+        }
+        write_astring(out, field.as_bytes());
+    }
+    out.push(b')');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fetch;
+    use crate::types::{MessageSection, SectionPath};
+
+    fn encode(fetch: Fetch) -> String {
+        let mut out = vec![];
+        fetch.encode(&mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_items() {
+        assert_eq!(
+            encode(Fetch::new().uid().flags().envelope().body_peek(
+                SectionPath::part([1, 2]).header_fields(["SUBJECT"]),
+                Some((0, 1024))
+            )),
+            "(UID FLAGS ENVELOPE BODY.PEEK[1.2.HEADER.FIELDS (SUBJECT)]<0.1024>)"
+        );
+        assert_eq!(
+            encode(
+                Fetch::new()
+                    .body(None, None)
+                    .body_peek(SectionPath::Full(MessageSection::Header), None)
+                    .body_peek(SectionPath::part([3]), None)
+                    .body_peek(SectionPath::part([3]).mime(), None)
+                    .body_peek(
+                        SectionPath::part([]).header_fields_not(["Received", "X-A B"]),
+                        None
+                    )
+            ),
+            "(BODY[] BODY.PEEK[HEADER] BODY.PEEK[3] BODY.PEEK[3.MIME] \
+             BODY.PEEK[HEADER.FIELDS.NOT (Received \"X-A B\")])"
+        );
+        assert_eq!(
+            encode(
+                Fetch::new()
+                    .binary_peek(&[1, 1], Some((0, 10)))
+                    .binary_size(&[])
+            ),
+            "(BINARY.PEEK[1.1]<0.10> BINARY.SIZE[])"
+        );
+    }
+
+    #[test]
+    fn test_macros() {
+        assert_eq!(encode(Fetch::all()), "ALL");
+        assert_eq!(encode(Fetch::fast()), "FAST");
+        assert_eq!(encode(Fetch::full()), "FULL");
+        assert_eq!(encode(Fetch::new()), "FAST");
+        assert_eq!(
+            encode(Fetch::fast().uid()),
+            "(FLAGS INTERNALDATE RFC822.SIZE UID)"
+        );
+        assert_eq!(
+            encode(Fetch::full().body_structure()),
+            "(FLAGS INTERNALDATE RFC822.SIZE ENVELOPE BODY BODYSTRUCTURE)"
+        );
+    }
+}
@@ -1 +1,4 @@
 pub mod command;
+pub mod encode;
+pub mod fetch;
+pub mod search;
@@ -0,0 +1,174 @@
+//! A fluent way to build `SearchCriteria`.
+//!
+//! Each method adds a search key that messages must match, so that
+//! `SearchBuilder::new().unseen().from("alice").build()` finds the unseen
+//! messages from alice. `not` and `or` take criteria that may come from
+//! other builders.
+
+use std::borrow::Cow;
+
+use crate::types::{Date, SearchCriteria, SequenceSet};
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchBuilder<'a> {
+    keys: Vec<SearchCriteria<'a>>,
+}
+
+impl<'a> SearchBuilder<'a> {
+    pub fn new() -> Self {
+        SearchBuilder { keys: vec![] }
+    }
+
+    /// Adds any search key, such as `SearchCriteria::New`.
+    pub fn key(mut self, key: SearchCriteria<'a>) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    pub fn answered(self) -> Self {
+        self.key(SearchCriteria::Answered)
+    }
+
+    pub fn unanswered(self) -> Self {
+        self.key(SearchCriteria::Unanswered)
+    }
+
+    pub fn deleted(self) -> Self {
+        self.key(SearchCriteria::Deleted)
+    }
+
+    pub fn undeleted(self) -> Self {
+        self.key(SearchCriteria::Undeleted)
+    }
+
+    pub fn draft(self) -> Self {
+        self.key(SearchCriteria::Draft)
+    }
+
+    pub fn undraft(self) -> Self {
+        self.key(SearchCriteria::Undraft)
+    }
+
+    pub fn flagged(self) -> Self {
+        self.key(SearchCriteria::Flagged)
+    }
+
+    pub fn unflagged(self) -> Self {
+        self.key(SearchCriteria::Unflagged)
+    }
+
+    pub fn seen(self) -> Self {
+        self.key(SearchCriteria::Seen)
+    }
+
+    pub fn unseen(self) -> Self {
+        self.key(SearchCriteria::Unseen)
+    }
+
+    pub fn keyword(self, keyword: impl Into<Cow<'a, str>>) -> Self {
+        self.key(SearchCriteria::Keyword(keyword.into()))
+    }
+
+    pub fn unkeyword(self, keyword: impl Into<Cow<'a, str>>) -> Self {
+        self.key(SearchCriteria::Unkeyword(keyword.into()))
+    }
+
+    pub fn bcc(self, s: impl Into<Cow<'a, str>>) -> Self {
+        self.key(SearchCriteria::Bcc(s.into()))
+    }
+
+    pub fn body(self, s: impl Into<Cow<'a, str>>) -> Self {
+        self.key(SearchCriteria::Body(s.into()))
+    }
+
+    pub fn cc(self, s: impl Into<Cow<'a, str>>) -> Self {
+        self.key(SearchCriteria::Cc(s.into()))
+    }
+
+    pub fn from(self, s: impl Into<Cow<'a, str>>) -> Self {
+        self.key(SearchCriteria::From(s.into()))
+    }
+
+    pub fn subject(self, s: impl Into<Cow<'a, str>>) -> Self {
+        self.key(SearchCriteria::Subject(s.into()))
+    }
+
+    pub fn text(self, s: impl Into<Cow<'a, str>>) -> Self {
+        self.key(SearchCriteria::Text(s.into()))
+    }
+
+    pub fn to(self, s: impl Into<Cow<'a, str>>) -> Self {
+        self.key(SearchCriteria::To(s.into()))
+    }
+
+    pub fn header(self, name: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) -> Self {
+        self.key(SearchCriteria::Header(name.into(), value.into()))
+    }
+
+    pub fn before(self, date: Date) -> Self {
+        self.key(SearchCriteria::Before(date))
+    }
+
+    pub fn on(self, date: Date) -> Self {
+        self.key(SearchCriteria::On(date))
+    }
+
+    pub fn since(self, date: Date) -> Self {
+        self.key(SearchCriteria::Since(date))
+    }
+
+    pub fn sent_before(self, date: Date) -> Self {
+        self.key(SearchCriteria::SentBefore(date))
+    }
+
+    pub fn sent_on(self, date: Date) -> Self {
+        self.key(SearchCriteria::SentOn(date))
+    }
+
+    pub fn sent_since(self, date: Date) -> Self {
+        self.key(SearchCriteria::SentSince(date))
+    }
+
+    pub fn larger(self, octets: u32) -> Self {
+        self.key(SearchCriteria::Larger(octets))
+    }
+
+    pub fn smaller(self, octets: u32) -> Self {
+        self.key(SearchCriteria::Smaller(octets))
+    }
+
+    /// Only messages with the given sequence numbers.
+    pub fn seq(self, set: SequenceSet) -> Self {
+        self.key(SearchCriteria::SequenceSet(set))
+    }
+
+    /// Only messages with the given UIDs.
+    pub fn uid(self, set: SequenceSet) -> Self {
+        self.key(SearchCriteria::Uid(set))
+    }
+
+    /// Only messages found by the last search that saved its result.
+    pub fn saved_result(self) -> Self {
+        self.seq(SequenceSet::LastResult)
+    }
+
+    /// Only messages that don't match `criteria`.
+    pub fn not(self, criteria: SearchCriteria<'a>) -> Self {
+        self.key(!criteria)
+    }
+
+    /// Only messages that match `a` or `b`.
+    pub fn or(self, a: SearchCriteria<'a>, b: SearchCriteria<'a>) -> Self {
+        self.key(a.or(b))
+    }
+
+    /// The criteria that match all of the keys, or all messages if no keys
+    /// were added.
+    pub fn build(mut self) -> SearchCriteria<'a> {
+        match self.keys.len() {
+            0 => SearchCriteria::All,
+            1 => self.keys.remove(0),
+            _ => SearchCriteria::And(self.keys),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+
+use nom::{error::ErrorKind, Needed};
+
+use crate::parser::{ParseError, Parsed};
+
+/// An error from parsing a response that does not borrow the input, so that
+/// it can be returned with `?` from functions using other error types.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input is not a valid response.
+    Parse(ParseError<'static>),
+    /// The input ends inside a response. `needed` is the number of octets
+    /// that are known to be missing, such as the rest of a literal; see
+    /// `Parsed::Incomplete`.
+    IncompleteData { needed: Option<usize> },
+    /// A literal announces a length that does not fit in 32 bits.
+    InvalidLiteralLength,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(err) => write!(f, "invalid response: {}", err),
+            Error::IncompleteData { needed: Some(n) } => {
+                write!(f, "incomplete response: {} more bytes needed", n)
+            }
+            Error::IncompleteData { needed: None } => write!(f, "incomplete response"),
+            Error::InvalidLiteralLength => write!(f, "literal length out of range"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError<'_>> for Error {
+    fn from(err: ParseError<'_>) -> Self {
+        match err.kind() {
+            ErrorKind::TooLarge => Error::InvalidLiteralLength,
+            _ => Error::Parse(err.into_owned()),
+        }
+    }
+}
+
+impl From<nom::Err<ParseError<'_>>> for Error {
+    fn from(err: nom::Err<ParseError<'_>>) -> Self {
+        match err {
+            nom::Err::Incomplete(Needed::Size(n)) => Error::IncompleteData { needed: Some(n) },
+            nom::Err::Incomplete(Needed::Unknown) => Error::IncompleteData { needed: None },
+            nom::Err::Error(err) | nom::Err::Failure(err) => err.into(),
+        }
+    }
+}
+
+impl<'a> Parsed<'a> {
+    /// Converts the result into one that can be used with `?`, reporting
+    /// incomplete input as `Error::IncompleteData`.
+    pub fn into_result(self) -> Result<(&'a [u8], crate::types::Response<'a>), Error> {
+        match self {
+            Parsed::Complete(rest, rsp) => Ok((rest, rsp)),
+            Parsed::Incomplete(needed) => Err(nom::Err::Incomplete(needed).into()),
+            Parsed::Error(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use crate::parser::rfc3501::{parse_response, parse_response_incremental};
+
+    #[test]
+    fn test_error() {
+        fn parse(input: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+            parse_response_incremental(input).into_result()?;
+            Ok(())
+        }
+        assert!(parse(b"* OK done\r\n").is_ok());
+        let msg = parse(b"* FOO\r\n").unwrap_err().to_string();
+        assert!(msg.starts_with("invalid response: "), "{}", msg);
+
+        let err = |input: &[u8]| Error::from(parse_response(input).unwrap_err());
+        assert_eq!(
+            err(b"* 1 FETCH (RFC822 {10}\r\nab"),
+            Error::IncompleteData { needed: Some(8) }
+        );
+        assert_eq!(
+            parse_response_incremental(b"* OK").into_result(),
+            Err(Error::IncompleteData { needed: None })
+        );
+        assert_eq!(
+            err(b"* 1 FETCH (RFC822 {99999999999}\r\n"),
+            Error::InvalidLiteralLength
+        );
+    }
+}
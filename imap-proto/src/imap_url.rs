@@ -0,0 +1,334 @@
+//!
+//! https://tools.ietf.org/html/rfc5092
+//!
+//! IMAP URL Scheme
+//!
+
+use std::str::FromStr;
+
+/// A parsed `imap://` URL, such as
+/// `imap://user@host/INBOX;UIDVALIDITY=3/;UID=20/;SECTION=1.2`.
+///
+/// Percent-encoded components are decoded. URLs naming a server, a mailbox
+/// (optionally with a search), or a message or message part are supported.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ImapUrl {
+    pub user: Option<String>,
+    /// The `;AUTH=` mechanism, `*` meaning any.
+    pub auth: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+    pub mailbox: Option<String>,
+    pub uid_validity: Option<u32>,
+    pub search: Option<String>,
+    pub uid: Option<u32>,
+    pub section: Option<String>,
+    /// The offset and optional length of a partial fetch.
+    pub partial: Option<(u32, Option<u32>)>,
+    pub url_auth: Option<UrlAuth>, // RFC 4467, section 3
+}
+
+/// The authorization part of a URLAUTH-authorized URL (RFC 4467).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UrlAuth {
+    /// The expiry date-time, in ISO 8601 format.
+    pub expire: Option<String>,
+    /// The access identifier, such as `anonymous` or `submit+user`.
+    pub access: String,
+    /// The authorization mechanism, such as `INTERNAL`, and the token.
+    pub verifier: Option<(String, String)>,
+}
+
+impl ImapUrl {
+    /// Parses an absolute `imap://` URL, returning `None` if it is malformed.
+    pub fn parse(s: &str) -> Option<Self> {
+        let rest = strip_prefix_ignore_case(s, "imap://")?;
+        let (server, path) = match rest.find('/') {
+            Some(n) => (&rest[..n], Some(&rest[n + 1..])),
+            None => (rest, None),
+        };
+
+        let mut url = ImapUrl::default();
+        parse_server(server, &mut url)?;
+        if let Some(path) = path.filter(|path| !path.is_empty()) {
+            parse_path(path, &mut url)?;
+        }
+        Some(url)
+    }
+}
+
+impl FromStr for ImapUrl {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        ImapUrl::parse(s).ok_or(())
+    }
+}
+
+// iserver   = [iuserinfo "@"] host [":" port]
+// iuserinfo = enc-user [iauth] / [enc-user] iauth
+// iauth     = ";AUTH=" ( "*" / enc-auth-type )
+fn parse_server(s: &str, url: &mut ImapUrl) -> Option<()> {
+    let hostport = match s.rfind('@') {
+        Some(n) => {
+            let userinfo = &s[..n];
+            let (user, auth) = match find_ignore_case(userinfo, ";AUTH=") {
+                Some(n) => (&userinfo[..n], Some(&userinfo[n + 6..])),
+                None => (userinfo, None),
+            };
+            if !user.is_empty() {
+                url.user = Some(percent_decode(user)?);
+            }
+            if let Some(auth) = auth {
+                url.auth = Some(percent_decode(auth).filter(|auth| !auth.is_empty())?);
+            }
+            &s[n + 1..]
+        }
+        None => s,
+    };
+
+    // An IPv6 address literal contains colons, so look for the port after it.
+    let port_start = match hostport.strip_prefix('[') {
+        Some(rest) => rest.find(']')? + 2,
+        None => 0,
+    };
+    let (host, port) = match hostport[port_start..].find(':') {
+        Some(n) => (
+            &hostport[..port_start + n],
+            Some(&hostport[port_start + n + 1..]),
+        ),
+        None => (hostport, None),
+    };
+    if host.is_empty() {
+        return None;
+    }
+    url.host = percent_decode(host)?;
+    if let Some(port) = port {
+        url.port = Some(port.parse().ok()?);
+    }
+    Some(())
+}
+
+// ipath-query    = ["/" [ icommand / ipath-empty ]]
+// icommand       = imessagelist / imessagepart [iurlauth]
+// imessagelist   = imailbox-ref [ "?" enc-search ]
+// imessagepart   = imailbox-ref iuid [isection] [ipartial]
+// imailbox-ref   = enc-mailbox [uidvalidity]
+// uidvalidity    = ";UIDVALIDITY=" nz-number
+// iuid           = "/" ";UID=" nz-number
+// isection       = "/" ";SECTION=" enc-section
+// ipartial       = "/" ";PARTIAL=" partial-range
+// Mailbox names may contain "/", so the path is split at "/;" instead.
+fn parse_path(path: &str, url: &mut ImapUrl) -> Option<()> {
+    let mut parts = path.split("/;");
+    let mailbox_ref = parts.next()?;
+    let (mailbox_ref, search) = match mailbox_ref.find('?') {
+        Some(n) => (&mailbox_ref[..n], Some(&mailbox_ref[n + 1..])),
+        None => (mailbox_ref, None),
+    };
+    let (mailbox, uid_validity) = match find_ignore_case(mailbox_ref, ";UIDVALIDITY=") {
+        Some(n) => (&mailbox_ref[..n], Some(&mailbox_ref[n + 13..])),
+        None => (mailbox_ref, None),
+    };
+    if mailbox.is_empty() {
+        return None;
+    }
+    url.mailbox = Some(percent_decode(mailbox)?);
+    if let Some(uid_validity) = uid_validity {
+        url.uid_validity = Some(nz_number(uid_validity)?);
+    }
+    if let Some(search) = search {
+        url.search = Some(percent_decode(search)?);
+    }
+
+    let mut parts = parts.peekable();
+    while let Some(mut part) = parts.next() {
+        if url.search.is_some() {
+            return None;
+        }
+        if parts.peek().is_none() {
+            part = parse_url_auth(part, url)?;
+        }
+        let (name, value) = split_param(part)?;
+        if name.eq_ignore_ascii_case("UID") && url.uid.is_none() {
+            url.uid = Some(nz_number(value)?);
+        } else if name.eq_ignore_ascii_case("SECTION") && url.uid.is_some() {
+            url.section = Some(percent_decode(value)?);
+        } else if name.eq_ignore_ascii_case("PARTIAL") && url.uid.is_some() {
+            let (offset, length) = match value.find('.') {
+                Some(n) => (&value[..n], Some(nz_number(&value[n + 1..])?)),
+                None => (value, None),
+            };
+            url.partial = Some((offset.parse().ok()?, length));
+        } else {
+            return None;
+        }
+    }
+    Some(())
+}
+
+// iurlauth       = iurlauth-rump iua-verifier
+// iurlauth-rump  = [expire] ";URLAUTH=" access
+// iua-verifier   = ":" uauth-mechanism ":" enc-urlauth
+// expire         = ";EXPIRE=" date-time
+// Returns the part of the last path component before the URLAUTH data.
+fn parse_url_auth<'a>(part: &'a str, url: &mut ImapUrl) -> Option<&'a str> {
+    let (part, expire) = match find_ignore_case(part, ";EXPIRE=") {
+        Some(n) => (&part[..n], Some(&part[n + 8..])),
+        None => (part, None),
+    };
+    let (part, rump) = match expire {
+        Some(expire) => (part, expire),
+        None => match find_ignore_case(part, ";URLAUTH=") {
+            Some(n) => (&part[..n], &part[n..]),
+            None => return Some(part),
+        },
+    };
+
+    let n = find_ignore_case(rump, ";URLAUTH=")?;
+    let expire = expire.map(|_| rump[..n].to_string());
+    let mut access = rump[n + 9..].splitn(3, ':');
+    let access_id = percent_decode(access.next()?)?;
+    let verifier = match (access.next(), access.next()) {
+        (Some(mechanism), Some(token)) => Some((mechanism.to_string(), token.to_string())),
+        (None, None) => None,
+        _ => return None,
+    };
+    url.url_auth = Some(UrlAuth {
+        expire,
+        access: access_id,
+        verifier,
+    });
+    Some(part)
+}
+
+fn split_param(part: &str) -> Option<(&str, &str)> {
+    let n = part.find('=')?;
+    Some((&part[..n], &part[n + 1..]))
+}
+
+fn nz_number(s: &str) -> Option<u32> {
+    if !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok().filter(|&n| n != 0)
+}
+
+fn find_ignore_case(s: &str, needle: &str) -> Option<usize> {
+    s.as_bytes()
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    match s.get(..prefix.len()) {
+        Some(start) if start.eq_ignore_ascii_case(prefix) => Some(&s[prefix.len()..]),
+        _ => None,
+    }
+}
+
+// Decodes %-escapes, failing on malformed escapes or if the result is not
+// valid UTF-8.
+fn percent_decode(s: &str) -> Option<String> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            out.push(b);
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_part() {
+        assert_eq!(
+            ImapUrl::parse("imap://user@host/INBOX;UIDVALIDITY=3/;UID=20/;SECTION=1.2"),
+            Some(ImapUrl {
+                user: Some("user".to_string()),
+                host: "host".to_string(),
+                mailbox: Some("INBOX".to_string()),
+                uid_validity: Some(3),
+                uid: Some(20),
+                section: Some("1.2".to_string()),
+                ..ImapUrl::default()
+            })
+        );
+
+        let url = ImapUrl::parse("imap://psicorp.example.org/~peter/%E6%97%A5%E6%9C%AC%E8%AA%9E/%E5%8F%B0%E5%8C%97;UIDVALIDITY=1/;UID=4/;PARTIAL=1024.512").unwrap();
+        assert_eq!(url.mailbox.as_deref(), Some("~peter/日本語/台北"));
+        assert_eq!(url.uid, Some(4));
+        assert_eq!(url.partial, Some((1024, Some(512))));
+    }
+
+    #[test]
+    fn test_server() {
+        let url = ImapUrl::parse("imap://fred;AUTH=*@[2001:db8::1]:1143").unwrap();
+        assert_eq!(url.user.as_deref(), Some("fred"));
+        assert_eq!(url.auth.as_deref(), Some("*"));
+        assert_eq!(url.host, "[2001:db8::1]");
+        assert_eq!(url.port, Some(1143));
+        assert_eq!(url.mailbox, None);
+
+        let url = ImapUrl::parse("IMAP://;AUTH=GSSAPI@minbari.example.org/").unwrap();
+        assert_eq!(url.user, None);
+        assert_eq!(url.auth.as_deref(), Some("GSSAPI"));
+        assert_eq!(url.host, "minbari.example.org");
+    }
+
+    #[test]
+    fn test_search() {
+        let url = ImapUrl::parse("imap://michael@example.org/INBOX?SUBJECT%20shadows").unwrap();
+        assert_eq!(url.mailbox.as_deref(), Some("INBOX"));
+        assert_eq!(url.search.as_deref(), Some("SUBJECT shadows"));
+    }
+
+    #[test]
+    fn test_url_auth() {
+        let url = ImapUrl::parse(
+            "imap://joe@example.com/INBOX/;uid=20/;section=1.2;urlauth=submit+fred:internal:91354a473744909de610943775f92038",
+        )
+        .unwrap();
+        assert_eq!(url.uid, Some(20));
+        assert_eq!(url.section.as_deref(), Some("1.2"));
+        assert_eq!(
+            url.url_auth,
+            Some(UrlAuth {
+                expire: None,
+                access: "submit+fred".to_string(),
+                verifier: Some((
+                    "internal".to_string(),
+                    "91354a473744909de610943775f92038".to_string()
+                )),
+            })
+        );
+
+        let url = ImapUrl::parse(
+            "imap://joe@example.com/INBOX/;uid=20;expire=2006-10-20T00:00:00Z;urlauth=anonymous",
+        )
+        .unwrap();
+        let url_auth = url.url_auth.unwrap();
+        assert_eq!(url_auth.expire.as_deref(), Some("2006-10-20T00:00:00Z"));
+        assert_eq!(url_auth.access, "anonymous");
+        assert_eq!(url_auth.verifier, None);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(ImapUrl::parse("http://example.org/"), None);
+        assert_eq!(ImapUrl::parse("imap:///INBOX"), None);
+        assert_eq!(ImapUrl::parse("imap://host:port/"), None);
+        assert_eq!(ImapUrl::parse("imap://host/INBOX/;UID=0"), None);
+        assert_eq!(ImapUrl::parse("imap://host/INBOX/;SECTION=1"), None);
+        assert_eq!(ImapUrl::parse("imap://host/INBOX/;UID=1/;FOO=2"), None);
+        assert_eq!(ImapUrl::parse("imap://host/%zz"), None);
+    }
+}
@@ -1,6 +1,22 @@
 pub mod builders;
+mod error;
+pub mod imap_url;
+#[cfg(feature = "mime")]
+mod mime;
 pub mod parser;
 pub mod types;
+pub mod utf7;
 
-pub use crate::parser::{rfc3501::parse_response, rfc5464::resp_metadata, ParseResult};
+pub use crate::error::Error;
+pub use crate::imap_url::ImapUrl;
+#[cfg(feature = "metadata")]
+pub use crate::parser::rfc5464::resp_metadata;
+pub use crate::parser::{
+    rfc3501::{
+        parse_greeting, parse_response, parse_response_complete, parse_response_incremental,
+        parse_response_incremental_with, parse_response_with, parse_responses_complete,
+    },
+    Options, ParseError, ParseResult, Parsed,
+};
 pub use crate::types::*;
+pub use crate::utf7::{decode_utf7_imap, encode_utf7_imap};
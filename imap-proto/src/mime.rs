@@ -0,0 +1,306 @@
+//! Decoding of MIME header extensions, as found in envelope fields and
+//! body structure parameters.
+//!
+//! https://tools.ietf.org/html/rfc2047
+//!
+//! MIME Part Three: Message Header Extensions for Non-ASCII Text
+//!
+//! https://tools.ietf.org/html/rfc2231
+//!
+//! MIME Parameter Value and Encoded Word Extensions: Character Sets,
+//! Languages, and Continuations
+//!
+
+use std::borrow::Cow;
+
+use crate::parser::core::decode_base64;
+
+/// Decodes the encoded-words in a header value, such as the display name of
+/// an address, into a string. Whitespace between adjacent encoded-words is
+/// dropped, as RFC 2047 requires. Words in a charset that can't be decoded
+/// are left as they are, and other invalid UTF-8 is replaced.
+pub(crate) fn decode_encoded_words(raw: &[u8]) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    let mut after_word = false;
+    while let Some(start) = rest.windows(2).position(|w| w == b"=?") {
+        let (before, word) = rest.split_at(start);
+        match encoded_word(word) {
+            Some((decoded, len)) => {
+                if !(after_word && before.iter().all(u8::is_ascii_whitespace)) {
+                    out.push_str(&String::from_utf8_lossy(before));
+                }
+                out.push_str(&decoded);
+                rest = &word[len..];
+                after_word = true;
+            }
+            None => {
+                out.push_str(&String::from_utf8_lossy(&rest[..start + 2]));
+                rest = &word[2..];
+                after_word = false;
+            }
+        }
+    }
+    out.push_str(&String::from_utf8_lossy(rest));
+    out
+}
+
+// encoded-word = "=?" charset "?" encoding "?" encoded-text "?="
+// Returns the decoded text and the length of the encoded-word.
+fn encoded_word(word: &[u8]) -> Option<(String, usize)> {
+    let inner = &word[2..];
+    let charset_len = inner.iter().position(|&c| c == b'?')?;
+    let (charset, rest) = inner.split_at(charset_len);
+    let (encoding, text) = match rest {
+        [b'?', encoding, b'?', text @ ..] => (encoding, text),
+        _ => return None,
+    };
+    let text_len = text.windows(2).position(|w| w == b"?=")?;
+    let text = &text[..text_len];
+    if charset.is_empty() || charset.iter().chain(text).any(u8::is_ascii_whitespace) {
+        return None;
+    }
+
+    let bytes = match encoding.to_ascii_uppercase() {
+        b'B' => decode_base64(std::str::from_utf8(text).ok()?)?,
+        b'Q' => decode_q(text)?,
+        _ => return None,
+    };
+    // RFC 2231 allows a language suffix, as in `UTF-8*en`.
+    let charset = charset.split(|&c| c == b'*').next()?;
+    let decoded = decode_charset(charset, &bytes)?;
+    Some((decoded, 2 + charset_len + 3 + text_len + 2))
+}
+
+// The "Q" encoding: like quoted-printable, with "_" standing for a space.
+fn decode_q(text: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut iter = text.iter();
+    while let Some(&c) = iter.next() {
+        match c {
+            b'_' => out.push(b' '),
+            b'=' => {
+                let hex = [*iter.next()?, *iter.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            _ => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+fn decode_charset(charset: &[u8], bytes: &[u8]) -> Option<String> {
+    let charset = std::str::from_utf8(charset).ok()?.to_ascii_lowercase();
+    match charset.as_str() {
+        "utf-8" | "utf8" | "us-ascii" => Some(String::from_utf8_lossy(bytes).into_owned()),
+        "iso-8859-1" | "latin1" => Some(bytes.iter().map(|&b| char::from(b)).collect()),
+        "windows-1252" | "cp1252" => Some(bytes.iter().map(|&b| windows_1252(b)).collect()),
+        _ => None,
+    }
+}
+
+/// Looks up a body parameter, ignoring case in the name. Values split into
+/// continuations (`name*0`, `name*1`, ...) are joined and extended values
+/// (`name*=utf-8''...`) are decoded, as RFC 2231 describes, and take
+/// precedence over a plain `name`. Encoded-words in plain values are
+/// decoded as well, since many clients send file names that way.
+pub(crate) fn find_param<'p>(
+    params: &'p [(Cow<'_, str>, Cow<'_, [u8]>)],
+    name: &str,
+) -> Option<Cow<'p, str>> {
+    let mut plain = None;
+    let mut parts = vec![];
+    for (key, value) in params {
+        if key.eq_ignore_ascii_case(name) {
+            plain = plain.or(Some(value.as_ref()));
+        } else if let Some((index, encoded)) = param_section(key, name) {
+            parts.push((index, encoded, value.as_ref()));
+        }
+    }
+    if let Some(value) = join_sections(parts) {
+        return Some(Cow::Owned(value));
+    }
+    let plain = plain?;
+    if plain.windows(2).any(|w| w == b"=?") {
+        Some(Cow::Owned(decode_encoded_words(plain)))
+    } else {
+        Some(String::from_utf8_lossy(plain))
+    }
+}
+
+// parameter-name = attribute [section] ["*"]
+// section        = "*" number
+// Returns the section number and whether the value is encoded, for a key
+// such as `filename*1*`. A key of `filename*` is an encoded section 0.
+fn param_section(key: &str, name: &str) -> Option<(u32, bool)> {
+    if !key.is_char_boundary(name.len()) {
+        return None;
+    }
+    let (attribute, rest) = key.split_at(name.len());
+    if !attribute.eq_ignore_ascii_case(name) {
+        return None;
+    }
+    let rest = rest.strip_prefix('*')?;
+    if rest.is_empty() {
+        return Some((0, true));
+    }
+    let (number, encoded) = match rest.strip_suffix('*') {
+        Some(number) => (number, true),
+        None => (rest, false),
+    };
+    if number.is_empty() || !number.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((number.parse().ok()?, encoded))
+}
+
+// extended-initial-value = [charset] "'" [language] "'" extended-other-values
+// Joins the sections in order, stopping at the first one that's missing,
+// and decodes the result in the charset given in the first section.
+fn join_sections(mut parts: Vec<(u32, bool, &[u8])>) -> Option<String> {
+    parts.sort_by_key(|&(index, _, _)| index);
+    if parts.first()?.0 != 0 {
+        return None;
+    }
+    let mut charset = &b""[..];
+    let mut bytes = vec![];
+    for (n, &(index, encoded, value)) in parts.iter().enumerate() {
+        if index as usize != n {
+            break;
+        }
+        if !encoded {
+            bytes.extend_from_slice(value);
+            continue;
+        }
+        let mut value = value;
+        if n == 0 {
+            let mut fields = value.splitn(3, |&c| c == b'\'');
+            if let (Some(name), Some(_), Some(rest)) = (fields.next(), fields.next(), fields.next())
+            {
+                charset = name;
+                value = rest;
+            }
+        }
+        decode_percent(value, &mut bytes);
+    }
+    Some(
+        decode_charset(charset, &bytes)
+            .unwrap_or_else(|| String::from_utf8_lossy(&bytes).into_owned()),
+    )
+}
+
+// Decodes `%` followed by two hex digits, keeping anything else as it is.
+fn decode_percent(value: &[u8], out: &mut Vec<u8>) {
+    let mut i = 0;
+    while i < value.len() {
+        let byte = match value[i..] {
+            [b'%', hi, lo, ..] => std::str::from_utf8(&[hi, lo])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match byte {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(value[i]);
+                i += 1;
+            }
+        }
+    }
+}
+
+// Windows-1252 is Latin-1 with printable characters in place of most of the
+// C1 controls. Mail labelled as Latin-1 often uses these too.
+fn windows_1252(b: u8) -> char {
+    const C1: [char; 32] = [
+        '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}',
+        '\u{2021}', '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}',
+        '\u{8f}', '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}',
+        '\u{2014}', '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}',
+        '\u{178}',
+    ];
+    match b {
+        0x80..=0x9f => C1[usize::from(b - 0x80)],
+        _ => char::from(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_encoded_words() {
+        let decode = |s: &str| decode_encoded_words(s.as_bytes());
+        assert_eq!(decode("Terry Gray"), "Terry Gray");
+        assert_eq!(decode("=?UTF-8?B?SsO2cmc=?= Meier"), "Jörg Meier");
+        assert_eq!(decode("=?iso-8859-1?q?J=F6rg_Meier?="), "Jörg Meier");
+        assert_eq!(decode("=?UTF-8?Q?a?= \r\n =?UTF-8?Q?b?="), "ab");
+        assert_eq!(decode("=?UTF-8?Q?a?= b =?UTF-8?Q?c?="), "a b c");
+        assert_eq!(decode("=?UTF-8*de?Q?Gr=C3=BC=C3=9Fe?="), "Grüße");
+        assert_eq!(
+            decode("=?windows-1252?Q?=93Gr=FC=DFe=94_=80?="),
+            "\u{201c}Grüße\u{201d} \u{20ac}"
+        );
+    }
+
+    fn params(
+        params: &[(&'static str, &'static str)],
+    ) -> Vec<(Cow<'static, str>, Cow<'static, [u8]>)> {
+        params
+            .iter()
+            .map(|&(key, value)| (Cow::Borrowed(key), Cow::Borrowed(value.as_bytes())))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_param() {
+        let p = params(&[("NAME", "a.pdf"), ("CHARSET", "utf-8")]);
+        assert_eq!(find_param(&p, "name").as_deref(), Some("a.pdf"));
+        assert!(matches!(find_param(&p, "charset"), Some(Cow::Borrowed(_))));
+        assert_eq!(find_param(&p, "filename"), None);
+
+        let p = params(&[
+            ("filename*", "UTF-8''Gr%C3%BC%C3%9Fe.txt"),
+            ("filename", "x.txt"),
+        ]);
+        assert_eq!(find_param(&p, "FILENAME").as_deref(), Some("Grüße.txt"));
+
+        let p = params(&[
+            ("FILENAME*1", " Bericht"),
+            ("FILENAME*0*", "iso-8859-1'de'M%E4rz"),
+            ("FILENAME*2*", "%2Epdf"),
+            ("FILENAME*4", "ignored"),
+        ]);
+        assert_eq!(
+            find_param(&p, "filename").as_deref(),
+            Some("März Bericht.pdf")
+        );
+
+        let p = params(&[("name*0", "long "), ("name*1", "name")]);
+        assert_eq!(find_param(&p, "name").as_deref(), Some("long name"));
+
+        let p = params(&[("name", "=?UTF-8?B?SsO2cmcucGRm?=")]);
+        assert_eq!(find_param(&p, "name").as_deref(), Some("Jörg.pdf"));
+
+        let p = params(&[("name*1", "orphan"), ("name*x", "bad"), ("name", "plain")]);
+        assert_eq!(find_param(&p, "name").as_deref(), Some("plain"));
+    }
+
+    #[test]
+    fn test_decode_invalid_words() {
+        let decode = |s: &str| decode_encoded_words(s.as_bytes());
+        assert_eq!(decode("=?KOI8-R?Q?=F0?="), "=?KOI8-R?Q?=F0?=");
+        assert_eq!(decode("=?UTF-8?X?abc?="), "=?UTF-8?X?abc?=");
+        assert_eq!(decode("=?UTF-8?B?abc"), "=?UTF-8?B?abc");
+        assert_eq!(
+            decode("a =?UTF-8?Q?not encoded?= b"),
+            "a =?UTF-8?Q?not encoded?= b"
+        );
+        assert_eq!(decode("=?UTF-8?B?/w==?="), "\u{fffd}");
+    }
+}
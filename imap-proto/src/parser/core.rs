@@ -1,15 +1,22 @@
 use nom::{
     branch::alt,
-    bytes::streaming::{tag, tag_no_case, take, take_while, take_while1},
+    bytes::streaming::{tag, tag_no_case, take_while, take_while1},
     character::streaming::{char, digit1},
-    combinator::{map, map_res},
+    combinator::{map, map_res, opt},
     multi::{separated_list, separated_nonempty_list},
-    sequence::{delimited, tuple},
-    IResult,
+    sequence::{delimited, preceded, tuple},
+    IResult, Needed,
 };
 
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::str::{from_utf8, FromStr};
 
+use crate::{
+    parser::options,
+    types::{SeqNumber, SeqRange, SequenceSet},
+};
+
 // ----- number -----
 
 // number          = 1*DIGIT
@@ -38,29 +45,80 @@ pub fn number_64(i: &[u8]) -> IResult<&[u8], u64> {
     }
 }
 
+// nz-number       = digit-nz *DIGIT
+//                    ; Non-zero unsigned 32-bit integer
+//                    ; (0 < n < 4,294,967,296)
+pub fn nz_number(i: &[u8]) -> IResult<&[u8], u32> {
+    let (i, num) = number(i)?;
+    if num == 0 {
+        return Err(nom::Err::Error(nom::error::make_error(
+            i,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
+    Ok((i, num))
+}
+
+// ----- sequence set -----
+
+// seq-number      = nz-number / "*"
+pub fn seq_number(i: &[u8]) -> IResult<&[u8], SeqNumber> {
+    alt((
+        map(nz_number, SeqNumber::Value),
+        map(char('*'), |_| SeqNumber::Largest),
+    ))(i)
+}
+
+// seq-range       = seq-number ":" seq-number
+//                    ; two seq-number values and all values between
+//                    ; these two regardless of order.
+pub fn seq_range(i: &[u8]) -> IResult<&[u8], SeqRange> {
+    map(
+        tuple((seq_number, opt(preceded(char(':'), seq_number)))),
+        |(start, end)| SeqRange::new(start, end.unwrap_or(start)),
+    )(i)
+}
+
+// sequence-set    = (seq-number / seq-range) *("," sequence-set)
+// sequence-set    =/ seq-last-command
+//                    ; Allow for "result of the last command" indicator.
+// seq-last-command   = "$"
+// [RFC5182 - 4. Formal Syntax](https://tools.ietf.org/html/rfc5182#section-4)
+pub fn sequence_set(i: &[u8]) -> IResult<&[u8], SequenceSet> {
+    alt((
+        map(
+            separated_nonempty_list(char(','), seq_range),
+            SequenceSet::Set,
+        ),
+        map(char('$'), |_| SequenceSet::LastResult),
+    ))(i)
+}
+
 // ----- string -----
 
 // string = quoted / literal
-pub fn string(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    alt((quoted, literal))(i)
+pub fn string(i: &[u8]) -> IResult<&[u8], Cow<'_, [u8]>> {
+    alt((quoted, map(literal, Cow::Borrowed)))(i)
 }
 
 // string bytes as utf8
-pub fn string_utf8(i: &[u8]) -> IResult<&[u8], &str> {
-    map_res(string, from_utf8)(i)
+pub fn string_utf8(i: &[u8]) -> IResult<&[u8], Cow<'_, str>> {
+    map(string, cow_utf8)(i)
 }
 
 // quoted = DQUOTE *QUOTED-CHAR DQUOTE
-pub fn quoted(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    delimited(char('"'), quoted_data, char('"'))(i)
+// The content is unescaped, which only copies it if it contains escapes.
+pub fn quoted(i: &[u8]) -> IResult<&[u8], Cow<'_, [u8]>> {
+    map(delimited(char('"'), quoted_data, char('"')), unescape)(i)
 }
 
 // quoted bytes as utf8
-pub fn quoted_utf8(i: &[u8]) -> IResult<&[u8], &str> {
-    map_res(quoted, from_utf8)(i)
+pub fn quoted_utf8(i: &[u8]) -> IResult<&[u8], Cow<'_, str>> {
+    map(quoted, cow_utf8)(i)
 }
 
 // QUOTED-CHAR = <any TEXT-CHAR except quoted-specials> / "\" quoted-specials
+// Returns the content of a quoted string as it is sent, with the escapes.
 pub fn quoted_data(i: &[u8]) -> IResult<&[u8], &[u8]> {
     // Ideally this should use nom's `escaped` macro, but it suffers from broken
     // type inference unless compiled with the verbose-errors feature enabled.
@@ -80,6 +138,38 @@ pub fn quoted_data(i: &[u8]) -> IResult<&[u8], &[u8]> {
     Ok((&i[len..], &i[..len]))
 }
 
+// Removes the backslashes from the escapes in the content of a quoted string.
+fn unescape(raw: &[u8]) -> Cow<'_, [u8]> {
+    if !raw.contains(&b'\\') {
+        return Cow::Borrowed(raw);
+    }
+    let mut out = Vec::with_capacity(raw.len());
+    let mut escape = false;
+    for &c in raw {
+        if c == b'\\' && !escape {
+            escape = true;
+        } else {
+            out.push(c);
+            escape = false;
+        }
+    }
+    Cow::Owned(out)
+}
+
+// Converts to UTF-8, keeping borrowed input borrowed. Invalid UTF-8, such
+// as Latin-1 from older servers, is replaced rather than failing the parse
+// of the whole response; fields that need the bytes as they were sent, such
+// as body parameters, are kept as bytes instead.
+pub(crate) fn cow_utf8(bytes: Cow<'_, [u8]>) -> Cow<'_, str> {
+    match bytes {
+        Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+        Cow::Owned(bytes) => match String::from_utf8(bytes) {
+            Ok(s) => Cow::Owned(s),
+            Err(e) => Cow::Owned(String::from_utf8_lossy(e.as_bytes()).into_owned()),
+        },
+    }
+}
+
 // quoted-specials = DQUOTE / "\"
 pub fn is_quoted_specials(c: u8) -> bool {
     c == b'"' || c == b'\\'
@@ -88,11 +178,11 @@ pub fn is_quoted_specials(c: u8) -> bool {
 /// literal = "{" number "}" CRLF *CHAR8
 ///            ; Number represents the number of CHAR8s
 pub fn literal(input: &[u8]) -> IResult<&[u8], &[u8]> {
-    let parser = tuple((tag(b"{"), number, tag(b"}"), tag("\r\n")));
+    let parser = tuple((tag(b"{"), literal_length, tag(b"}"), tag("\r\n")));
 
     let (remaining, (_, count, _, _)) = parser(input)?;
 
-    let (remaining, data) = take(count)(remaining)?;
+    let (remaining, data) = literal_data(remaining, count)?;
 
     if !data.iter().all(|byte| is_char8(*byte)) {
         // FIXME: what ErrorKind should this have?
@@ -102,6 +192,67 @@ pub fn literal(input: &[u8]) -> IResult<&[u8], &[u8]> {
     Ok((remaining, data))
 }
 
+/// literal8 = "~{" number "}" CRLF *OCTET
+///            ; A string that might contain NULs.
+///            ; <number> represents the number of OCTETs
+///            ; in the response string.
+/// [RFC3516 - 4.2. FETCH Command Extensions](https://tools.ietf.org/html/rfc3516#section-4.2)
+pub fn literal8(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    let parser = tuple((tag(b"~{"), literal_length, tag(b"}"), tag("\r\n")));
+
+    let (remaining, (_, count, _, _)) = parser(input)?;
+
+    literal_data(remaining, count)
+}
+
+// A literal whose length doesn't fit in a number can't be skipped, so this
+// fails the whole response rather than letting other alternatives match.
+fn literal_length(i: &[u8]) -> IResult<&[u8], u32> {
+    let (rest, bytes) = digit1(i)?;
+    match from_utf8(bytes).ok().and_then(|s| u32::from_str(s).ok()) {
+        Some(v) => Ok((rest, v)),
+        None => Err(nom::Err::Failure((i, nom::error::ErrorKind::TooLarge))),
+    }
+}
+
+thread_local! {
+    static LITERAL_NEEDED: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+// Takes the octets of a literal. If the input ends early, this reports the
+// number of missing octets, which is also recorded so that
+// `parse_response_incremental` can tell it apart from other incomplete input.
+fn literal_data(i: &[u8], count: u32) -> IResult<&[u8], &[u8]> {
+    let count = count as usize;
+    if i.len() < count {
+        let missing = count - i.len();
+        LITERAL_NEEDED.with(|needed| needed.set(Some(missing)));
+        return Err(nom::Err::Incomplete(Needed::Size(missing)));
+    }
+    Ok((&i[count..], &i[..count]))
+}
+
+// Returns and clears the number of octets missing from the last incomplete
+// literal.
+pub(crate) fn take_literal_needed() -> Option<usize> {
+    LITERAL_NEEDED.with(Cell::take)
+}
+
+// Like `string`, but also accepts a literal8 (RFC 3516), so the result
+// may contain NUL bytes.
+pub fn string8(i: &[u8]) -> IResult<&[u8], Cow<'_, [u8]>> {
+    alt((
+        quoted,
+        map(literal, Cow::Borrowed),
+        map(literal8, Cow::Borrowed),
+    ))(i)
+}
+
+// Like `nstring`, but also accepts a literal8 (RFC 3516).
+pub fn nstring8(i: &[u8]) -> IResult<&[u8], Option<Cow<'_, [u8]>>> {
+    alt((map(nil, |_| None), map(string8, Some)))(i)
+}
+
 /// CHAR8 = %x01-ff ; any OCTET except NUL, %x00
 pub fn is_char8(i: u8) -> bool {
     i != 0
@@ -110,13 +261,13 @@ pub fn is_char8(i: u8) -> bool {
 // ----- astring ----- atom (roughly) or string
 
 // astring = 1*ASTRING-CHAR / string
-pub fn astring(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    alt((take_while1(is_astring_char), string))(i)
+pub fn astring(i: &[u8]) -> IResult<&[u8], Cow<'_, [u8]>> {
+    alt((map(take_while1(is_astring_char), Cow::Borrowed), string))(i)
 }
 
 // astring bytes as utf8
-pub fn astring_utf8(i: &[u8]) -> IResult<&[u8], &str> {
-    map_res(astring, from_utf8)(i)
+pub fn astring_utf8(i: &[u8]) -> IResult<&[u8], Cow<'_, str>> {
+    map(astring, cow_utf8)(i)
 }
 
 // ASTRING-CHAR = ATOM-CHAR / resp-specials
@@ -154,12 +305,12 @@ pub fn atom(i: &[u8]) -> IResult<&[u8], &str> {
 // ----- nstring ----- nil or string
 
 // nstring = string / nil
-pub fn nstring(i: &[u8]) -> IResult<&[u8], Option<&[u8]>> {
+pub fn nstring(i: &[u8]) -> IResult<&[u8], Option<Cow<'_, [u8]>>> {
     alt((map(nil, |_| None), map(string, Some)))(i)
 }
 
 // nstring bytes as utf8
-pub fn nstring_utf8(i: &[u8]) -> IResult<&[u8], Option<&str>> {
+pub fn nstring_utf8(i: &[u8]) -> IResult<&[u8], Option<Cow<'_, str>>> {
     alt((map(nil, |_| None), map(string_utf8, Some)))(i)
 }
 
@@ -171,8 +322,30 @@ pub fn nil(i: &[u8]) -> IResult<&[u8], &[u8]> {
 // ----- text -----
 
 // text = 1*TEXT-CHAR
-pub fn text(i: &[u8]) -> IResult<&[u8], &str> {
-    map_res(take_while(is_text_char), from_utf8)(i)
+// With UTF8=ACCEPT, this also accepts UTF-8 (RFC 6855, section 3).
+pub fn text(i: &[u8]) -> IResult<&[u8], Cow<'_, str>> {
+    let options = options::current();
+    let eight_bit = options.utf8_accept || options.lenient;
+    let (rest, bytes) = take_while(move |c| is_text_char(c) || (eight_bit && c >= 0x80))(i)?;
+    Ok((rest, cow_utf8(Cow::Borrowed(bytes))))
+}
+
+// SP, or any run of spaces in lenient mode.
+pub fn sp(i: &[u8]) -> IResult<&[u8], &[u8]> {
+    if options::current().lenient {
+        take_while1(|c| c == b' ')(i)
+    } else {
+        tag(" ")(i)
+    }
+}
+
+// CRLF, which lenient mode allows to be preceded by spaces.
+pub fn crlf(i: &[u8]) -> IResult<&[u8], &[u8]> {
+    if options::current().lenient {
+        preceded(take_while(|c| c == b' '), tag("\r\n"))(i)
+    } else {
+        tag("\r\n")(i)
+    }
 }
 
 // TEXT-CHAR = <any CHAR except CR and LF>
@@ -185,14 +358,63 @@ pub fn is_text_char(c: u8) -> bool {
 //          ;  excluding NUL
 // From RFC5234
 pub fn is_char(c: u8) -> bool {
-    match c {
-        0x01..=0x7F => true,
-        _ => false,
+    matches!(c, 0x01..=0x7F)
+}
+
+// ----- base64 -----
+
+// base64          = *(4base64-char) [base64-terminal]
+// Decodes base64 with padding, as used for SASL challenges.
+pub(crate) fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some(u32::from(c - b'A')),
+            b'a'..=b'z' => Some(u32::from(c - b'a') + 26),
+            b'0'..=b'9' => Some(u32::from(c - b'0') + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let bytes = s.as_bytes();
+    if bytes.len() % 4 != 0 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (n, chunk) in bytes.chunks(4).enumerate() {
+        let last = n == bytes.len() / 4 - 1;
+        let padding = match chunk {
+            [_, _, b'=', b'='] if last => 2,
+            [_, _, _, b'='] if last => 1,
+            _ => 0,
+        };
+        let mut acc = 0;
+        for &c in &chunk[..4 - padding] {
+            acc = (acc << 6) | value(c)?;
+        }
+        acc <<= 6 * padding;
+        out.extend_from_slice(&acc.to_be_bytes()[1..4 - padding]);
     }
+    Some(out)
 }
 
 // ----- others -----
 
+// Nested lists, such as extension data and body structures, are parsed this
+// many levels deep at most. Deeper nesting is rejected rather than letting a
+// server overflow the stack.
+const MAX_NESTING_DEPTH: usize = 64;
+
+// Fails with `TooLarge` once `depth` is past `MAX_NESTING_DEPTH`, to be
+// called on entering each level of a recursive parser.
+pub(crate) fn check_nesting_depth(i: &[u8], depth: usize) -> IResult<&[u8], ()> {
+    if depth > MAX_NESTING_DEPTH {
+        return Err(nom::Err::Failure((i, nom::error::ErrorKind::TooLarge)));
+    }
+    Ok((i, ()))
+}
+
 // list-wildcards = "%" / "*"
 pub fn is_list_wildcards(c: u8) -> bool {
     c == b'%' || c == b'*'
@@ -243,17 +465,92 @@ mod tests {
     fn test_string_literal() {
         match string(b"{3}\r\nXYZ") {
             Ok((_, value)) => {
-                assert_eq!(value, b"XYZ");
+                assert_eq!(value.as_ref(), b"XYZ");
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_quoted() {
+        match quoted(b"\"plain\" ") {
+            Ok((rest, Cow::Borrowed(value))) => {
+                assert_eq!(value, b"plain");
+                assert_eq!(rest, b" ");
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+        match quoted_utf8(b"\"a \\\"quoted\\\" word \\\\\" ") {
+            Ok((_, Cow::Owned(value))) => assert_eq!(value, "a \"quoted\" word \\"),
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_literal8() {
+        match string8(b"~{4}\r\nA\0\xffB ") {
+            Ok((rest, value)) => {
+                assert_eq!(value.as_ref(), b"A\0\xffB");
+                assert_eq!(rest, b" ");
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        // Binary data is rejected by the regular string parser.
+        assert!(string(b"~{1}\r\n\0 ").is_err());
+        assert!(string(b"{1}\r\n\0 ").is_err());
+        assert_eq!(nstring8(b"NIL "), Ok((&b" "[..], None)));
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64(""), Some(vec![]));
+        assert_eq!(decode_base64("Zm9v"), Some(b"foo".to_vec()));
+        assert_eq!(decode_base64("Zm9vYg=="), Some(b"foob".to_vec()));
+        assert_eq!(decode_base64("Zm9vYmE="), Some(b"fooba".to_vec()));
+        assert_eq!(decode_base64("Zm9"), None);
+        assert_eq!(decode_base64("Zm=v"), None);
+        assert_eq!(decode_base64("Zg==Zm9v"), None);
+    }
+
+    #[test]
+    fn test_sequence_set() {
+        match sequence_set(b"1:5,8,12:10 ") {
+            Ok((_, set)) => {
+                assert_eq!(set, SequenceSet::from(vec![1..=5, 8..=8, 10..=12]));
             }
             rsp => panic!("unexpected response {:?}", rsp),
         }
+
+        match sequence_set(b"10:*,*:4,* ") {
+            Ok((_, set)) => {
+                let (largest, ten) = (SeqNumber::Largest, SeqNumber::Value(10));
+                assert_eq!(
+                    set.ranges(),
+                    vec![
+                        SeqRange::new(ten, largest),
+                        SeqRange::new(SeqNumber::Value(4), largest),
+                        SeqRange::new(largest, largest),
+                    ]
+                );
+                assert_eq!(set.ranges()[1].start, SeqNumber::Value(4));
+                assert_eq!(set.ranges()[0].to_range(7), 7..=10);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        assert!(sequence_set(b"0:4 ").is_err());
+        assert_eq!(
+            sequence_set(b"$ "),
+            Ok((&b" "[..], SequenceSet::LastResult))
+        );
     }
 
     #[test]
     fn test_astring() {
         match astring(b"text ") {
             Ok((_, value)) => {
-                assert_eq!(value, b"text");
+                assert_eq!(value.as_ref(), b"text");
             }
             rsp => panic!("unexpected response {:?}", rsp),
         }
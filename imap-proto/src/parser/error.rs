@@ -0,0 +1,207 @@
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+use nom::{error::ErrorKind, IResult};
+
+/// An error from parsing a response, describing where and in which grammar
+/// rules the input stopped matching.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError<'a> {
+    offset: usize,
+    kind: ErrorKind,
+    context: Vec<&'static str>,
+    snippet: Cow<'a, [u8]>,
+}
+
+impl<'a> ParseError<'a> {
+    fn new(input: &'a [u8], trail: Vec<Frame>, remaining: usize, kind: ErrorKind) -> Self {
+        let (remaining, kind) = trail
+            .last()
+            .map_or((remaining, kind), |frame| (frame.remaining, frame.kind));
+        let offset = input.len() - remaining;
+        let rest = &input[offset..];
+        ParseError {
+            offset,
+            kind,
+            context: trail.iter().rev().map(|frame| frame.name).collect(),
+            snippet: Cow::Borrowed(&rest[..rest.len().min(32)]),
+        }
+    }
+
+    /// The offset of the offending byte from the start of the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The kind of nom parser that failed.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The names of the grammar rules that were being parsed, outermost
+    /// first, such as `["response", "response-data", "msg-att"]`.
+    pub fn context(&self) -> &[&'static str] {
+        &self.context
+    }
+
+    /// Up to 32 bytes of the input, starting at the offending byte.
+    pub fn snippet(&self) -> &[u8] {
+        &self.snippet
+    }
+
+    /// Copies the snippet, so that the error no longer borrows the input.
+    pub fn into_owned(self) -> ParseError<'static> {
+        ParseError {
+            snippet: Cow::Owned(self.snippet.into_owned()),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} error at byte {} in {}: \"",
+            self.kind,
+            self.offset,
+            self.context.join(" > ")
+        )?;
+        for &b in self.snippet() {
+            write!(f, "{}", std::ascii::escape_default(b))?;
+        }
+        write!(f, "\"")
+    }
+}
+
+impl std::error::Error for ParseError<'_> {}
+
+// A grammar rule that failed: its nesting depth, its name, and the remaining
+// input and error kind of the furthest failure within it.
+struct Frame {
+    depth: usize,
+    name: &'static str,
+    remaining: usize,
+    kind: ErrorKind,
+}
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    static TRAIL: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+// Names the grammar rule parsed by `f`, for the context of a `ParseError`.
+//
+// Every failed rule leaves a frame behind. A rule that succeeds drops the
+// frames of the rules it tried, and a rule that fails keeps only the frames
+// of the sub-rule that got furthest into the input, since nom returns the
+// error of the last alternative rather than the most useful one.
+pub(crate) fn context<'a, O, F>(
+    name: &'static str,
+    f: F,
+) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], O>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    move |i| {
+        let depth = DEPTH.with(|d| d.replace(d.get() + 1));
+        let mark = TRAIL.with(|t| t.borrow().len());
+        let res = f(i);
+        DEPTH.with(|d| d.set(depth));
+
+        TRAIL.with(|t| {
+            let mut trail = t.borrow_mut();
+            let (remaining, kind) = match &res {
+                Err(nom::Err::Error((rest, kind))) | Err(nom::Err::Failure((rest, kind))) => {
+                    (rest.len(), *kind)
+                }
+                // Recorded for `parse_complete`, where the end of the input
+                // is final.
+                Err(nom::Err::Incomplete(_)) => (0, ErrorKind::Eof),
+                Ok(_) => {
+                    trail.truncate(mark);
+                    return;
+                }
+            };
+
+            // Find the chain of frames left by the child that got furthest,
+            // preferring later children on ties. Each chain ends with the
+            // frame of the child itself.
+            let mut best: Option<(usize, usize)> = None;
+            let mut start = mark;
+            for n in mark..trail.len() {
+                if trail[n].depth == depth + 1 {
+                    if best.map_or(true, |(_, end)| trail[n].remaining <= trail[end].remaining) {
+                        best = Some((start, n));
+                    }
+                    start = n + 1;
+                }
+            }
+
+            let mut frame = Frame {
+                depth,
+                name,
+                remaining,
+                kind,
+            };
+            match best {
+                Some((start, end)) if trail[end].remaining <= remaining => {
+                    frame.remaining = trail[end].remaining;
+                    frame.kind = trail[end].kind;
+                    trail.truncate(end + 1);
+                    trail.drain(mark..start);
+                }
+                _ => trail.truncate(mark),
+            }
+            trail.push(frame);
+        });
+        res
+    }
+}
+
+fn run<'a, O, F>(input: &'a [u8], name: &'static str, f: F) -> (IResult<&'a [u8], O>, Vec<Frame>)
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    DEPTH.with(|d| d.set(0));
+    TRAIL.with(|t| t.borrow_mut().clear());
+    let res = context(name, f)(input);
+    (res, TRAIL.with(|t| t.take()))
+}
+
+// Runs the parser of an entry point, turning its errors into `ParseError`s
+// that describe the offending part of `input`.
+pub(crate) fn parse<'a, O, F>(
+    input: &'a [u8],
+    name: &'static str,
+    f: F,
+) -> IResult<&'a [u8], O, ParseError<'a>>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    let (res, trail) = run(input, name, f);
+    res.map_err(|err| err.map(|(rest, kind)| ParseError::new(input, trail, rest.len(), kind)))
+}
+
+// Like `parse`, but for input that is known to be complete: running out of
+// input is reported as an error at its end.
+pub(crate) fn parse_complete<'a, O, F>(
+    input: &'a [u8],
+    name: &'static str,
+    f: F,
+) -> Result<(&'a [u8], O), ParseError<'a>>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    match run(input, name, f) {
+        (Ok(res), _) => Ok(res),
+        (Err(nom::Err::Error((rest, kind))), trail)
+        | (Err(nom::Err::Failure((rest, kind))), trail) => {
+            Err(ParseError::new(input, trail, rest.len(), kind))
+        }
+        (Err(nom::Err::Incomplete(_)), trail) => {
+            Err(ParseError::new(input, trail, 0, ErrorKind::Eof))
+        }
+    }
+}
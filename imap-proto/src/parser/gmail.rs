@@ -0,0 +1,156 @@
+//!
+//! https://developers.google.com/gmail/imap/imap-extensions
+//!
+//! Gmail IMAP extensions
+//!
+
+use std::borrow::Cow;
+
+use nom::{
+    branch::alt, bytes::streaming::tag_no_case, combinator::map, sequence::preceded, IResult,
+};
+
+use crate::{
+    parser::{
+        core::*,
+        rfc3501::{flag_extension, mailbox_list},
+    },
+    types::*,
+};
+
+// XLIST predates SPECIAL-USE (RFC 6154) and uses different names for some of
+// the same mailbox roles. These are mapped onto the SPECIAL-USE attributes;
+// the XLIST-only \Inbox and \Important are kept as extensions.
+fn xlist_name_attribute(attr: NameAttribute<'_>) -> NameAttribute<'_> {
+    match attr {
+        NameAttribute::Extension(s) => match s.to_ascii_lowercase().as_str() {
+            "\\spam" => NameAttribute::Junk,
+            "\\allmail" => NameAttribute::All,
+            "\\starred" => NameAttribute::Flagged,
+            _ => NameAttribute::Extension(s),
+        },
+        attr => attr,
+    }
+}
+
+// xlist-data = "XLIST" SP mailbox-list
+// The response is returned in the same form as a LIST response.
+pub(crate) fn mailbox_data_xlist(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        preceded(tag_no_case("XLIST "), mailbox_list),
+        |datum| match datum {
+            MailboxDatum::List {
+                flags,
+                delimiter,
+                name,
+                extended_data,
+            } => Response::MailboxData(MailboxDatum::List {
+                flags: flags.into_iter().map(xlist_name_attribute).collect(),
+                delimiter,
+                name,
+                extended_data,
+            }),
+            datum => Response::MailboxData(datum),
+        },
+    )(i)
+}
+
+// Labels are sent as astrings, except for system labels which use
+// flag syntax, e.g. (\Inbox \Sent "Muy Importante" foo)
+fn gmail_label(i: &[u8]) -> IResult<&[u8], Cow<'_, str>> {
+    alt((map(flag_extension, Cow::Borrowed), astring_utf8))(i)
+}
+
+pub(crate) fn msg_att_gmail_labels(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(
+        preceded(tag_no_case("X-GM-LABELS "), parenthesized_list(gmail_label)),
+        AttributeValue::GmailLabels,
+    )(i)
+}
+
+pub(crate) fn msg_att_gmail_msgid(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(
+        preceded(tag_no_case("X-GM-MSGID "), number_64),
+        AttributeValue::GmailMsgId,
+    )(i)
+}
+
+pub(crate) fn msg_att_gmail_thrid(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(
+        preceded(tag_no_case("X-GM-THRID "), number_64),
+        AttributeValue::GmailThrId,
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_gmail_fetch() {
+        let res = parse_response(
+            b"* 1 FETCH (X-GM-THRID 1278455344230334865 X-GM-MSGID 1278455344230334865 \
+              X-GM-LABELS (\\Inbox \\Sent Important \"Muy Importante\") UID 4)\r\n",
+        );
+        match res {
+            Ok((_, Response::Fetch(Seq(1), attrs))) => {
+                assert_eq!(
+                    attrs,
+                    vec![
+                        AttributeValue::GmailThrId(1278455344230334865),
+                        AttributeValue::GmailMsgId(1278455344230334865),
+                        AttributeValue::GmailLabels(vec![
+                            Cow::Borrowed("\\Inbox"),
+                            Cow::Borrowed("\\Sent"),
+                            Cow::Borrowed("Important"),
+                            Cow::Borrowed("Muy Importante"),
+                        ]),
+                        AttributeValue::Uid(Uid(4)),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_xlist() {
+        match parse_response(b"* XLIST (\\HasNoChildren \\Spam) \"/\" \"[Gmail]/Spam\"\r\n") {
+            Ok((_, Response::MailboxData(MailboxDatum::List { flags, name, .. }))) => {
+                assert_eq!(name, "[Gmail]/Spam");
+                assert_eq!(
+                    flags,
+                    vec![NameAttribute::HasNoChildren, NameAttribute::Junk]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* XLIST (\\HasNoChildren \\Inbox) \"/\" \"Inbox\"\r\n") {
+            Ok((_, Response::MailboxData(MailboxDatum::List { flags, name, .. }))) => {
+                assert_eq!(name, "INBOX");
+                assert_eq!(
+                    flags,
+                    vec![
+                        NameAttribute::HasNoChildren,
+                        NameAttribute::Extension(Cow::Borrowed("\\Inbox"))
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_gmail_labels_empty() {
+        match parse_response(b"* 2 FETCH (X-GM-LABELS ())\r\n") {
+            Ok((_, Response::Fetch(Seq(2), attrs))) => {
+                assert_eq!(attrs, vec![AttributeValue::GmailLabels(vec![])]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
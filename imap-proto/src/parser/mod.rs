@@ -1,14 +1,69 @@
 use crate::types::Response;
-use nom::IResult;
+use nom::{IResult, Needed};
 
 pub mod core;
 
+#[cfg(feature = "gmail")]
+pub mod gmail;
+
+mod error;
+mod options;
+pub use self::error::ParseError;
+pub use self::options::Options;
+
+#[cfg(feature = "quota")]
+pub mod rfc2087;
+pub mod rfc2221;
+pub mod rfc2342;
+pub mod rfc2971;
 pub mod rfc3501;
+pub mod rfc3516;
+#[cfg(feature = "acl")]
+pub mod rfc4314;
+pub mod rfc4315;
+pub mod rfc4466;
+pub mod rfc4467;
+pub mod rfc4469;
+#[cfg(feature = "condstore")]
 pub mod rfc4551;
+pub mod rfc4731;
+pub mod rfc4978;
 pub mod rfc5161;
+pub mod rfc5255;
+pub mod rfc5256;
+pub mod rfc5257;
+pub mod rfc5258;
+pub mod rfc5322;
+#[cfg(feature = "metadata")]
 pub mod rfc5464;
+pub mod rfc5465;
+#[cfg(feature = "condstore")]
+pub mod rfc7162;
+pub mod rfc7889;
+pub mod rfc8438;
+pub mod rfc8474;
+pub mod rfc8514;
+pub mod rfc8970;
+pub mod rfc9394;
+pub mod rfc9586;
 
 #[cfg(test)]
 mod tests;
 
-pub type ParseResult<'a> = IResult<&'a [u8], Response<'a>>;
+pub type ParseResult<'a> = IResult<&'a [u8], Response<'a>, ParseError<'a>>;
+
+/// The result of parsing a response from a buffer that may not hold all of
+/// it yet, as returned by
+/// [`parse_response_incremental`](rfc3501::parse_response_incremental).
+#[derive(Debug)]
+pub enum Parsed<'a> {
+    /// A complete response, and the input following it.
+    Complete(&'a [u8], Response<'a>),
+    /// The input ends inside a response. `Needed::Size(n)` means that the
+    /// input ends inside a literal, so at least `n` more bytes are required
+    /// before parsing can succeed. Otherwise, any amount of additional data
+    /// may complete the response.
+    Incomplete(Needed),
+    /// The input is not a valid response.
+    Error(ParseError<'a>),
+}
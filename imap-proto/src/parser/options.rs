@@ -0,0 +1,47 @@
+use std::cell::Cell;
+
+/// Options that change the grammar accepted by the parsers.
+///
+/// The default is the grammar of RFC 3501 and its extensions. Options are
+/// passed to the `*_with` entry points, such as
+/// [`parse_response_with`](super::rfc3501::parse_response_with).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Options {
+    /// Accept UTF-8 in quoted strings, mailbox names and human-readable
+    /// text, as sent by servers once `UTF8=ACCEPT` has been enabled
+    /// (RFC 6855, section 3).
+    pub utf8_accept: bool,
+    /// Reject syntax that was removed in IMAP4rev2 (RFC 9051, appendix E):
+    /// the `RFC822`, `RFC822.HEADER` and `RFC822.TEXT` fetch items, and the
+    /// `SEARCH` and `RECENT` responses, which are replaced by `ESEARCH`.
+    pub imap4rev2: bool,
+    /// Tolerate common violations of the grammar by real-world servers:
+    /// runs of spaces where a single space is expected, a missing space
+    /// before a response code or after the status of an empty response,
+    /// trailing spaces before the CRLF, and 8-bit bytes in human-readable
+    /// text.
+    pub lenient: bool,
+}
+
+thread_local! {
+    static CURRENT: Cell<Options> = Cell::new(Options::default());
+}
+
+// The individual parsers are plain functions, so the options in effect are
+// kept per thread for the duration of a call to one of the entry points.
+pub(crate) fn current() -> Options {
+    CURRENT.with(Cell::get)
+}
+
+pub(crate) fn with<T>(options: Options, f: impl FnOnce() -> T) -> T {
+    struct Reset(Options);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            CURRENT.with(|current| current.set(self.0));
+        }
+    }
+
+    let _reset = Reset(CURRENT.with(|current| current.replace(options)));
+    f()
+}
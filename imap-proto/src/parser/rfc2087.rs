@@ -0,0 +1,206 @@
+//!
+//! https://tools.ietf.org/html/rfc2087
+//!
+//! IMAP4 QUOTA extension, including the resource names and 64-bit
+//! values introduced by https://tools.ietf.org/html/rfc9208
+//!
+
+use std::borrow::Cow;
+
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case},
+    character::streaming::char,
+    combinator::map,
+    multi::many0,
+    sequence::{preceded, tuple},
+    IResult,
+};
+
+use crate::{
+    parser::{core::*, rfc3501::mailbox},
+    types::*,
+};
+
+// resource-name = "STORAGE" / "MESSAGE" / "MAILBOX" /
+//                 "ANNOTATION-STORAGE" / resource-name-ext
+// [RFC9208 - 7. Formal Syntax](https://tools.ietf.org/html/rfc9208#section-7)
+fn quota_resource_name(i: &[u8]) -> IResult<&[u8], QuotaResourceName<'_>> {
+    alt((
+        map(tag_no_case("STORAGE "), |_| QuotaResourceName::Storage),
+        map(tag_no_case("MESSAGE "), |_| QuotaResourceName::Message),
+        map(tag_no_case("MAILBOX "), |_| QuotaResourceName::Mailbox),
+        map(tag_no_case("ANNOTATION-STORAGE "), |_| {
+            QuotaResourceName::AnnotationStorage
+        }),
+        map(tuple((atom, char(' '))), |(name, _)| {
+            QuotaResourceName::Atom(Cow::Borrowed(name))
+        }),
+    ))(i)
+}
+
+// quota-resource = resource-name SP resource-usage SP resource-limit
+// resource-usage = number64
+// resource-limit = number64
+fn quota_resource(i: &[u8]) -> IResult<&[u8], QuotaResource<'_>> {
+    map(
+        tuple((quota_resource_name, number_64, char(' '), number_64)),
+        |(name, usage, _, limit)| QuotaResource { name, usage, limit },
+    )(i)
+}
+
+// quota-response = "QUOTA" SP quota-root-name SP quota-list
+// quota-list     = "(" quota-resource *(SP quota-resource) ")"
+// quota-root-name = astring
+// [RFC2087 - 5.1. QUOTA Response](https://tools.ietf.org/html/rfc2087#section-5.1)
+pub(crate) fn resp_quota(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        tuple((
+            tag_no_case("QUOTA "),
+            astring_utf8,
+            tag(" "),
+            parenthesized_list(quota_resource),
+        )),
+        |(_, root_name, _, resources)| {
+            Response::Quota(Quota {
+                root_name,
+                resources,
+            })
+        },
+    )(i)
+}
+
+// quotaroot-response = "QUOTAROOT" SP mailbox *(SP quota-root-name)
+// [RFC2087 - 5.2. QUOTAROOT Response](https://tools.ietf.org/html/rfc2087#section-5.2)
+pub(crate) fn resp_quota_root(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        tuple((
+            tag_no_case("QUOTAROOT "),
+            mailbox,
+            many0(preceded(char(' '), astring_utf8)),
+        )),
+        |(_, mailbox_name, quota_root_names)| {
+            Response::QuotaRoot(QuotaRoot {
+                mailbox_name,
+                quota_root_names,
+            })
+        },
+    )(i)
+}
+
+// resp-text-code =/ "OVERQUOTA"
+// [RFC9208 - 7. Formal Syntax](https://tools.ietf.org/html/rfc9208#section-7)
+pub(crate) fn resp_text_code_over_quota(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(tag_no_case("OVERQUOTA"), |_| ResponseCode::OverQuota)(i)
+}
+
+// status-att-val =/ "DELETED" SP number /
+//                   "DELETED-STORAGE" SP number64
+// [RFC9208 - 7. Formal Syntax](https://tools.ietf.org/html/rfc9208#section-7)
+pub(crate) fn status_att_val_deleted(i: &[u8]) -> IResult<&[u8], StatusAttribute<'_>> {
+    alt((
+        map(
+            preceded(tag_no_case("DELETED "), number),
+            StatusAttribute::Deleted,
+        ),
+        map(
+            preceded(tag_no_case("DELETED-STORAGE "), number_64),
+            StatusAttribute::DeletedStorage,
+        ),
+    ))(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{resp_quota, resp_quota_root};
+    use crate::types::*;
+
+    #[test]
+    fn test_quota() {
+        match resp_quota(b"QUOTA \"\" (STORAGE 10 512)\r\n") {
+            Ok((_, Response::Quota(quota))) => {
+                assert_eq!(
+                    quota,
+                    Quota {
+                        root_name: Cow::Borrowed(""),
+                        resources: vec![QuotaResource {
+                            name: QuotaResourceName::Storage,
+                            usage: 10,
+                            limit: 512,
+                        }],
+                    }
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match resp_quota(
+            b"QUOTA \"#user/alice\" (STORAGE 54 111 MESSAGE 42 1000 X-THINGS 5000000000 9000000000)\r\n",
+        ) {
+            Ok((_, Response::Quota(quota))) => {
+                assert_eq!(quota.root_name, "#user/alice");
+                assert_eq!(quota.resources.len(), 3);
+                assert_eq!(quota.resources[1].name, QuotaResourceName::Message);
+                assert_eq!(quota.resources[2].name, QuotaResourceName::Atom(Cow::Borrowed("X-THINGS")));
+                assert_eq!(quota.resources[2].usage, 5_000_000_000);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match resp_quota(b"QUOTA \"\" ()\r\n") {
+            Ok((_, Response::Quota(quota))) => assert!(quota.resources.is_empty()),
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_over_quota() {
+        use crate::parser::rfc3501::parse_response;
+
+        match parse_response(b"A3 NO [OVERQUOTA] Quota exceeded\r\n") {
+            Ok((_, Response::Done { code, .. })) => {
+                assert_eq!(code, Some(ResponseCode::OverQuota));
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* STATUS INBOX (DELETED 12 DELETED-STORAGE 5000000000)\r\n") {
+            Ok((_, Response::MailboxData(MailboxDatum::Status { status, .. }))) => {
+                assert_eq!(
+                    status,
+                    vec![
+                        StatusAttribute::Deleted(12),
+                        StatusAttribute::DeletedStorage(5_000_000_000),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_quota_root() {
+        match resp_quota_root(b"QUOTAROOT inbox \"\"\r\n") {
+            Ok((_, Response::QuotaRoot(root))) => {
+                assert_eq!(
+                    root,
+                    QuotaRoot {
+                        mailbox_name: Cow::Borrowed("INBOX"),
+                        quota_root_names: vec![Cow::Borrowed("")],
+                    }
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match resp_quota_root(b"QUOTAROOT comp.mail.mime\r\n") {
+            Ok((_, Response::QuotaRoot(root))) => {
+                assert_eq!(root.mailbox_name, "comp.mail.mime");
+                assert!(root.quota_root_names.is_empty());
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//!
+//! https://tools.ietf.org/html/rfc2221
+//!
+//! IMAP4 Login Referrals
+//!
+
+use std::borrow::Cow;
+use std::str::from_utf8;
+
+use nom::{
+    bytes::streaming::{tag_no_case, take_while1},
+    character::streaming::char,
+    combinator::{map, map_res},
+    multi::many1,
+    sequence::preceded,
+    IResult,
+};
+
+use crate::types::*;
+
+// A URL runs up to the next space or the closing bracket of the code.
+fn url(i: &[u8]) -> IResult<&[u8], &str> {
+    map_res(
+        take_while1(|c: u8| c.is_ascii_graphic() && c != b']'),
+        from_utf8,
+    )(i)
+}
+
+// referral_response_code = "[" "REFERRAL" 1*(SP <url>) "]"
+//    ; See [IMAP-URL] for definition of <url>
+//    ; See [IMAP4rev1] for definition of SP
+// Mailbox referrals (RFC 2193) use the same response code.
+// [RFC2221 - 4.1. LOGIN and AUTHENTICATE Referrals](https://tools.ietf.org/html/rfc2221#section-4.1)
+pub(crate) fn resp_text_code_referral(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(
+        preceded(
+            tag_no_case("REFERRAL"),
+            many1(preceded(char(' '), map(url, Cow::Borrowed))),
+        ),
+        ResponseCode::Referral,
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_referral() {
+        match parse_response(
+            b"A001 NO [REFERRAL IMAP://user;AUTH=*@SERVER2/] Specified user is invalid on this server. Try SERVER2.\r\n",
+        ) {
+            Ok((
+                _,
+                Response::Done {
+                    status: Status::No,
+                    code: Some(ResponseCode::Referral(urls)),
+                    information: Some(Cow::Borrowed("Specified user is invalid on this server. Try SERVER2.")),
+                    ..
+                },
+            )) => {
+                assert_eq!(urls, vec!["IMAP://user;AUTH=*@SERVER2/"]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* OK [REFERRAL imap://s1/foo imap://s2/foo] Mailbox moved\r\n") {
+            Ok((
+                _,
+                Response::Data {
+                    code: Some(ResponseCode::Referral(urls)),
+                    ..
+                },
+            )) => {
+                assert_eq!(urls, vec!["imap://s1/foo", "imap://s2/foo"]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* CAPABILITY IMAP4rev1 LOGIN-REFERRALS MAILBOX-REFERRALS\r\n") {
+            Ok((_, Response::Capabilities(caps))) => {
+                assert_eq!(
+                    caps,
+                    vec![
+                        Capability::Imap4rev1,
+                        Capability::LoginReferrals,
+                        Capability::MailboxReferrals,
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//!
+//! https://tools.ietf.org/html/rfc2342
+//!
+//! IMAP4 Namespace
+//!
+
+use std::borrow::Cow;
+
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case},
+    character::streaming::char,
+    combinator::{map, opt},
+    multi::{many0, many1},
+    sequence::{delimited, terminated, tuple},
+    IResult,
+};
+
+use crate::{parser::core::*, types::*};
+
+type NamespaceExtension<'a> = (Cow<'a, str>, Vec<Cow<'a, str>>);
+
+// Namespace_Response_Extension = SP string SP "(" string *(SP string) ")"
+fn namespace_response_extension(i: &[u8]) -> IResult<&[u8], NamespaceExtension<'_>> {
+    map(
+        tuple((
+            char(' '),
+            string_utf8,
+            char(' '),
+            parenthesized_nonempty_list(string_utf8),
+        )),
+        |(_, name, _, values)| (name, values),
+    )(i)
+}
+
+// "(" string SP (<"> QUOTED_CHAR <"> / nil) *(Namespace_Response_Extension) ")"
+fn namespace_description(i: &[u8]) -> IResult<&[u8], NamespaceDescription<'_>> {
+    paren_delimited(map(
+        tuple((
+            string_utf8,
+            char(' '),
+            alt((map(quoted_utf8, Some), map(nil, |_| None))),
+            many0(namespace_response_extension),
+        )),
+        |(prefix, _, delimiter, extensions)| NamespaceDescription {
+            prefix,
+            delimiter,
+            extensions,
+        },
+    ))(i)
+}
+
+// Namespace = nil / "(" 1*( "(" string SP  (<"> QUOTED_CHAR <"> / nil)
+//              *(Namespace_Response_Extension) ")" ) ")"
+// Some servers separate the descriptions with a space, which is accepted here.
+fn namespace(i: &[u8]) -> IResult<&[u8], Option<Vec<NamespaceDescription<'_>>>> {
+    alt((
+        map(nil, |_| None),
+        map(
+            delimited(
+                char('('),
+                many1(terminated(namespace_description, opt(char(' ')))),
+                char(')'),
+            ),
+            Some,
+        ),
+    ))(i)
+}
+
+// Namespace_Response = "*" SP "NAMESPACE" SP Namespace SP Namespace SP Namespace
+//         ; The first Namespace is the Personal Namespace(s)
+//         ; The second Namespace is the Other Users' Namespace(s)
+//         ; The third Namespace is the Shared Namespace(s)
+// [RFC2342 - 6. Formal Syntax](https://tools.ietf.org/html/rfc2342#section-6)
+pub(crate) fn resp_namespace(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        tuple((
+            tag_no_case("NAMESPACE "),
+            namespace,
+            tag(" "),
+            namespace,
+            tag(" "),
+            namespace,
+        )),
+        |(_, personal, _, other, _, shared)| Response::Namespace {
+            personal,
+            other,
+            shared,
+        },
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::resp_namespace;
+    use crate::types::*;
+
+    #[test]
+    fn test_namespace() {
+        match resp_namespace(b"NAMESPACE ((\"\" \"/\")) ((\"~\" \"/\")) ((\"#shared/\" \"/\")(\"#public/\" \"/\")(\"#ftp/\" \"/\")(\"#news.\" \".\"))\r\n") {
+            Ok((_, Response::Namespace { personal, other, shared })) => {
+                assert_eq!(
+                    personal,
+                    Some(vec![NamespaceDescription {
+                        prefix: Cow::Borrowed(""),
+                        delimiter: Some(Cow::Borrowed("/")),
+                        extensions: vec![],
+                    }])
+                );
+                assert_eq!(other.unwrap()[0].prefix, "~");
+                let shared = shared.unwrap();
+                assert_eq!(shared.len(), 4);
+                assert_eq!(shared[3].prefix, "#news.");
+                assert_eq!(shared[3].delimiter.as_deref(), Some("."));
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match resp_namespace(b"NAMESPACE ((\"\" NIL)) NIL NIL\r\n") {
+            Ok((
+                _,
+                Response::Namespace {
+                    personal,
+                    other,
+                    shared,
+                },
+            )) => {
+                assert_eq!(personal.unwrap()[0].delimiter, None);
+                assert_eq!(other, None);
+                assert_eq!(shared, None);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_namespace_extension() {
+        match resp_namespace(
+            b"NAMESPACE ((\"\" \"/\" \"X-PARAM\" (\"FLAG1\" \"FLAG2\"))) NIL NIL\r\n",
+        ) {
+            Ok((_, Response::Namespace { personal, .. })) => {
+                assert_eq!(
+                    personal.unwrap()[0].extensions,
+                    vec![(
+                        Cow::Borrowed("X-PARAM"),
+                        vec![Cow::Borrowed("FLAG1"), Cow::Borrowed("FLAG2")]
+                    )]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//!
+//! https://tools.ietf.org/html/rfc2971
+//!
+//! IMAP4 ID extension
+//!
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case},
+    combinator::map,
+    sequence::{preceded, tuple},
+    IResult,
+};
+
+use crate::{parser::core::*, types::*};
+
+type IdParams<'a> = Option<BTreeMap<Cow<'a, str>, Option<Cow<'a, str>>>>;
+
+// id-params-list ::= "(" #(string SPACE nstring) ")" / nil
+//         ;; list of field value pairs
+// [RFC2971 - 4. Formal Syntax](https://tools.ietf.org/html/rfc2971#section-4)
+fn id_params_list(i: &[u8]) -> IResult<&[u8], IdParams<'_>> {
+    alt((
+        map(nil, |_| None),
+        map(
+            parenthesized_list(map(
+                tuple((string_utf8, tag(" "), nstring_utf8)),
+                |(key, _, val)| (key, val),
+            )),
+            |pairs| Some(pairs.into_iter().collect()),
+        ),
+    ))(i)
+}
+
+// id_response ::= "ID" SPACE id_params_list
+// [RFC2971 - 3.2. ID Response](https://tools.ietf.org/html/rfc2971#section-3.2)
+pub(crate) fn resp_id(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(preceded(tag_no_case("ID "), id_params_list), Response::Id)(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resp_id;
+    use crate::types::*;
+
+    #[test]
+    fn test_id() {
+        match resp_id(b"ID (\"name\" \"Cyrus\" \"version\" \"1.5\" \"os\" NIL)\r\n") {
+            Ok((_, Response::Id(Some(id)))) => {
+                assert_eq!(id.len(), 3);
+                assert_eq!(id["name"].as_deref(), Some("Cyrus"));
+                assert_eq!(id["version"].as_deref(), Some("1.5"));
+                assert_eq!(id["os"], None);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match resp_id(b"ID NIL\r\n") {
+            Ok((_, Response::Id(None))) => {}
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -8,7 +8,10 @@ use nom::{
     IResult,
 };
 
-use crate::{parser::core::*, types::*};
+use crate::{
+    parser::{core::*, error::context},
+    types::*,
+};
 
 pub fn section_part(i: &[u8]) -> IResult<&[u8], Vec<u32>> {
     let (i, (part, mut rest)) = tuple((number, many0(preceded(char('.'), number))))(i)?;
@@ -16,30 +19,40 @@ pub fn section_part(i: &[u8]) -> IResult<&[u8], Vec<u32>> {
     Ok((i, rest))
 }
 
-pub fn section_msgtext(i: &[u8]) -> IResult<&[u8], MessageSection> {
+// section-msgtext = "HEADER" / "HEADER.FIELDS" [".NOT"] SP header-list /
+//                   "TEXT"
+//                     ; top-level or MESSAGE/RFC822 part
+// header-list     = "(" header-fld-name *(SP header-fld-name) ")"
+pub fn section_msgtext(i: &[u8]) -> IResult<&[u8], MessageSection<'_>> {
     alt((
         map(
             tuple((
                 tag_no_case("HEADER.FIELDS"),
                 opt(tag_no_case(".NOT")),
                 tag(" "),
-                parenthesized_list(astring),
+                parenthesized_list(astring_utf8),
             )),
-            |_| MessageSection::Header,
+            |(_, not, _, fields)| match not {
+                Some(_) => MessageSection::HeaderFieldsNot(fields),
+                None => MessageSection::HeaderFields(fields),
+            },
         ),
         map(tag_no_case("HEADER"), |_| MessageSection::Header),
         map(tag_no_case("TEXT"), |_| MessageSection::Text),
     ))(i)
 }
 
-pub fn section_text(i: &[u8]) -> IResult<&[u8], MessageSection> {
+// section-text    = section-msgtext / "MIME"
+//                     ; text other than actual body part (headers, etc.)
+pub fn section_text(i: &[u8]) -> IResult<&[u8], MessageSection<'_>> {
     alt((
         section_msgtext,
         map(tag_no_case("MIME"), |_| MessageSection::Mime),
     ))(i)
 }
 
-pub fn section_spec(i: &[u8]) -> IResult<&[u8], SectionPath> {
+// section-spec    = section-msgtext / (section-part ["." section-text])
+pub fn section_spec(i: &[u8]) -> IResult<&[u8], SectionPath<'_>> {
     alt((
         map(section_msgtext, SectionPath::Full),
         map(
@@ -49,23 +62,30 @@ pub fn section_spec(i: &[u8]) -> IResult<&[u8], SectionPath> {
     ))(i)
 }
 
-pub fn section(i: &[u8]) -> IResult<&[u8], Option<SectionPath>> {
-    delimited(char('['), opt(section_spec), char(']'))(i)
+// section         = "[" [section-spec] "]"
+pub fn section(i: &[u8]) -> IResult<&[u8], Option<SectionPath<'_>>> {
+    context(
+        "section",
+        delimited(char('['), opt(section_spec), char(']')),
+    )(i)
 }
 
-pub fn msg_att_body_section(i: &[u8]) -> IResult<&[u8], AttributeValue> {
-    map(
-        tuple((
-            tag_no_case("BODY"),
-            section,
-            opt(delimited(char('<'), number, char('>'))),
-            tag(" "),
-            nstring,
-        )),
-        |(_, section, index, _, data)| AttributeValue::BodySection {
-            section,
-            index,
-            data,
-        },
+pub fn msg_att_body_section(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    context(
+        "body-section",
+        map(
+            tuple((
+                tag_no_case("BODY"),
+                section,
+                opt(delimited(char('<'), number, char('>'))),
+                tag(" "),
+                nstring,
+            )),
+            |(_, section, index, _, data)| AttributeValue::BodySection {
+                section,
+                index,
+                data,
+            },
+        ),
     )(i)
 }
@@ -1,21 +1,23 @@
+use std::borrow::Cow;
+
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
     character::streaming::char,
-    combinator::{map, opt},
-    multi::many1,
+    combinator::map,
+    multi::{many0, many1},
     sequence::{delimited, preceded, tuple},
     IResult,
 };
 
 use crate::{
-    parser::{core::*, rfc3501::envelope},
+    parser::{core::*, error::context, rfc3501::envelope},
     types::*,
 };
 
 // body-fields     = body-fld-param SP body-fld-id SP body-fld-desc SP
 //                   body-fld-enc SP body-fld-octets
-fn body_fields(i: &[u8]) -> IResult<&[u8], BodyFields> {
+fn body_fields(i: &[u8]) -> IResult<&[u8], BodyFields<'_>> {
     let (i, (param, _, id, _, description, _, transfer_encoding, _, octets)) = tuple((
         body_param,
         tag(" "),
@@ -47,15 +49,15 @@ fn body_fields(i: &[u8]) -> IResult<&[u8], BodyFields> {
 //                   [SP body-fld-loc *(SP body-extension)]]]
 //                     ; MUST NOT be returned on non-extensible
 //                     ; "BODY" fetch
-fn body_ext_1part(i: &[u8]) -> IResult<&[u8], BodyExt1Part> {
-    let (i, (md5, disposition, language, location, extension)) = tuple((
+fn body_ext_1part(i: &[u8], depth: usize) -> IResult<&[u8], BodyExt1Part<'_>> {
+    let (i, (md5, disposition, language, location, extensions)) = tuple((
         // Per RFC 1864, MD5 values are base64-encoded
         opt_opt(preceded(tag(" "), nstring_utf8)),
         opt_opt(preceded(tag(" "), body_disposition)),
         opt_opt(preceded(tag(" "), body_lang)),
         // Location appears to reference a URL, which by RFC 1738 (section 2.2) should be ASCII
         opt_opt(preceded(tag(" "), nstring_utf8)),
-        opt(preceded(tag(" "), body_extension)),
+        many0(preceded(tag(" "), |i| body_extension(i, depth))),
     ))(i)?;
     Ok((
         i,
@@ -64,7 +66,7 @@ fn body_ext_1part(i: &[u8]) -> IResult<&[u8], BodyExt1Part> {
             disposition,
             language,
             location,
-            extension,
+            extensions,
         },
     ))
 }
@@ -73,14 +75,14 @@ fn body_ext_1part(i: &[u8]) -> IResult<&[u8], BodyExt1Part> {
 //                   [SP body-fld-loc *(SP body-extension)]]]
 //                     ; MUST NOT be returned on non-extensible
 //                     ; "BODY" fetch
-fn body_ext_mpart(i: &[u8]) -> IResult<&[u8], BodyExtMPart> {
-    let (i, (param, disposition, language, location, extension)) = tuple((
+fn body_ext_mpart(i: &[u8], depth: usize) -> IResult<&[u8], BodyExtMPart<'_>> {
+    let (i, (param, disposition, language, location, extensions)) = tuple((
         opt_opt(preceded(tag(" "), body_param)),
         opt_opt(preceded(tag(" "), body_disposition)),
         opt_opt(preceded(tag(" "), body_lang)),
         // Location appears to reference a URL, which by RFC 1738 (section 2.2) should be ASCII
         opt_opt(preceded(tag(" "), nstring_utf8)),
-        opt(preceded(tag(" "), body_extension)),
+        many0(preceded(tag(" "), |i| body_extension(i, depth))),
     ))(i)?;
     Ok((
         i,
@@ -89,12 +91,12 @@ fn body_ext_mpart(i: &[u8]) -> IResult<&[u8], BodyExtMPart> {
             disposition,
             language,
             location,
-            extension,
+            extensions,
         },
     ))
 }
 
-fn body_encoding(i: &[u8]) -> IResult<&[u8], ContentEncoding> {
+fn body_encoding(i: &[u8]) -> IResult<&[u8], ContentEncoding<'_>> {
     alt((
         delimited(
             char('"'),
@@ -109,11 +111,11 @@ fn body_encoding(i: &[u8]) -> IResult<&[u8], ContentEncoding> {
             )),
             char('"'),
         ),
-        map(string_utf8, |enc| ContentEncoding::Other(enc)),
+        map(string_utf8, ContentEncoding::Other),
     ))(i)
 }
 
-fn body_lang(i: &[u8]) -> IResult<&[u8], Option<Vec<&str>>> {
+fn body_lang(i: &[u8]) -> IResult<&[u8], Option<Vec<Cow<'_, str>>>> {
     alt((
         // body language seems to refer to RFC 3066 language tags, which should be ASCII-only
         map(nstring_utf8, |v| v.map(|s| vec![s])),
@@ -121,12 +123,12 @@ fn body_lang(i: &[u8]) -> IResult<&[u8], Option<Vec<&str>>> {
     ))(i)
 }
 
-fn body_param(i: &[u8]) -> IResult<&[u8], BodyParams> {
+fn body_param(i: &[u8]) -> IResult<&[u8], BodyParams<'_>> {
     alt((
         map(nil, |_| None),
         map(
             parenthesized_nonempty_list(map(
-                tuple((string_utf8, tag(" "), string_utf8)),
+                tuple((string_utf8, tag(" "), string)),
                 |(key, _, val)| (key, val),
             )),
             Option::from,
@@ -134,20 +136,21 @@ fn body_param(i: &[u8]) -> IResult<&[u8], BodyParams> {
     ))(i)
 }
 
-fn body_extension(i: &[u8]) -> IResult<&[u8], BodyExtension> {
+fn body_extension(i: &[u8], depth: usize) -> IResult<&[u8], BodyExtension<'_>> {
+    let (i, _) = check_nesting_depth(i, depth)?;
     alt((
         map(number, BodyExtension::Num),
         // Cannot find documentation on character encoding for body extension values.
         // So far, assuming UTF-8 seems fine, please report if you run into issues here.
         map(nstring_utf8, BodyExtension::Str),
         map(
-            parenthesized_nonempty_list(body_extension),
+            parenthesized_nonempty_list(|i| body_extension(i, depth + 1)),
             BodyExtension::List,
         ),
     ))(i)
 }
 
-fn body_disposition(i: &[u8]) -> IResult<&[u8], Option<ContentDisposition>> {
+fn body_disposition(i: &[u8]) -> IResult<&[u8], Option<ContentDisposition<'_>>> {
     alt((
         map(nil, |_| None),
         paren_delimited(map(
@@ -157,7 +160,7 @@ fn body_disposition(i: &[u8]) -> IResult<&[u8], Option<ContentDisposition>> {
     ))(i)
 }
 
-fn body_type_basic(i: &[u8]) -> IResult<&[u8], BodyStructure> {
+fn body_type_basic(i: &[u8], depth: usize) -> IResult<&[u8], BodyStructure<'_>> {
     map(
         tuple((
             string_utf8,
@@ -165,7 +168,7 @@ fn body_type_basic(i: &[u8]) -> IResult<&[u8], BodyStructure> {
             string_utf8,
             tag(" "),
             body_fields,
-            body_ext_1part,
+            |i| body_ext_1part(i, depth),
         )),
         |(ty, _, subtype, _, fields, ext)| BodyStructure::Basic {
             common: BodyContentCommon {
@@ -185,12 +188,12 @@ fn body_type_basic(i: &[u8]) -> IResult<&[u8], BodyStructure> {
                 description: fields.description,
                 transfer_encoding: fields.transfer_encoding,
             },
-            extension: ext.extension,
+            extensions: ext.extensions,
         },
     )(i)
 }
 
-fn body_type_text(i: &[u8]) -> IResult<&[u8], BodyStructure> {
+fn body_type_text(i: &[u8], depth: usize) -> IResult<&[u8], BodyStructure<'_>> {
     map(
         tuple((
             tag_no_case("\"TEXT\""),
@@ -200,12 +203,12 @@ fn body_type_text(i: &[u8]) -> IResult<&[u8], BodyStructure> {
             body_fields,
             tag(" "),
             number,
-            body_ext_1part,
+            |i| body_ext_1part(i, depth),
         )),
         |(_, _, subtype, _, fields, _, lines, ext)| BodyStructure::Text {
             common: BodyContentCommon {
                 ty: ContentType {
-                    ty: "TEXT",
+                    ty: Cow::Borrowed("TEXT"),
                     subtype,
                     params: fields.param,
                 },
@@ -221,12 +224,12 @@ fn body_type_text(i: &[u8]) -> IResult<&[u8], BodyStructure> {
                 transfer_encoding: fields.transfer_encoding,
             },
             lines,
-            extension: ext.extension,
+            extensions: ext.extensions,
         },
     )(i)
 }
 
-fn body_type_message(i: &[u8]) -> IResult<&[u8], BodyStructure> {
+fn body_type_message(i: &[u8], depth: usize) -> IResult<&[u8], BodyStructure<'_>> {
     map(
         tuple((
             tag_no_case("\"MESSAGE\" \"RFC822\""),
@@ -235,16 +238,16 @@ fn body_type_message(i: &[u8]) -> IResult<&[u8], BodyStructure> {
             tag(" "),
             envelope,
             tag(" "),
-            body,
+            |i| body_at_depth(i, depth + 1),
             tag(" "),
             number,
-            body_ext_1part,
+            |i| body_ext_1part(i, depth),
         )),
         |(_, _, fields, _, envelope, _, body, _, lines, ext)| BodyStructure::Message {
             common: BodyContentCommon {
                 ty: ContentType {
-                    ty: "MESSAGE",
-                    subtype: "RFC822",
+                    ty: Cow::Borrowed("MESSAGE"),
+                    subtype: Cow::Borrowed("RFC822"),
                     params: fields.param,
                 },
                 disposition: ext.disposition,
@@ -261,18 +264,23 @@ fn body_type_message(i: &[u8]) -> IResult<&[u8], BodyStructure> {
             envelope,
             body: Box::new(body),
             lines,
-            extension: ext.extension,
+            extensions: ext.extensions,
         },
     )(i)
 }
 
-fn body_type_multipart(i: &[u8]) -> IResult<&[u8], BodyStructure> {
+fn body_type_multipart(i: &[u8], depth: usize) -> IResult<&[u8], BodyStructure<'_>> {
     map(
-        tuple((many1(body), tag(" "), string_utf8, body_ext_mpart)),
+        tuple((
+            many1(|i| body_at_depth(i, depth + 1)),
+            tag(" "),
+            string_utf8,
+            |i| body_ext_mpart(i, depth),
+        )),
         |(bodies, _, subtype, ext)| BodyStructure::Multipart {
             common: BodyContentCommon {
                 ty: ContentType {
-                    ty: "MULTIPART",
+                    ty: Cow::Borrowed("MULTIPART"),
                     subtype,
                     params: ext.param,
                 },
@@ -281,28 +289,47 @@ fn body_type_multipart(i: &[u8]) -> IResult<&[u8], BodyStructure> {
                 location: ext.location,
             },
             bodies,
-            extension: ext.extension,
+            extensions: ext.extensions,
         },
     )(i)
 }
 
-pub(crate) fn body(i: &[u8]) -> IResult<&[u8], BodyStructure> {
-    paren_delimited(alt((
-        body_type_text,
-        body_type_message,
-        body_type_basic,
-        body_type_multipart,
-    )))(i)
+pub(crate) fn body(i: &[u8]) -> IResult<&[u8], BodyStructure<'_>> {
+    body_at_depth(i, 1)
 }
 
-pub(crate) fn msg_att_body_structure(i: &[u8]) -> IResult<&[u8], AttributeValue> {
-    map(tuple((tag_no_case("BODYSTRUCTURE "), body)), |(_, body)| {
-        AttributeValue::BodyStructure(body)
-    })(i)
+// The parts of multipart and message bodies, as well as their extension
+// data, count towards the nesting depth of the outermost body.
+fn body_at_depth(i: &[u8], depth: usize) -> IResult<&[u8], BodyStructure<'_>> {
+    let (i, _) = check_nesting_depth(i, depth)?;
+    context(
+        "body",
+        paren_delimited(alt((
+            |i| body_type_text(i, depth),
+            |i| body_type_message(i, depth),
+            |i| body_type_basic(i, depth),
+            |i| body_type_multipart(i, depth),
+        ))),
+    )(i)
+}
+
+// msg-att-static  =/ "BODY" ["STRUCTURE"] SP body
+// The non-extensible BODY form is parsed into the same structure, since it
+// is simply BODYSTRUCTURE without the extension data.
+pub(crate) fn msg_att_body_structure(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(
+        tuple((
+            alt((tag_no_case("BODYSTRUCTURE "), tag_no_case("BODY "))),
+            body,
+        )),
+        |(_, body)| AttributeValue::BodyStructure(body),
+    )(i)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+
     use super::*;
     use assert_matches::assert_matches;
 
@@ -310,9 +337,10 @@ mod tests {
 
     // body-fld-param SP body-fld-id SP body-fld-desc SP body-fld-enc SP body-fld-octets
     const BODY_FIELDS: &str = r#"("foo" "bar") "id" "desc" "7BIT" 1337"#;
-    const BODY_FIELD_PARAM_PAIR: (&str, &str) = ("foo", "bar");
-    const BODY_FIELD_ID: Option<&str> = Some("id");
-    const BODY_FIELD_DESC: Option<&str> = Some("desc");
+    const BODY_FIELD_PARAM_PAIR: (Cow<'static, str>, Cow<'static, [u8]>) =
+        (Cow::Borrowed("foo"), Cow::Borrowed(b"bar"));
+    const BODY_FIELD_ID: Option<Cow<'static, str>> = Some(Cow::Borrowed("id"));
+    const BODY_FIELD_DESC: Option<Cow<'static, str>> = Some(Cow::Borrowed("desc"));
     const BODY_FIELD_ENC: ContentEncoding = ContentEncoding::SevenBit;
     const BODY_FIELD_OCTETS: u32 = 1337;
 
@@ -322,8 +350,8 @@ mod tests {
             BodyStructure::Text {
                 common: BodyContentCommon {
                     ty: ContentType {
-                        ty: "TEXT",
-                        subtype: "PLAIN",
+                        ty: Cow::Borrowed("TEXT"),
+                        subtype: Cow::Borrowed("PLAIN"),
                         params: Some(vec![BODY_FIELD_PARAM_PAIR]),
                     },
                     disposition: None,
@@ -338,7 +366,7 @@ mod tests {
                     description: BODY_FIELD_DESC,
                 },
                 lines: 42,
-                extension: None,
+                extensions: vec![],
             },
         )
     }
@@ -350,7 +378,7 @@ mod tests {
         assert_matches!(
             body_param(br#"("foo" "bar")"#),
             Ok((EMPTY, Some(param))) => {
-                assert_eq!(param, vec![("foo", "bar")]);
+                assert_eq!(param, vec![BODY_FIELD_PARAM_PAIR]);
             }
         );
     }
@@ -377,30 +405,47 @@ mod tests {
     #[test]
     fn test_body_extension_data() {
         assert_matches!(
-            body_extension(br#""blah""#),
-            Ok((EMPTY, BodyExtension::Str(Some("blah"))))
+            body_extension(br#""blah""#, 1),
+            Ok((EMPTY, BodyExtension::Str(Some(Cow::Borrowed("blah")))))
         );
 
         assert_matches!(
-            body_extension(br#"NIL"#),
+            body_extension(br#"NIL"#, 1),
             Ok((EMPTY, BodyExtension::Str(None)))
         );
 
         assert_matches!(
-            body_extension(br#"("hello")"#),
+            body_extension(br#"("hello")"#, 1),
             Ok((EMPTY, BodyExtension::List(list))) => {
-                assert_eq!(list, vec![BodyExtension::Str(Some("hello"))]);
+                assert_eq!(list, vec![BodyExtension::Str(Some(Cow::Borrowed("hello")))]);
             }
         );
 
         assert_matches!(
-            body_extension(br#"(1337)"#),
+            body_extension(br#"(1337)"#, 1),
             Ok((EMPTY, BodyExtension::List(list))) => {
                 assert_eq!(list, vec![BodyExtension::Num(1337)]);
             }
         );
     }
 
+    #[test]
+    fn test_body_too_deep() {
+        let nested = |depth| format!("{}1{} ", "(".repeat(depth), ")".repeat(depth));
+        assert!(body_extension(nested(60).as_bytes(), 1).is_ok());
+        assert!(body_extension(nested(5000).as_bytes(), 1).is_err());
+
+        let multipart = |depth| {
+            format!(
+                "{}(\"TEXT\" \"PLAIN\" NIL NIL NIL \"7BIT\" 1 1){} ",
+                "(".repeat(depth),
+                " \"MIXED\")".repeat(depth)
+            )
+        };
+        assert!(body(multipart(60).as_bytes()).is_ok());
+        assert!(body(multipart(5000).as_bytes()).is_err());
+    }
+
     #[test]
     fn test_body_disposition_data() {
         assert_matches!(body_disposition(br#"NIL"#), Ok((EMPTY, None)));
@@ -409,9 +454,9 @@ mod tests {
             body_disposition(br#"("attachment" ("FILENAME" "pages.pdf"))"#),
             Ok((EMPTY, Some(disposition))) => {
                 assert_eq!(disposition, ContentDisposition {
-                    ty: "attachment",
+                    ty: Cow::Borrowed("attachment"),
                     params: Some(vec![
-                        ("FILENAME", "pages.pdf")
+                        (Cow::Borrowed("FILENAME"), Cow::Borrowed(&b"pages.pdf"[..]))
                     ])
                 });
             }
@@ -443,6 +488,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_body_structure_text_with_all_ext() {
+        let body_str = format!(
+            r#"("TEXT" "PLAIN" {} 42 "Q2hlY2sgSW50ZWdyaXR5IQ==" ("inline" NIL) ("en" "de") "http://example.com/a.txt" 7 ("x" (NIL 1)))"#,
+            BODY_FIELDS
+        );
+
+        assert_matches!(
+            body(body_str.as_bytes()),
+            Ok((EMPTY, BodyStructure::Text { common, other, extensions, .. })) => {
+                assert_eq!(other.md5.as_deref(), Some("Q2hlY2sgSW50ZWdyaXR5IQ=="));
+                assert_eq!(common.disposition, Some(ContentDisposition {
+                    ty: Cow::Borrowed("inline"),
+                    params: None,
+                }));
+                assert_eq!(common.language, Some(vec![Cow::Borrowed("en"), Cow::Borrowed("de")]));
+                assert_eq!(common.location.as_deref(), Some("http://example.com/a.txt"));
+                assert_eq!(extensions, vec![
+                    BodyExtension::Num(7),
+                    BodyExtension::List(vec![
+                        BodyExtension::Str(Some(Cow::Borrowed("x"))),
+                        BodyExtension::List(vec![BodyExtension::Str(None), BodyExtension::Num(1)]),
+                    ]),
+                ]);
+            }
+        );
+    }
+
     #[test]
     fn test_body_structure_basic() {
         const BODY: &[u8] = br#"("APPLICATION" "PDF" ("NAME" "pages.pdf") NIL NIL "BASE64" 38838 NIL ("attachment" ("FILENAME" "pages.pdf")) NIL NIL)"#;
@@ -453,13 +526,13 @@ mod tests {
                 assert_eq!(basic, BodyStructure::Basic {
                     common: BodyContentCommon {
                         ty: ContentType {
-                            ty: "APPLICATION",
-                            subtype: "PDF",
-                            params: Some(vec![("NAME", "pages.pdf")])
+                            ty: Cow::Borrowed("APPLICATION"),
+                            subtype: Cow::Borrowed("PDF"),
+                            params: Some(vec![(Cow::Borrowed("NAME"), Cow::Borrowed(&b"pages.pdf"[..]))])
                         },
                         disposition: Some(ContentDisposition {
-                            ty: "attachment",
-                            params: Some(vec![("FILENAME", "pages.pdf")])
+                            ty: Cow::Borrowed("attachment"),
+                            params: Some(vec![(Cow::Borrowed("FILENAME"), Cow::Borrowed(&b"pages.pdf"[..]))])
                         }),
                         language: None,
                         location: None,
@@ -471,7 +544,7 @@ mod tests {
                         md5: None,
                         description: None,
                     },
-                    extension: None,
+                    extensions: vec![],
                 })
             }
         );
@@ -507,8 +580,8 @@ mod tests {
                 assert_eq!(multipart, BodyStructure::Multipart {
                     common: BodyContentCommon {
                         ty: ContentType {
-                            ty: "MULTIPART",
-                            subtype: "ALTERNATIVE",
+                            ty: Cow::Borrowed("MULTIPART"),
+                            subtype: Cow::Borrowed("ALTERNATIVE"),
                             params: None
                         },
                         language: None,
@@ -519,7 +592,38 @@ mod tests {
                         text_body_struct1,
                         text_body_struct2,
                     ],
-                    extension: None
+                    extensions: vec![]
+                });
+            }
+        );
+    }
+
+    #[test]
+    fn test_body_structure_multipart_with_ext() {
+        let (text_body_str, _) = mock_body_text();
+        let signed = format!(
+            r#"({0}{0} "SIGNED" ("BOUNDARY" "b2") ("inline" NIL) "fr" NIL "x")"#,
+            text_body_str
+        );
+        let body_str = format!(
+            r#"({}{} "MIXED" ("BOUNDARY" "b1") NIL NIL NIL 1 2)"#,
+            text_body_str, signed
+        );
+
+        assert_matches!(
+            body(body_str.as_bytes()),
+            Ok((EMPTY, BodyStructure::Multipart { common, bodies, extensions })) => {
+                assert_eq!(common.ty.subtype, "MIXED");
+                assert_eq!(extensions, vec![BodyExtension::Num(1), BodyExtension::Num(2)]);
+                assert_matches!(&bodies[1], BodyStructure::Multipart { common, extensions, .. } => {
+                    assert_eq!(common.ty.subtype, "SIGNED");
+                    assert_eq!(common.disposition.as_ref().unwrap().ty, "inline");
+                    assert_eq!(common.language, Some(vec![Cow::Borrowed("fr")]));
+                    assert_eq!(common.location, None);
+                    assert_eq!(
+                        extensions,
+                        &vec![BodyExtension::Str(Some(Cow::Borrowed("x")))]
+                    );
                 });
             }
         );
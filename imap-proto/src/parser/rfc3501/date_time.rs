@@ -0,0 +1,165 @@
+use std::str::from_utf8;
+
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case, take},
+    combinator::{map, map_opt, value},
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+use crate::{parser::error::context, types::DateTime};
+
+// A fixed-width run of `n` ASCII digits.
+fn digits(n: usize) -> impl Fn(&[u8]) -> IResult<&[u8], u16> {
+    move |i| {
+        map_opt(take(n), |b: &[u8]| {
+            if b.iter().all(u8::is_ascii_digit) {
+                from_utf8(b).ok()?.parse().ok()
+            } else {
+                None
+            }
+        })(i)
+    }
+}
+
+// date-day-fixed  = (SP DIGIT) / 2DIGIT
+//                     ; Fixed-format version of date-day
+fn date_day_fixed(i: &[u8]) -> IResult<&[u8], u16> {
+    alt((preceded(tag(" "), digits(1)), digits(2)))(i)
+}
+
+// date-month      = "Jan" / "Feb" / "Mar" / "Apr" / "May" / "Jun" /
+//                   "Jul" / "Aug" / "Sep" / "Oct" / "Nov" / "Dec"
+pub(crate) fn date_month(i: &[u8]) -> IResult<&[u8], u8> {
+    alt((
+        value(1, tag_no_case("Jan")),
+        value(2, tag_no_case("Feb")),
+        value(3, tag_no_case("Mar")),
+        value(4, tag_no_case("Apr")),
+        value(5, tag_no_case("May")),
+        value(6, tag_no_case("Jun")),
+        value(7, tag_no_case("Jul")),
+        value(8, tag_no_case("Aug")),
+        value(9, tag_no_case("Sep")),
+        value(10, tag_no_case("Oct")),
+        value(11, tag_no_case("Nov")),
+        value(12, tag_no_case("Dec")),
+    ))(i)
+}
+
+// time            = 2DIGIT ":" 2DIGIT ":" 2DIGIT
+//                     ; Hours minutes seconds
+fn time(i: &[u8]) -> IResult<&[u8], (u16, u16, u16)> {
+    map(
+        tuple((digits(2), tag(":"), digits(2), tag(":"), digits(2))),
+        |(h, _, m, _, s)| (h, m, s),
+    )(i)
+}
+
+// zone            = ("+" / "-") 4DIGIT
+//                     ; Signed four-digit value of hhmm representing
+//                     ; hours and minutes east of Greenwich (that is,
+//                     ; the amount that the given time differs from
+//                     ; Universal Time).
+fn zone(i: &[u8]) -> IResult<&[u8], i16> {
+    map(
+        tuple((
+            alt((value(1, tag("+")), value(-1, tag("-")))),
+            digits(2),
+            digits(2),
+        )),
+        |(sign, h, m)| sign * (h * 60 + m) as i16,
+    )(i)
+}
+
+// date-time       = DQUOTE date-day-fixed "-" date-month "-" date-year
+//                   SP time SP zone DQUOTE
+// This parses the contents between the quotes, as `DateTime::parse` takes
+// them; `quoted_date_time` parses the whole date-time.
+// [RFC3501 - 9. Formal Syntax](https://tools.ietf.org/html/rfc3501#section-9)
+pub fn date_time(i: &[u8]) -> IResult<&[u8], DateTime> {
+    context(
+        "date-time",
+        map_opt(
+            tuple((
+                date_day_fixed,
+                tag("-"),
+                date_month,
+                tag("-"),
+                digits(4),
+                tag(" "),
+                time,
+                tag(" "),
+                zone,
+            )),
+            |(day, _, month, _, year, _, (hour, minute, second), _, offset)| {
+                let dt = DateTime {
+                    year,
+                    month,
+                    day: day as u8,
+                    hour: hour as u8,
+                    minute: minute as u8,
+                    second: second as u8,
+                    offset,
+                };
+                if dt.day >= 1 && dt.day <= 31 && dt.hour < 24 && dt.minute < 60 && dt.second < 61 {
+                    Some(dt)
+                } else {
+                    None
+                }
+            },
+        ),
+    )(i)
+}
+
+pub(crate) fn quoted_date_time(i: &[u8]) -> IResult<&[u8], DateTime> {
+    delimited(tag("\""), date_time, tag("\""))(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_time() {
+        assert_eq!(
+            DateTime::parse("17-Jul-1996 02:44:25 -0700"),
+            Some(DateTime {
+                year: 1996,
+                month: 7,
+                day: 17,
+                hour: 2,
+                minute: 44,
+                second: 25,
+                offset: -420,
+            })
+        );
+        assert_eq!(
+            DateTime::parse(" 1-Feb-2020 23:59:60 +0530"),
+            Some(DateTime {
+                year: 2020,
+                month: 2,
+                day: 1,
+                hour: 23,
+                minute: 59,
+                second: 60,
+                offset: 330,
+            })
+        );
+        assert_eq!(DateTime::parse("17-Jul-1996 02:44:25"), None);
+        assert_eq!(DateTime::parse("32-Jul-1996 02:44:25 +0000"), None);
+        assert_eq!(DateTime::parse("17-Jul-1996 02:44:25 +0000 "), None);
+    }
+
+    #[test]
+    fn test_display() {
+        for s in &[
+            "17-Jul-1996 02:44:25 -0700",
+            " 1-Feb-2020 23:59:60 +0530",
+            " 9-Dec-0999 00:00:00 -0030",
+        ] {
+            assert_eq!(DateTime::parse(s).unwrap().to_string(), *s);
+        }
+    }
+}
@@ -4,28 +4,59 @@
 //! INTERNET MESSAGE ACCESS PROTOCOL
 //!
 
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::str::from_utf8;
 
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case, take_while, take_while1},
     character::streaming::char,
-    combinator::{map, map_res, opt, recognize},
-    multi::{many0, many1},
+    combinator::{map, map_opt, map_res, opt, recognize, verify},
+    multi::{many0, many1, separated_nonempty_list},
     sequence::{delimited, pair, preceded, terminated, tuple},
-    IResult,
+    IResult, Needed,
 };
 
 use crate::{
     parser::{
-        core::*, rfc3501::body::*, rfc3501::body_structure::*, rfc4551, rfc5161,
-        rfc5464::resp_metadata, ParseResult,
+        core::*, error, error::context, options, rfc2221, rfc2342, rfc2971, rfc3501::body::*,
+        rfc3501::body_structure::*, rfc3516, rfc4315, rfc4467, rfc4469, rfc4731, rfc4978, rfc5161,
+        rfc5255, rfc5256, rfc5257, rfc5258, rfc5465, rfc7889, rfc8438, rfc8474, rfc8514, rfc8970,
+        rfc9586, Options, ParseError, ParseResult, Parsed,
     },
     types::*,
 };
 
+#[cfg(feature = "gmail")]
+use crate::parser::gmail;
+#[cfg(feature = "quota")]
+use crate::parser::rfc2087;
+#[cfg(feature = "acl")]
+use crate::parser::rfc4314;
+#[cfg(feature = "metadata")]
+use crate::parser::rfc5464;
+#[cfg(feature = "condstore")]
+use crate::parser::{rfc4551, rfc7162};
+
 pub mod body;
 pub mod body_structure;
+pub mod date_time;
+
+// Syntax that was removed in IMAP4rev2 fails to parse when the `imap4rev2`
+// option is set.
+// [RFC9051 - Appendix E](https://tools.ietf.org/html/rfc9051#appendix-E)
+fn rev1_only<'a, O, F>(f: F) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], O>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    move |i| {
+        if options::current().imap4rev2 {
+            return Err(nom::Err::Error((i, nom::error::ErrorKind::Verify)));
+        }
+        f(i)
+    }
+}
 
 fn is_tag_char(c: u8) -> bool {
     c != b'+' && is_astring_char(c)
@@ -51,40 +82,59 @@ fn status(i: &[u8]) -> IResult<&[u8], Status> {
     alt((status_ok, status_no, status_bad, status_preauth, status_bye))(i)
 }
 
-fn mailbox(i: &[u8]) -> IResult<&[u8], &str> {
-    map(astring_utf8, |s| {
+// mailbox         = "INBOX" / astring
+// With UTF8=ACCEPT, names sent as atoms may also contain UTF-8.
+pub(crate) fn mailbox(i: &[u8]) -> IResult<&[u8], Cow<'_, str>> {
+    let utf8 = options::current().utf8_accept;
+    let name = alt((
+        map_res(
+            take_while1(move |c| is_astring_char(c) || (utf8 && c >= 0x80)),
+            |s| from_utf8(s).map(Cow::Borrowed),
+        ),
+        string_utf8,
+    ));
+    map(name, |s| {
         if s.eq_ignore_ascii_case("INBOX") {
-            "INBOX"
+            Cow::Borrowed("INBOX")
         } else {
             s
         }
     })(i)
 }
 
-fn flag_extension(i: &[u8]) -> IResult<&[u8], &str> {
+pub(crate) fn flag_extension(i: &[u8]) -> IResult<&[u8], &str> {
     map_res(
         recognize(pair(tag(b"\\"), take_while(is_atom_char))),
         from_utf8,
     )(i)
 }
 
-fn flag(i: &[u8]) -> IResult<&[u8], &str> {
-    alt((flag_extension, atom))(i)
+// flag            = "\Answered" / "\Flagged" / "\Deleted" /
+//                   "\Seen" / "\Draft" / flag-keyword / flag-extension
+//                     ; Does not include "\Recent"
+// System flags are matched case-insensitively. "\Recent" is accepted here as
+// well, since it appears in FETCH and FLAGS responses.
+fn flag(i: &[u8]) -> IResult<&[u8], Flag<'_>> {
+    alt((
+        map(flag_extension, Flag::system),
+        map(atom, |s| Flag::Keyword(Cow::Borrowed(s))),
+    ))(i)
 }
 
-fn flag_list(i: &[u8]) -> IResult<&[u8], Vec<&str>> {
+pub(crate) fn flag_list(i: &[u8]) -> IResult<&[u8], Vec<Flag<'_>>> {
     parenthesized_list(flag)(i)
 }
 
-fn flag_perm(i: &[u8]) -> IResult<&[u8], &str> {
-    alt((map_res(tag(b"\\*"), from_utf8), flag))(i)
+// flag-perm       = flag / "\*"
+fn flag_perm(i: &[u8]) -> IResult<&[u8], Flag<'_>> {
+    alt((map(tag(b"\\*"), |_| Flag::MayCreate), flag))(i)
 }
 
-fn resp_text_code_alert(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_alert(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(tag_no_case(b"ALERT"), |_| ResponseCode::Alert)(i)
 }
 
-fn resp_text_code_badcharset(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_badcharset(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(
         preceded(
             tag_no_case(b"BADCHARSET"),
@@ -97,15 +147,15 @@ fn resp_text_code_badcharset(i: &[u8]) -> IResult<&[u8], ResponseCode> {
     )(i)
 }
 
-fn resp_text_code_capability(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_capability(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(capability_data, ResponseCode::Capabilities)(i)
 }
 
-fn resp_text_code_parse(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_parse(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(tag_no_case(b"PARSE"), |_| ResponseCode::Parse)(i)
 }
 
-fn resp_text_code_permanent_flags(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_permanent_flags(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(
         preceded(
             tag_no_case(b"PERMANENTFLAGS "),
@@ -115,152 +165,342 @@ fn resp_text_code_permanent_flags(i: &[u8]) -> IResult<&[u8], ResponseCode> {
     )(i)
 }
 
-fn resp_text_code_read_only(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_read_only(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(tag_no_case(b"READ-ONLY"), |_| ResponseCode::ReadOnly)(i)
 }
 
-fn resp_text_code_read_write(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_read_write(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(tag_no_case(b"READ-WRITE"), |_| ResponseCode::ReadWrite)(i)
 }
 
-fn resp_text_code_try_create(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_try_create(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(tag_no_case(b"TRYCREATE"), |_| ResponseCode::TryCreate)(i)
 }
 
-fn resp_text_code_uid_validity(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_uid_validity(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(
         preceded(tag_no_case(b"UIDVALIDITY "), number),
         ResponseCode::UidValidity,
     )(i)
 }
 
-fn resp_text_code_uid_next(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_uid_next(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(
         preceded(tag_no_case(b"UIDNEXT "), number),
         ResponseCode::UidNext,
     )(i)
 }
 
-fn resp_text_code_unseen(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+fn resp_text_code_unseen(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     map(
         preceded(tag_no_case(b"UNSEEN "), number),
         ResponseCode::Unseen,
     )(i)
 }
 
-fn resp_text_code(i: &[u8]) -> IResult<&[u8], ResponseCode> {
-    // Per the spec, the closing tag should be "] ".
-    // See `resp_text` for more on why this is done differently.
-    delimited(
-        tag(b"["),
-        alt((
-            resp_text_code_alert,
-            resp_text_code_badcharset,
-            resp_text_code_capability,
-            resp_text_code_parse,
-            resp_text_code_permanent_flags,
-            resp_text_code_uid_validity,
-            resp_text_code_uid_next,
-            resp_text_code_unseen,
-            resp_text_code_read_only,
-            resp_text_code_read_write,
-            resp_text_code_try_create,
-            rfc4551::resp_text_code_highest_mod_seq,
+// Codes that have a dedicated parser. If one of these does not parse, the
+// code is malformed and is left in the response text instead of being
+// returned as `ResponseCode::Other`.
+const KNOWN_RESPONSE_CODES: &[&str] = &[
+    "ALERT",
+    "APPENDUID",
+    "BADCHARSET",
+    "BADEVENT",
+    "BADURL",
+    "CAPABILITY",
+    "COMPRESSIONACTIVE",
+    "COPYUID",
+    #[cfg(feature = "condstore")]
+    "HIGHESTMODSEQ",
+    "MAILBOXID",
+    #[cfg(feature = "metadata")]
+    "METADATA",
+    #[cfg(feature = "condstore")]
+    "MODIFIED",
+    #[cfg(feature = "condstore")]
+    "NOMODSEQ",
+    "NOTIFICATIONOVERFLOW",
+    #[cfg(feature = "quota")]
+    "OVERQUOTA",
+    "PARSE",
+    "PERMANENTFLAGS",
+    "READ-ONLY",
+    "READ-WRITE",
+    "REFERRAL",
+    "TOOBIG",
+    "TRYCREATE",
+    "UIDNEXT",
+    "UIDNOTSTICKY",
+    "UIDREQUIRED",
+    "UIDVALIDITY",
+    "URLMECH",
+    "UNSEEN",
+];
+
+// resp-text-code  =/ atom [SP 1*<any TEXT-CHAR except "]">]
+fn resp_text_code_other(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    let utf8 = options::current().utf8_accept;
+    map(
+        tuple((
+            verify(atom, |name: &str| {
+                !KNOWN_RESPONSE_CODES
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(name))
+            }),
+            opt(preceded(
+                char(' '),
+                map_res(
+                    take_while1(move |c| c != b']' && (is_text_char(c) || (utf8 && c >= 0x80))),
+                    from_utf8,
+                ),
+            )),
         )),
-        tag(b"]"),
+        |(name, value)| ResponseCode::Other(Cow::Borrowed(name), value.map(Cow::Borrowed)),
+    )(i)
+}
+
+fn resp_text_code(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    context(
+        "resp-text-code", // Per the spec, the closing tag should be "] ".
+        // See `resp_text` for more on why this is done differently.
+        delimited(
+            tag(b"["),
+            alt((
+                resp_text_code_rfc3501,
+                resp_text_code_extension,
+                resp_text_code_other,
+            )),
+            tag(b"]"),
+        ),
     )(i)
 }
 
-fn capability(i: &[u8]) -> IResult<&[u8], Capability> {
+fn resp_text_code_rfc3501(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     alt((
-        map(tag_no_case(b"IMAP4rev1"), |_| Capability::Imap4rev1),
-        map(preceded(tag_no_case(b"AUTH="), atom), Capability::Auth),
-        map(atom, Capability::Atom),
+        resp_text_code_alert,
+        resp_text_code_badcharset,
+        resp_text_code_capability,
+        resp_text_code_parse,
+        resp_text_code_permanent_flags,
+        resp_text_code_uid_validity,
+        resp_text_code_uid_next,
+        resp_text_code_unseen,
+        resp_text_code_read_only,
+        resp_text_code_read_write,
+        resp_text_code_try_create,
     ))(i)
 }
 
+fn resp_text_code_extension(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    alt((
+        #[cfg(feature = "condstore")]
+        rfc4551::resp_text_code_highest_mod_seq,
+        #[cfg(feature = "condstore")]
+        rfc4551::resp_text_code_no_mod_seq,
+        #[cfg(feature = "condstore")]
+        rfc4551::resp_text_code_modified,
+        #[cfg(feature = "metadata")]
+        rfc5464::resp_text_code_metadata,
+        rfc8474::resp_text_code_mailbox_id,
+        rfc4315::resp_text_code_append_uid,
+        rfc4315::resp_text_code_copy_uid,
+        rfc4315::resp_text_code_uid_not_sticky,
+        rfc4467::resp_text_code_url_mech,
+        rfc4469::resp_text_code_too_big,
+        rfc4469::resp_text_code_bad_url,
+        rfc4978::resp_text_code_compression_active,
+        rfc2221::resp_text_code_referral,
+        rfc5465::resp_text_code_notification_overflow,
+        rfc5465::resp_text_code_bad_event,
+        #[cfg(feature = "quota")]
+        rfc2087::resp_text_code_over_quota,
+        rfc9586::resp_text_code_uid_required,
+    ))(i)
+}
+
+// capability = ("AUTH=" auth-type) / atom
+// Capability names are case-insensitive; well-known ones are mapped to their
+// own variant and anything else is returned as `Capability::Atom`.
+pub(crate) fn capability(i: &[u8]) -> IResult<&[u8], Capability<'_>> {
+    map(atom, |s| {
+        if s.len() > 5 && s[..5].eq_ignore_ascii_case("AUTH=") {
+            return Capability::Auth(Cow::Borrowed(&s[5..]));
+        }
+        // compress-algorithm = "COMPRESS=" algorithm (RFC 4978)
+        if s.len() > 9 && s[..9].eq_ignore_ascii_case("COMPRESS=") {
+            return Capability::Compress(Cow::Borrowed(&s[9..]));
+        }
+        // append-limit = "APPENDLIMIT" ["=" number64] (RFC 7889)
+        if s.len() > 12 && s[..12].eq_ignore_ascii_case("APPENDLIMIT=") {
+            if let Ok(limit) = s[12..].parse() {
+                return Capability::AppendLimit(Some(limit));
+            }
+        }
+        match s.to_ascii_uppercase().as_str() {
+            "IMAP4REV1" => Capability::Imap4rev1,
+            "IMAP4REV2" => Capability::Imap4rev2,
+            "STARTTLS" => Capability::StartTls,
+            "LOGINDISABLED" => Capability::LoginDisabled,
+            "IDLE" => Capability::Idle,
+            "NAMESPACE" => Capability::Namespace,
+            "ID" => Capability::Id,
+            "ENABLE" => Capability::Enable,
+            "CONDSTORE" => Capability::Condstore,
+            "QRESYNC" => Capability::Qresync,
+            "UIDPLUS" => Capability::UidPlus,
+            "MOVE" => Capability::Move,
+            "UNSELECT" => Capability::Unselect,
+            "CHILDREN" => Capability::Children,
+            "LITERAL+" => Capability::LiteralPlus,
+            "LITERAL-" => Capability::LiteralMinus,
+            "BINARY" => Capability::Binary,
+            "SPECIAL-USE" => Capability::SpecialUse,
+            "LIST-EXTENDED" => Capability::ListExtended,
+            "LANGUAGE" => Capability::Language,
+            "APPENDLIMIT" => Capability::AppendLimit(None),
+            "REPLACE" => Capability::Replace,
+            "LOGIN-REFERRALS" => Capability::LoginReferrals,
+            "MAILBOX-REFERRALS" => Capability::MailboxReferrals,
+            "CLIENTID" => Capability::ClientId,
+            "UIDONLY" => Capability::UidOnly,
+            "PARTIAL" => Capability::Partial,
+            "MULTIAPPEND" => Capability::MultiAppend,
+            "CATENATE" => Capability::Catenate,
+            _ => Capability::Atom(Cow::Borrowed(s)),
+        }
+    })(i)
+}
+
 fn ensure_capabilities_contains_imap4rev<'a>(
     capabilities: Vec<Capability<'a>>,
 ) -> Result<Vec<Capability<'a>>, ()> {
-    if capabilities.contains(&Capability::Imap4rev1) {
+    if capabilities.contains(&Capability::Imap4rev1)
+        || capabilities.contains(&Capability::Imap4rev2)
+    {
         Ok(capabilities)
     } else {
         Err(())
     }
 }
 
-fn capability_data(i: &[u8]) -> IResult<&[u8], Vec<Capability>> {
-    map_res(
-        preceded(
-            tag_no_case(b"CAPABILITY"),
-            many0(preceded(char(' '), capability)),
+fn capability_data(i: &[u8]) -> IResult<&[u8], Vec<Capability<'_>>> {
+    context(
+        "capability-data",
+        map_res(
+            preceded(
+                tag_no_case(b"CAPABILITY"),
+                many0(preceded(char(' '), capability)),
+            ),
+            ensure_capabilities_contains_imap4rev,
         ),
-        ensure_capabilities_contains_imap4rev,
     )(i)
 }
 
-fn resp_capability(i: &[u8]) -> IResult<&[u8], Response> {
+fn resp_capability(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     map(capability_data, Response::Capabilities)(i)
 }
 
-fn mailbox_data_search(i: &[u8]) -> IResult<&[u8], Response> {
+fn mailbox_data_search(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     map(
         preceded(tag_no_case(b"SEARCH"), many0(preceded(tag(" "), number))),
         Response::IDs,
     )(i)
 }
 
-fn mailbox_data_flags(i: &[u8]) -> IResult<&[u8], Response> {
+fn mailbox_data_flags(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     map(preceded(tag_no_case("FLAGS "), flag_list), |flags| {
         Response::MailboxData(MailboxDatum::Flags(flags))
     })(i)
 }
 
-fn mailbox_data_exists(i: &[u8]) -> IResult<&[u8], Response> {
+fn mailbox_data_exists(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     map(terminated(number, tag_no_case(" EXISTS")), |num| {
         Response::MailboxData(MailboxDatum::Exists(num))
     })(i)
 }
 
-#[allow(clippy::type_complexity)]
-fn mailbox_list(i: &[u8]) -> IResult<&[u8], (Vec<&str>, Option<&str>, &str)> {
-    map(
-        tuple((
-            flag_list,
-            tag(b" "),
-            alt((map(quoted_utf8, Some), map(nil, |_| None))),
-            tag(b" "),
-            mailbox,
-        )),
-        |(flags, _, delimiter, _, name)| (flags, delimiter, name),
+// mbx-list-flags  = *(mbx-list-oflag SP) mbx-list-sflag
+//                   *(SP mbx-list-oflag) /
+//                   mbx-list-oflag *(SP mbx-list-oflag)
+// Extended with the attributes defined by RFC 5258 and RFC 6154.
+// Attributes not known here are returned as extensions.
+fn name_attribute(i: &[u8]) -> IResult<&[u8], NameAttribute<'_>> {
+    map(alt((flag_extension, atom)), |s| {
+        match s.to_ascii_lowercase().as_str() {
+            "\\noinferiors" => NameAttribute::NoInferiors,
+            "\\noselect" => NameAttribute::NoSelect,
+            "\\marked" => NameAttribute::Marked,
+            "\\unmarked" => NameAttribute::Unmarked,
+            "\\haschildren" => NameAttribute::HasChildren,
+            "\\hasnochildren" => NameAttribute::HasNoChildren,
+            "\\nonexistent" => NameAttribute::NonExistent,
+            "\\subscribed" => NameAttribute::Subscribed,
+            "\\remote" => NameAttribute::Remote,
+            "\\all" => NameAttribute::All,
+            "\\archive" => NameAttribute::Archive,
+            "\\drafts" => NameAttribute::Drafts,
+            "\\flagged" => NameAttribute::Flagged,
+            "\\junk" => NameAttribute::Junk,
+            "\\sent" => NameAttribute::Sent,
+            "\\trash" => NameAttribute::Trash,
+            _ => NameAttribute::Extension(Cow::Borrowed(s)),
+        }
+    })(i)
+}
+
+// mailbox-list = "(" [mbx-list-flags] ")" SP
+//                (DQUOTE QUOTED-CHAR DQUOTE / nil) SP mailbox
+//                [SP mbox-list-extended]
+//              ; This is the list information pointed to by the ABNF
+//              ; item "mailbox-data", which is defined in [IMAP4]
+// [RFC5258 - 6. Formal Syntax](https://tools.ietf.org/html/rfc5258#section-6)
+pub(crate) fn mailbox_list(i: &[u8]) -> IResult<&[u8], MailboxDatum<'_>> {
+    context(
+        "mailbox-list",
+        map(
+            tuple((
+                parenthesized_list(name_attribute),
+                tag(b" "),
+                alt((map(quoted_utf8, Some), map(nil, |_| None))),
+                tag(b" "),
+                mailbox,
+                opt(preceded(tag(b" "), rfc5258::mbox_list_extended)),
+            )),
+            |(flags, _, delimiter, _, name, extended_data)| MailboxDatum::List {
+                flags,
+                delimiter,
+                name: MailboxName::new(name),
+                extended_data: extended_data.unwrap_or_default(),
+            },
+        ),
     )(i)
 }
 
-fn mailbox_data_list(i: &[u8]) -> IResult<&[u8], Response> {
-    map(preceded(tag_no_case("LIST "), mailbox_list), |data| {
-        Response::MailboxData(MailboxDatum::List {
-            flags: data.0,
-            delimiter: data.1,
-            name: data.2,
-        })
-    })(i)
+fn mailbox_data_list(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        preceded(tag_no_case("LIST "), mailbox_list),
+        Response::MailboxData,
+    )(i)
 }
 
-fn mailbox_data_lsub(i: &[u8]) -> IResult<&[u8], Response> {
-    map(preceded(tag_no_case("LSUB "), mailbox_list), |data| {
-        Response::MailboxData(MailboxDatum::List {
-            flags: data.0,
-            delimiter: data.1,
-            name: data.2,
-        })
-    })(i)
+fn mailbox_data_lsub(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        preceded(tag_no_case("LSUB "), mailbox_list),
+        Response::MailboxData,
+    )(i)
 }
 
 // Unlike `status_att` in the RFC syntax, this includes the value,
 // so that it can return a valid enum object instead of just a key.
-fn status_att(i: &[u8]) -> IResult<&[u8], StatusAttribute> {
+fn status_att(i: &[u8]) -> IResult<&[u8], StatusAttribute<'_>> {
     alt((
+        #[cfg(feature = "condstore")]
         rfc4551::status_att_val_highest_mod_seq,
+        rfc8438::status_att_val_size,
+        rfc8474::status_att_val_mailbox_id,
+        rfc7889::status_att_val_append_limit,
+        #[cfg(feature = "quota")]
+        rfc2087::status_att_val_deleted,
         map(
             preceded(tag_no_case("MESSAGES "), number),
             StatusAttribute::Messages,
@@ -284,58 +524,73 @@ fn status_att(i: &[u8]) -> IResult<&[u8], StatusAttribute> {
     ))(i)
 }
 
-fn status_att_list(i: &[u8]) -> IResult<&[u8], Vec<StatusAttribute>> {
-    parenthesized_nonempty_list(status_att)(i)
+// "(" [status-att-list] ")"
+// The list may be empty, e.g. in STATUS responses sent for NOTIFY (RFC 5465).
+fn status_att_list(i: &[u8]) -> IResult<&[u8], Vec<StatusAttribute<'_>>> {
+    context("status-att-list", parenthesized_list(status_att))(i)
 }
 
-fn mailbox_data_status(i: &[u8]) -> IResult<&[u8], Response> {
+fn mailbox_data_status(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     map(
         tuple((tag_no_case("STATUS "), mailbox, tag(" "), status_att_list)),
-        |(_, mailbox, _, status)| Response::MailboxData(MailboxDatum::Status { mailbox, status }),
+        |(_, mailbox, _, status)| {
+            Response::MailboxData(MailboxDatum::Status {
+                mailbox: MailboxName::new(mailbox),
+                status,
+            })
+        },
     )(i)
 }
 
-fn mailbox_data_recent(i: &[u8]) -> IResult<&[u8], Response> {
+fn mailbox_data_recent(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     map(terminated(number, tag_no_case(" RECENT")), |num| {
         Response::MailboxData(MailboxDatum::Recent(num))
     })(i)
 }
 
-fn mailbox_data(i: &[u8]) -> IResult<&[u8], Response> {
-    alt((
-        mailbox_data_flags,
-        mailbox_data_exists,
-        mailbox_data_list,
-        mailbox_data_lsub,
-        mailbox_data_status,
-        mailbox_data_recent,
-        mailbox_data_search,
-    ))(i)
+fn mailbox_data(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    context(
+        "mailbox-data",
+        alt((
+            mailbox_data_flags,
+            mailbox_data_exists,
+            mailbox_data_list,
+            mailbox_data_lsub,
+            #[cfg(feature = "gmail")]
+            gmail::mailbox_data_xlist,
+            mailbox_data_status,
+            rev1_only(mailbox_data_recent),
+            rev1_only(mailbox_data_search),
+        )),
+    )(i)
 }
 
 // An address structure is a parenthesized list that describes an
 // electronic mail address.
-fn address(i: &[u8]) -> IResult<&[u8], Address> {
-    paren_delimited(map(
-        tuple((
-            nstring,
-            tag(" "),
-            nstring,
-            tag(" "),
-            nstring,
-            tag(" "),
-            nstring,
+fn address(i: &[u8]) -> IResult<&[u8], Address<'_>> {
+    context(
+        "address",
+        paren_delimited(map(
+            tuple((
+                nstring,
+                tag(" "),
+                nstring,
+                tag(" "),
+                nstring,
+                tag(" "),
+                nstring,
+            )),
+            |(name, _, adl, _, mailbox, _, host)| Address {
+                name,
+                adl,
+                mailbox,
+                host,
+            },
         )),
-        |(name, _, adl, _, mailbox, _, host)| Address {
-            name,
-            adl,
-            mailbox,
-            host,
-        },
-    ))(i)
+    )(i)
 }
 
-fn opt_addresses(i: &[u8]) -> IResult<&[u8], Option<Vec<Address>>> {
+fn opt_addresses(i: &[u8]) -> IResult<&[u8], Option<Vec<Address<'_>>>> {
     alt((
         map(nil, |_s| None),
         map(
@@ -345,92 +600,94 @@ fn opt_addresses(i: &[u8]) -> IResult<&[u8], Option<Vec<Address>>> {
     ))(i)
 }
 
-pub(crate) fn envelope(i: &[u8]) -> IResult<&[u8], Envelope> {
-    paren_delimited(map(
-        tuple((
-            nstring,
-            tag(" "),
-            nstring,
-            tag(" "),
-            opt_addresses,
-            tag(" "),
-            opt_addresses,
-            tag(" "),
-            opt_addresses,
-            tag(" "),
-            opt_addresses,
-            tag(" "),
-            opt_addresses,
-            tag(" "),
-            opt_addresses,
-            tag(" "),
-            nstring,
-            tag(" "),
-            nstring,
+pub(crate) fn envelope(i: &[u8]) -> IResult<&[u8], Envelope<'_>> {
+    context(
+        "envelope",
+        paren_delimited(map(
+            tuple((
+                nstring,
+                tag(" "),
+                nstring,
+                tag(" "),
+                opt_addresses,
+                tag(" "),
+                opt_addresses,
+                tag(" "),
+                opt_addresses,
+                tag(" "),
+                opt_addresses,
+                tag(" "),
+                opt_addresses,
+                tag(" "),
+                opt_addresses,
+                tag(" "),
+                nstring,
+                tag(" "),
+                nstring,
+            )),
+            |(
+                date,
+                _,
+                subject,
+                _,
+                from,
+                _,
+                sender,
+                _,
+                reply_to,
+                _,
+                to,
+                _,
+                cc,
+                _,
+                bcc,
+                _,
+                in_reply_to,
+                _,
+                message_id,
+            )| Envelope {
+                date,
+                subject,
+                from,
+                sender,
+                reply_to,
+                to,
+                cc,
+                bcc,
+                in_reply_to,
+                message_id,
+            },
         )),
-        |(
-            date,
-            _,
-            subject,
-            _,
-            from,
-            _,
-            sender,
-            _,
-            reply_to,
-            _,
-            to,
-            _,
-            cc,
-            _,
-            bcc,
-            _,
-            in_reply_to,
-            _,
-            message_id,
-        )| Envelope {
-            date,
-            subject,
-            from,
-            sender,
-            reply_to,
-            to,
-            cc,
-            bcc,
-            in_reply_to,
-            message_id,
-        },
-    ))(i)
+    )(i)
 }
 
-fn msg_att_envelope(i: &[u8]) -> IResult<&[u8], AttributeValue> {
+fn msg_att_envelope(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
     map(preceded(tag_no_case("ENVELOPE "), envelope), |envelope| {
         AttributeValue::Envelope(Box::new(envelope))
     })(i)
 }
 
-fn msg_att_internal_date(i: &[u8]) -> IResult<&[u8], AttributeValue> {
+fn msg_att_internal_date(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
     map(
-        preceded(tag_no_case("INTERNALDATE "), nstring_utf8),
-        |date| AttributeValue::InternalDate(date.unwrap()),
+        preceded(tag_no_case("INTERNALDATE "), date_time::quoted_date_time),
+        AttributeValue::InternalDate,
     )(i)
 }
 
-fn msg_att_flags(i: &[u8]) -> IResult<&[u8], AttributeValue> {
+fn msg_att_flags(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
     map(
         preceded(tag_no_case("FLAGS "), flag_list),
         AttributeValue::Flags,
     )(i)
 }
 
-fn msg_att_rfc822(i: &[u8]) -> IResult<&[u8], AttributeValue> {
-    map(
-        preceded(tag_no_case("RFC822 "), nstring),
-        AttributeValue::Rfc822,
-    )(i)
+fn msg_att_rfc822(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(preceded(tag_no_case("RFC822 "), nstring), |raw| {
+        AttributeValue::Rfc822(raw)
+    })(i)
 }
 
-fn msg_att_rfc822_header(i: &[u8]) -> IResult<&[u8], AttributeValue> {
+fn msg_att_rfc822_header(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
     // extra space workaround for DavMail
     map(
         tuple((tag_no_case("RFC822.HEADER "), opt(tag(b" ")), nstring)),
@@ -438,56 +695,82 @@ fn msg_att_rfc822_header(i: &[u8]) -> IResult<&[u8], AttributeValue> {
     )(i)
 }
 
-fn msg_att_rfc822_size(i: &[u8]) -> IResult<&[u8], AttributeValue> {
+fn msg_att_rfc822_size(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
     map(
-        preceded(tag_no_case("RFC822.SIZE "), number),
+        preceded(tag_no_case("RFC822.SIZE "), number_64),
         AttributeValue::Rfc822Size,
     )(i)
 }
 
-fn msg_att_rfc822_text(i: &[u8]) -> IResult<&[u8], AttributeValue> {
-    map(
-        preceded(tag_no_case("RFC822.TEXT "), nstring),
-        AttributeValue::Rfc822Text,
-    )(i)
+fn msg_att_rfc822_text(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(preceded(tag_no_case("RFC822.TEXT "), nstring), |raw| {
+        AttributeValue::Rfc822Text(raw)
+    })(i)
 }
 
-fn msg_att_uid(i: &[u8]) -> IResult<&[u8], AttributeValue> {
-    map(preceded(tag_no_case("UID "), number), AttributeValue::Uid)(i)
+fn msg_att_uid(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(preceded(tag_no_case("UID "), number), |uid| {
+        AttributeValue::Uid(Uid(uid))
+    })(i)
 }
 
-fn msg_att(i: &[u8]) -> IResult<&[u8], AttributeValue> {
+fn msg_att(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    context(
+        "msg-att",
+        alt((
+            msg_att_body_section,
+            msg_att_body_structure,
+            msg_att_envelope,
+            msg_att_internal_date,
+            msg_att_flags,
+            #[cfg(feature = "condstore")]
+            rfc4551::msg_att_mod_seq,
+            rev1_only(msg_att_rfc822),
+            rev1_only(msg_att_rfc822_header),
+            msg_att_rfc822_size,
+            rev1_only(msg_att_rfc822_text),
+            msg_att_uid,
+            msg_att_extension,
+        )),
+    )(i)
+}
+
+fn msg_att_extension(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
     alt((
-        msg_att_body_section,
-        msg_att_body_structure,
-        msg_att_envelope,
-        msg_att_internal_date,
-        msg_att_flags,
-        rfc4551::msg_att_mod_seq,
-        msg_att_rfc822,
-        msg_att_rfc822_header,
-        msg_att_rfc822_size,
-        msg_att_rfc822_text,
-        msg_att_uid,
+        rfc3516::msg_att_binary_size,
+        rfc3516::msg_att_binary,
+        #[cfg(feature = "gmail")]
+        gmail::msg_att_gmail_labels,
+        #[cfg(feature = "gmail")]
+        gmail::msg_att_gmail_msgid,
+        #[cfg(feature = "gmail")]
+        gmail::msg_att_gmail_thrid,
+        rfc8474::msg_att_email_id,
+        rfc8474::msg_att_thread_id,
+        rfc8970::msg_att_preview,
+        rfc8514::msg_att_save_date,
+        rfc5257::msg_att_annotation,
     ))(i)
 }
 
-fn msg_att_list(i: &[u8]) -> IResult<&[u8], Vec<AttributeValue>> {
-    parenthesized_nonempty_list(msg_att)(i)
+pub(crate) fn msg_att_list(i: &[u8]) -> IResult<&[u8], Vec<AttributeValue<'_>>> {
+    delimited(char('('), separated_nonempty_list(sp, msg_att), char(')'))(i)
 }
 
-fn message_data_fetch(i: &[u8]) -> IResult<&[u8], Response> {
-    map(
-        tuple((number, tag_no_case(" FETCH "), msg_att_list)),
-        |(num, _, attrs)| Response::Fetch(num, attrs),
+fn message_data_fetch(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    context(
+        "message-data",
+        map(
+            tuple((number, tag_no_case(" FETCH "), msg_att_list)),
+            |(num, _, attrs)| Response::Fetch(Seq(num), attrs),
+        ),
     )(i)
 }
 
-fn message_data_expunge(i: &[u8]) -> IResult<&[u8], Response> {
-    map(
-        terminated(number, tag_no_case(" EXPUNGE")),
-        Response::Expunge,
-    )(i)
+fn message_data_expunge(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(terminated(number, tag_no_case(" EXPUNGE")), |num| {
+        Response::Expunge(Seq(num))
+    })(i)
 }
 
 fn imap_tag(i: &[u8]) -> IResult<&[u8], RequestId> {
@@ -496,57 +779,84 @@ fn imap_tag(i: &[u8]) -> IResult<&[u8], RequestId> {
     })(i)
 }
 
+type RespText<'a> = (Option<ResponseCode<'a>>, Option<Cow<'a, str>>);
+
 // This is not quite according to spec, which mandates the following:
 //     ["[" resp-text-code "]" SP] text
 // However, examples in RFC 4551 (Conditional STORE) counteract this by giving
 // examples of `resp-text` that do not include the trailing space and text.
-fn resp_text(i: &[u8]) -> IResult<&[u8], (Option<ResponseCode>, Option<&str>)> {
-    map(tuple((opt(resp_text_code), text)), |(code, text)| {
-        let res = if text.is_empty() {
-            None
-        } else if code.is_some() {
-            Some(&text[1..])
-        } else {
-            Some(text)
-        };
-        (code, res)
-    })(i)
+fn resp_text(i: &[u8]) -> IResult<&[u8], RespText<'_>> {
+    context(
+        "resp-text",
+        map(tuple((opt(resp_text_code), text)), |(code, text)| {
+            let skip = if code.is_none() {
+                0
+            } else if options::current().lenient {
+                text.len() - text.trim_start_matches(' ').len()
+            } else {
+                usize::from(text.starts_with(' '))
+            };
+            let res = match text {
+                _ if text.is_empty() => None,
+                Cow::Borrowed(text) => Some(Cow::Borrowed(&text[skip..])),
+                Cow::Owned(mut text) => {
+                    text.drain(..skip);
+                    Some(Cow::Owned(text))
+                }
+            };
+            (code, res)
+        }),
+    )(i)
 }
 
-fn continue_req(i: &[u8]) -> IResult<&[u8], Response> {
-    // Some servers do not send the space :/
-    // TODO: base64
-    map(
-        tuple((tag("+"), opt(tag(" ")), resp_text, tag("\r\n"))),
-        |(_, _, text, _)| Response::Continue {
-            code: text.0,
-            information: text.1,
-        },
+fn continue_req(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    context(
+        "continue-req", // Some servers do not send the space :/
+        // A base64 challenge is also valid resp-text, so it is returned as text
+        // and can be decoded with `ContinueRequest::challenge`.
+        map(
+            tuple((tag("+"), opt(tag(" ")), resp_text, tag("\r\n"))),
+            |(_, _, text, _)| {
+                Response::Continue(ContinueRequest {
+                    code: text.0,
+                    information: text.1,
+                })
+            },
+        ),
     )(i)
 }
 
-fn response_tagged(i: &[u8]) -> IResult<&[u8], Response> {
-    map(
-        tuple((
-            imap_tag,
-            tag(b" "),
-            status,
-            tag(b" "),
-            resp_text,
-            tag(b"\r\n"),
-        )),
-        |(tag, _, status, _, text, _)| Response::Done {
-            tag,
-            status,
-            code: text.0,
-            information: text.1,
-        },
+// SP before resp-text. Lenient mode also accepts runs of spaces, or no space
+// at all before a response code or the CRLF.
+fn sp_resp_text(i: &[u8]) -> IResult<&[u8], ()> {
+    if !options::current().lenient {
+        return map(tag(b" "), |_| ())(i);
+    }
+    let (rest, spaces) = take_while(|c| c == b' ')(i)?;
+    if spaces.is_empty() && !rest.starts_with(b"[") && !rest.starts_with(b"\r\n") {
+        return Err(nom::Err::Error((i, nom::error::ErrorKind::Tag)));
+    }
+    Ok((rest, ()))
+}
+
+fn response_tagged(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    context(
+        "response-tagged",
+        map(
+            tuple((imap_tag, sp, status, sp_resp_text, resp_text, crlf)),
+            |(tag, _, status, _, text, _)| Response::Done {
+                tag,
+                status,
+                code: text.0,
+                information: text.1,
+            },
+        ),
     )(i)
 }
 
-fn resp_cond(i: &[u8]) -> IResult<&[u8], Response> {
+fn resp_cond(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     map(
-        tuple((status, tag(b" "), resp_text)),
+        tuple((status, sp_resp_text, resp_text)),
         |(status, _, text)| Response::Data {
             status,
             code: text.0,
@@ -555,36 +865,152 @@ fn resp_cond(i: &[u8]) -> IResult<&[u8], Response> {
     )(i)
 }
 
-fn response_data(i: &[u8]) -> IResult<&[u8], Response> {
-    delimited(
-        tag(b"* "),
-        alt((
-            resp_cond,
-            mailbox_data,
-            message_data_expunge,
-            message_data_fetch,
-            resp_capability,
-            resp_metadata,
-            rfc5161::resp_enabled,
-        )),
-        tag(b"\r\n"),
+fn response_data(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    context(
+        "response-data",
+        delimited(
+            pair(tag(b"*"), sp),
+            alt((
+                resp_cond,
+                mailbox_data,
+                message_data_expunge,
+                message_data_fetch,
+                resp_capability,
+                response_data_extension,
+            )),
+            crlf,
+        ),
     )(i)
 }
 
-fn response(i: &[u8]) -> IResult<&[u8], Response> {
+fn response_data_extension(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    alt((
+        #[cfg(feature = "metadata")]
+        rfc5464::resp_metadata,
+        rfc5161::resp_enabled,
+        #[cfg(feature = "condstore")]
+        rfc7162::resp_vanished,
+        rfc4731::resp_esearch,
+        rfc2971::resp_id,
+        rfc2342::resp_namespace,
+        #[cfg(feature = "quota")]
+        rfc2087::resp_quota,
+        #[cfg(feature = "quota")]
+        rfc2087::resp_quota_root,
+        #[cfg(feature = "acl")]
+        rfc4314::resp_acl,
+        #[cfg(feature = "acl")]
+        rfc4314::resp_list_rights,
+        #[cfg(feature = "acl")]
+        rfc4314::resp_my_rights,
+        rfc5256::resp_sort,
+        rfc5256::resp_thread,
+        rfc5255::resp_language,
+        rfc4467::resp_gen_url_auth,
+        rfc4467::resp_url_fetch,
+        rfc5257::resp_annotation,
+        rfc9586::resp_uidfetch,
+    ))(i)
+}
+
+fn response(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     alt((continue_req, response_data, response_tagged))(i)
 }
 
-pub fn parse_response(msg: &[u8]) -> ParseResult {
-    response(msg)
+pub fn parse_response(msg: &[u8]) -> ParseResult<'_> {
+    error::parse(msg, "response", response)
+}
+
+/// Parses a response like `parse_response`, but tells how much more input is
+/// needed if the buffer ends inside a literal, so that transports can wait
+/// for the whole literal instead of retrying on every read.
+pub fn parse_response_incremental(msg: &[u8]) -> Parsed<'_> {
+    take_literal_needed();
+    match parse_response(msg) {
+        Ok((rest, rsp)) => Parsed::Complete(rest, rsp),
+        Err(nom::Err::Incomplete(needed)) => match take_literal_needed() {
+            Some(n) if needed == Needed::Size(n) => Parsed::Incomplete(needed),
+            _ => Parsed::Incomplete(Needed::Unknown),
+        },
+        Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => Parsed::Error(err),
+    }
+}
+
+/// Parses a response from input that is known to be complete, such as a
+/// captured protocol log or a test fixture. Unlike `parse_response`, input
+/// that ends inside a response is an error rather than `Incomplete`.
+pub fn parse_response_complete(msg: &[u8]) -> Result<(&[u8], Response<'_>), ParseError<'_>> {
+    error::parse_complete(msg, "response", response)
+}
+
+/// Parses all responses in input that is known to be complete, failing if
+/// any of them is invalid or truncated.
+pub fn parse_responses_complete(mut msg: &[u8]) -> Result<Vec<Response<'_>>, ParseError<'_>> {
+    let mut responses = Vec::new();
+    while !msg.is_empty() {
+        let (rest, rsp) = parse_response_complete(msg)?;
+        responses.push(rsp);
+        msg = rest;
+    }
+    Ok(responses)
+}
+
+/// Parses a response like `parse_response`, using the grammar changes
+/// selected by `options`.
+pub fn parse_response_with(msg: &[u8], options: Options) -> ParseResult<'_> {
+    options::with(options, || parse_response(msg))
+}
+
+/// Parses a response like `parse_response_incremental`, using the grammar
+/// changes selected by `options`.
+pub fn parse_response_incremental_with(msg: &[u8], options: Options) -> Parsed<'_> {
+    options::with(options, || parse_response_incremental(msg))
+}
+
+// greeting        = "*" SP (resp-cond-auth / resp-cond-bye) CRLF
+// [RFC3501 - 9. Formal Syntax](https://tools.ietf.org/html/rfc3501#section-9)
+pub fn parse_greeting(msg: &[u8]) -> IResult<&[u8], Greeting<'_>, ParseError<'_>> {
+    error::parse(
+        msg,
+        "greeting",
+        map_opt(response, |rsp| Greeting::try_from(rsp).ok()),
+    )
 }
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+
     use super::parse_response;
     use crate::types::*;
     use assert_matches::assert_matches;
 
+    #[test]
+    fn test_greeting() {
+        match super::parse_greeting(b"* PREAUTH [CAPABILITY IMAP4rev1 IDLE] Welcome\r\n") {
+            Ok((_, greeting)) => {
+                assert_eq!(greeting.status, GreetingStatus::PreAuth);
+                assert_eq!(
+                    greeting.capabilities(),
+                    Some(&[Capability::Imap4rev1, Capability::Idle][..])
+                );
+                assert_eq!(greeting.information.as_deref(), Some("Welcome"));
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match super::parse_greeting(b"* BYE Too many connections\r\n") {
+            Ok((_, greeting)) => {
+                assert_eq!(greeting.status, GreetingStatus::Bye);
+                assert_eq!(greeting.capabilities(), None);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        assert!(super::parse_greeting(b"* NO Go away\r\n").is_err());
+        assert!(super::parse_greeting(b"* 3 EXISTS\r\n").is_err());
+    }
+
     #[test]
     fn test_list() {
         match super::mailbox(b"iNboX ") {
@@ -600,6 +1026,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_list_special_use() {
+        match parse_response(
+            b"* LIST (\\HasNoChildren \\Sent \\junk \\X-Custom) \"/\" \"Sent Mail\"\r\n",
+        ) {
+            Ok((_, Response::MailboxData(MailboxDatum::List { flags, name, .. }))) => {
+                assert_eq!(name, "Sent Mail");
+                assert_eq!(
+                    flags,
+                    vec![
+                        NameAttribute::HasNoChildren,
+                        NameAttribute::Sent,
+                        NameAttribute::Junk,
+                        NameAttribute::Extension(Cow::Borrowed("\\X-Custom")),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
     #[test]
     fn test_envelope() {
         let env = br#"ENVELOPE ("Wed, 17 Jul 1996 02:23:25 -0700 (PDT)" "IMAP4rev1 WG mtg summary and minutes" (("Terry Gray" NIL "gray" "cac.washington.edu")) (("Terry Gray" NIL "gray" "cac.washington.edu")) (("Terry Gray" NIL "gray" "cac.washington.edu")) ((NIL NIL "imap" "cac.washington.edu")) ((NIL NIL "minutes" "CNRI.Reston.VA.US") ("John Klensin" NIL "KLENSIN" "MIT.EDU")) NIL NIL "<B27397-0100000@cac.washington.edu>") "#;
@@ -628,6 +1075,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_opt_addresses_group() {
+        let addr = br#"((NIL NIL "undisclosed-recipients" NIL)(NIL NIL NIL NIL)(NIL NIL "team" NIL)("Terry Gray" NIL "gray" "cac.washington.edu")(NIL NIL NIL NIL))"#;
+        match super::opt_addresses(addr) {
+            Ok((_, Some(addresses))) => {
+                assert_eq!(addresses.len(), 5);
+                assert_eq!(
+                    addresses[0].group_name(),
+                    Some(&b"undisclosed-recipients"[..])
+                );
+                assert!(addresses[1].is_group_end());
+                assert_eq!(addresses[2].group_name(), Some(&b"team"[..]));
+                assert!(!addresses[3].is_group_start());
+                assert!(!addresses[3].is_group_end());
+                assert_eq!(addresses[3].mailbox.as_deref(), Some(&b"gray"[..]));
+                assert!(addresses[4].is_group_end());
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
     #[test]
     fn test_addresses() {
         match super::address(b"(\"John Klensin\" NIL \"KLENSIN\" \"MIT.EDU\") ") {
@@ -642,6 +1110,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_capability_display() {
+        let line = "IMAP4rev1 AUTH=PLAIN LITERAL+ APPENDLIMIT=1024 APPENDLIMIT X-GM-EXT-1";
+        let data = format!("CAPABILITY {}\r\n", line);
+        let (_, caps) = super::capability_data(data.as_bytes()).unwrap();
+        assert_eq!(caps[2], Capability::LiteralPlus);
+        assert_eq!(caps[3], Capability::AppendLimit(Some(1024)));
+        let names = caps.iter().map(Capability::to_string).collect::<Vec<_>>();
+        assert_eq!(names.join(" "), line);
+    }
+
     #[test]
     fn test_capability_data() {
         // Minimal capabilities
@@ -663,8 +1142,8 @@ mod tests {
             super::capability_data(b"CAPABILITY XPIG-LATIN IMAP4rev1 STARTTLS AUTH=GSSAPI\r\n"),
             Ok((_, capabilities)) => {
                 assert_eq!(capabilities, vec![
-                    Capability::Atom("XPIG-LATIN"), Capability::Imap4rev1,
-                    Capability::Atom("STARTTLS"), Capability::Auth("GSSAPI")
+                    Capability::Atom(Cow::Borrowed("XPIG-LATIN")), Capability::Imap4rev1,
+                    Capability::StartTls, Capability::Auth(Cow::Borrowed("GSSAPI"))
                 ])
             }
         );
@@ -673,7 +1152,26 @@ mod tests {
             super::capability_data(b"CAPABILITY IMAP4rev1 AUTH=GSSAPI AUTH=PLAIN\r\n"),
             Ok((_, capabilities)) => {
                 assert_eq!(capabilities, vec![
-                    Capability::Imap4rev1, Capability::Auth("GSSAPI"),  Capability::Auth("PLAIN")
+                    Capability::Imap4rev1, Capability::Auth(Cow::Borrowed("GSSAPI")),  Capability::Auth(Cow::Borrowed("PLAIN"))
+                ])
+            }
+        );
+
+        assert_matches!(
+            super::capability_data(b"CAPABILITY imap4rev1 idle auth=plain Condstore IDLEX\r\n"),
+            Ok((_, capabilities)) => {
+                assert_eq!(capabilities, vec![
+                    Capability::Imap4rev1, Capability::Idle, Capability::Auth(Cow::Borrowed("plain")),
+                    Capability::Condstore, Capability::Atom(Cow::Borrowed("IDLEX"))
+                ])
+            }
+        );
+
+        assert_matches!(
+            super::capability_data(b"CAPABILITY IMAP4rev1 CLIENTID UIDONLY\r\n"),
+            Ok((_, capabilities)) => {
+                assert_eq!(capabilities, vec![
+                    Capability::Imap4rev1, Capability::ClientId, Capability::UidOnly
                 ])
             }
         );
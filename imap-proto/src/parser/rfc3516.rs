@@ -0,0 +1,110 @@
+//!
+//! https://tools.ietf.org/html/rfc3516
+//!
+//! IMAP4 Binary Content Extension
+//!
+
+use nom::{
+    bytes::streaming::tag_no_case,
+    character::streaming::char,
+    combinator::{map, opt},
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+use crate::{
+    parser::{
+        core::{nstring8, number},
+        rfc3501::body::section_part,
+    },
+    types::*,
+};
+
+// section-binary = "[" [section-part] "]"
+fn section_binary(i: &[u8]) -> IResult<&[u8], Vec<u32>> {
+    map(
+        delimited(char('['), opt(section_part), char(']')),
+        Option::unwrap_or_default,
+    )(i)
+}
+
+// msg-att-static =/ "BINARY" section-binary SP (nstring / literal8)
+// Servers may echo the partial offset of the request, just like with
+// BODY[]<origin>, so it is accepted here as well.
+// [RFC3516 - 4.2. FETCH Command Extensions](https://tools.ietf.org/html/rfc3516#section-4.2)
+pub(crate) fn msg_att_binary(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(
+        tuple((
+            tag_no_case("BINARY"),
+            section_binary,
+            opt(delimited(char('<'), number, char('>'))),
+            char(' '),
+            nstring8,
+        )),
+        |(_, section, index, _, data)| AttributeValue::Binary {
+            section,
+            index,
+            data,
+        },
+    )(i)
+}
+
+// msg-att-static =/ "BINARY.SIZE" section-binary SP number
+// [RFC3516 - 4.2. FETCH Command Extensions](https://tools.ietf.org/html/rfc3516#section-4.2)
+pub(crate) fn msg_att_binary_size(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(
+        tuple((
+            tag_no_case("BINARY.SIZE"),
+            section_binary,
+            preceded(char(' '), number),
+        )),
+        |(_, section, size)| AttributeValue::BinarySize { section, size },
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_binary() {
+        match parse_response(
+            b"* 1 FETCH (BINARY.SIZE[1.2] 4 BINARY[1.2]<0> ~{4}\r\n\x00\x01\x02\x03)\r\n",
+        ) {
+            Ok((_, Response::Fetch(Seq(1), attrs))) => {
+                assert_eq!(
+                    attrs,
+                    vec![
+                        AttributeValue::BinarySize {
+                            section: vec![1, 2],
+                            size: 4,
+                        },
+                        AttributeValue::Binary {
+                            section: vec![1, 2],
+                            index: Some(0),
+                            data: Some(Cow::Borrowed(b"\x00\x01\x02\x03")),
+                        },
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* 2 FETCH (BINARY[] NIL)\r\n") {
+            Ok((_, Response::Fetch(Seq(2), attrs))) => {
+                assert_eq!(
+                    attrs,
+                    vec![AttributeValue::Binary {
+                        section: vec![],
+                        index: None,
+                        data: None,
+                    }]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
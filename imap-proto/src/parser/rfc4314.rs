@@ -0,0 +1,140 @@
+//!
+//! https://tools.ietf.org/html/rfc4314
+//!
+//! IMAP4 Access Control List (ACL) Extension
+//!
+
+use nom::{
+    bytes::streaming::tag_no_case,
+    character::streaming::char,
+    combinator::map,
+    multi::many0,
+    sequence::{preceded, tuple},
+    IResult,
+};
+
+use crate::{
+    parser::{core::astring_utf8, rfc3501::mailbox},
+    types::*,
+};
+
+// rights = astring
+//          ;; only lowercase ASCII letters and digits are allowed.
+fn rights(i: &[u8]) -> IResult<&[u8], Vec<AclRight>> {
+    map(astring_utf8, |s| {
+        s.chars().map(AclRight::from_char).collect()
+    })(i)
+}
+
+// acl-data = "ACL" SP mailbox *(SP identifier SP rights)
+// [RFC4314 - 3.6. ACL Response](https://tools.ietf.org/html/rfc4314#section-3.6)
+pub(crate) fn resp_acl(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        tuple((
+            tag_no_case("ACL "),
+            mailbox,
+            many0(map(
+                tuple((char(' '), astring_utf8, char(' '), rights)),
+                |(_, identifier, _, rights)| AclEntry { identifier, rights },
+            )),
+        )),
+        |(_, mailbox, acls)| Response::Acl(Acl { mailbox, acls }),
+    )(i)
+}
+
+// listrights-data = "LISTRIGHTS" SP mailbox SP identifier
+//                   SP rights *(SP rights)
+// [RFC4314 - 3.7. LISTRIGHTS Response](https://tools.ietf.org/html/rfc4314#section-3.7)
+pub(crate) fn resp_list_rights(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        tuple((
+            tag_no_case("LISTRIGHTS "),
+            mailbox,
+            char(' '),
+            astring_utf8,
+            char(' '),
+            rights,
+            many0(preceded(char(' '), rights)),
+        )),
+        |(_, mailbox, _, identifier, _, required, optional)| {
+            Response::ListRights(ListRights {
+                mailbox,
+                identifier,
+                required,
+                optional,
+            })
+        },
+    )(i)
+}
+
+// myrights-data = "MYRIGHTS" SP mailbox SP rights
+// [RFC4314 - 3.8. MYRIGHTS Response](https://tools.ietf.org/html/rfc4314#section-3.8)
+pub(crate) fn resp_my_rights(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        tuple((tag_no_case("MYRIGHTS "), mailbox, char(' '), rights)),
+        |(_, mailbox, _, rights)| Response::MyRights(MyRights { mailbox, rights }),
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acl() {
+        match resp_acl(b"ACL INBOX Fred rwipslxetad \"Chris Newman\" lrswi\r\n") {
+            Ok((_, Response::Acl(acl))) => {
+                assert_eq!(acl.mailbox, "INBOX");
+                assert_eq!(acl.acls.len(), 2);
+                assert_eq!(acl.acls[0].identifier, "Fred");
+                assert_eq!(acl.acls[0].rights.len(), 11);
+                assert_eq!(acl.acls[1].identifier, "Chris Newman");
+                assert_eq!(
+                    acl.acls[1].rights,
+                    vec![
+                        AclRight::Lookup,
+                        AclRight::Read,
+                        AclRight::Seen,
+                        AclRight::Write,
+                        AclRight::Insert,
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match resp_acl(b"ACL empty\r\n") {
+            Ok((_, Response::Acl(acl))) => assert!(acl.acls.is_empty()),
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_list_rights() {
+        match resp_list_rights(b"LISTRIGHTS ~/Mail/saved smith la r swicdkxte\r\n") {
+            Ok((_, Response::ListRights(rights))) => {
+                assert_eq!(rights.mailbox, "~/Mail/saved");
+                assert_eq!(rights.identifier, "smith");
+                assert_eq!(
+                    rights.required,
+                    vec![AclRight::Lookup, AclRight::Administer]
+                );
+                assert_eq!(rights.optional.len(), 2);
+                assert_eq!(rights.optional[0], vec![AclRight::Read]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_my_rights() {
+        match resp_my_rights(b"MYRIGHTS INBOX rwiptsldaex9\r\n") {
+            Ok((_, Response::MyRights(rights))) => {
+                assert_eq!(rights.mailbox, "INBOX");
+                assert_eq!(rights.rights.len(), 12);
+                assert_eq!(rights.rights[11], AclRight::Custom('9'));
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//!
+//! https://tools.ietf.org/html/rfc4315
+//!
+//! The IMAP UIDPLUS Extension
+//!
+
+use nom::{
+    bytes::streaming::tag_no_case, character::streaming::char, combinator::map, sequence::tuple,
+    IResult,
+};
+
+use crate::{
+    parser::core::{nz_number, sequence_set},
+    types::*,
+};
+
+// resp-code-apnd = "APPENDUID" SP nz-number SP append-uid
+// append-uid     = uniqueid
+//                  ; a uid-set is used instead for MULTIAPPEND
+// [RFC4315 - 4. Formal Syntax](https://tools.ietf.org/html/rfc4315#section-4)
+pub(crate) fn resp_text_code_append_uid(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(
+        tuple((
+            tag_no_case("APPENDUID "),
+            nz_number,
+            char(' '),
+            sequence_set,
+        )),
+        |(_, uid_validity, _, uids)| ResponseCode::AppendUid(uid_validity, uids),
+    )(i)
+}
+
+// resp-code-copy = "COPYUID" SP nz-number SP uid-set SP uid-set
+// [RFC4315 - 4. Formal Syntax](https://tools.ietf.org/html/rfc4315#section-4)
+pub(crate) fn resp_text_code_copy_uid(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(
+        tuple((
+            tag_no_case("COPYUID "),
+            nz_number,
+            char(' '),
+            sequence_set,
+            char(' '),
+            sequence_set,
+        )),
+        |(_, uid_validity, _, source, _, destination)| {
+            ResponseCode::CopyUid(uid_validity, source, destination)
+        },
+    )(i)
+}
+
+// resp-text-code =/ "UIDNOTSTICKY"
+// [RFC4315 - 4. Formal Syntax](https://tools.ietf.org/html/rfc4315#section-4)
+pub(crate) fn resp_text_code_uid_not_sticky(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(tag_no_case("UIDNOTSTICKY"), |_| ResponseCode::UidNotSticky)(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_uid_not_sticky() {
+        match parse_response(b"* NO [UIDNOTSTICKY] Non-persistent UIDs\r\n") {
+            Ok((
+                _,
+                Response::Data {
+                    status: Status::No,
+                    code: Some(ResponseCode::UidNotSticky),
+                    information: Some(Cow::Borrowed("Non-persistent UIDs")),
+                },
+            )) => {}
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_append_uid() {
+        match parse_response(b"A003 OK [APPENDUID 38505 3955] APPEND completed\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    status: Status::Ok,
+                    code: Some(ResponseCode::AppendUid(38505, uids)),
+                    information: Some(Cow::Borrowed("APPEND completed")),
+                    ..
+                },
+            )) => {
+                assert_eq!(uids, SequenceSet::from(vec![3955..=3955]));
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_copy_uid() {
+        match parse_response(b"A004 OK [COPYUID 38505 304,319:320 3956:3958] Done\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    status: Status::Ok,
+                    code: Some(ResponseCode::CopyUid(38505, source, destination)),
+                    information: Some(Cow::Borrowed("Done")),
+                    ..
+                },
+            )) => {
+                assert_eq!(source, SequenceSet::from(vec![304..=304, 319..=320]));
+                assert_eq!(destination, SequenceSet::from(vec![3956..=3958]));
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
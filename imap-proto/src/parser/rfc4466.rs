@@ -0,0 +1,103 @@
+//!
+//! https://tools.ietf.org/html/rfc4466
+//!
+//! Collected Extensions to IMAP4 ABNF
+//!
+
+use nom::{
+    branch::alt,
+    character::streaming::{char, one_of},
+    combinator::{map, not, opt},
+    multi::separated_nonempty_list,
+    sequence::{delimited, terminated},
+    IResult,
+};
+
+use crate::{parser::core::*, types::*};
+
+// tagged-ext-comp     = astring /
+//                       tagged-ext-comp *(SP tagged-ext-comp) /
+//                       "(" tagged-ext-comp ")"
+fn tagged_ext_comp(i: &[u8], depth: usize) -> IResult<&[u8], TaggedExtComp<'_>> {
+    let (i, _) = check_nesting_depth(i, depth)?;
+    alt((
+        map(astring_utf8, TaggedExtComp::Astring),
+        map(
+            delimited(
+                char('('),
+                separated_nonempty_list(char(' '), |i| tagged_ext_comp(i, depth + 1)),
+                char(')'),
+            ),
+            TaggedExtComp::List,
+        ),
+    ))(i)
+}
+
+// tagged-ext-simple   = sequence-set / number / number64
+// A plain number is returned as such; anything that is only valid
+// as a sequence-set is returned as one.
+fn tagged_ext_simple(i: &[u8]) -> IResult<&[u8], TaggedExtVal<'_>> {
+    alt((
+        map(
+            terminated(number_64, not(one_of(":,*"))),
+            TaggedExtVal::Number,
+        ),
+        map(sequence_set, TaggedExtVal::SequenceSet),
+    ))(i)
+}
+
+// tagged-ext-val      = tagged-ext-simple /
+//                       "(" [tagged-ext-comp] ")"
+// [RFC4466 - 2.1. Formal Syntax](https://tools.ietf.org/html/rfc4466#section-2.1)
+pub(crate) fn tagged_ext_val(i: &[u8]) -> IResult<&[u8], TaggedExtVal<'_>> {
+    alt((
+        tagged_ext_simple,
+        map(
+            delimited(
+                char('('),
+                opt(separated_nonempty_list(char(' '), |i| {
+                    tagged_ext_comp(i, 1)
+                })),
+                char(')'),
+            ),
+            |comp| TaggedExtVal::Comp(comp.unwrap_or_default()),
+        ),
+    ))(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+
+    #[test]
+    fn test_tagged_ext_val() {
+        assert_eq!(tagged_ext_val(b"42 ").unwrap().1, TaggedExtVal::Number(42));
+        assert_eq!(
+            tagged_ext_val(b"1:3,5 ").unwrap().1,
+            TaggedExtVal::SequenceSet(SequenceSet::from(vec![1..=3, 5..=5]))
+        );
+        assert_eq!(
+            tagged_ext_val(b"(\"SUBSCRIBED\" (a b)) ").unwrap().1,
+            TaggedExtVal::Comp(vec![
+                TaggedExtComp::Astring(Cow::Borrowed("SUBSCRIBED")),
+                TaggedExtComp::List(vec![
+                    TaggedExtComp::Astring(Cow::Borrowed("a")),
+                    TaggedExtComp::Astring(Cow::Borrowed("b")),
+                ]),
+            ])
+        );
+        assert_eq!(
+            tagged_ext_val(b"() ").unwrap().1,
+            TaggedExtVal::Comp(vec![])
+        );
+    }
+
+    #[test]
+    fn test_tagged_ext_val_too_deep() {
+        let nested = |depth| format!("({}a{}) ", "(".repeat(depth), ")".repeat(depth));
+        assert!(tagged_ext_val(nested(60).as_bytes()).is_ok());
+        assert!(tagged_ext_val(nested(5000).as_bytes()).is_err());
+    }
+}
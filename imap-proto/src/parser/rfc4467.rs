@@ -0,0 +1,163 @@
+//!
+//! https://tools.ietf.org/html/rfc4467
+//!
+//! Internet Message Access Protocol (IMAP) - URLAUTH Extension
+//!
+
+use std::borrow::Cow;
+use std::str::from_utf8;
+
+use nom::{
+    bytes::streaming::{tag_no_case, take_while1},
+    character::streaming::char,
+    combinator::{map, map_res, opt},
+    multi::{many0, many1},
+    sequence::{preceded, tuple},
+    IResult,
+};
+
+use crate::{
+    parser::core::{astring_utf8, nstring},
+    types::*,
+};
+
+// genurlauth-data = "*" SP "GENURLAUTH" 1*(SP url-full)
+// [RFC4467 - 9. Formal Syntax](https://tools.ietf.org/html/rfc4467#section-9)
+pub(crate) fn resp_gen_url_auth(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        preceded(
+            tag_no_case("GENURLAUTH"),
+            many1(preceded(char(' '), astring_utf8)),
+        ),
+        Response::GenUrlAuth,
+    )(i)
+}
+
+// urlfetch-data   = "*" SP "URLFETCH" 1*(SP url-rump SP nstring)
+// [RFC4467 - 9. Formal Syntax](https://tools.ietf.org/html/rfc4467#section-9)
+pub(crate) fn resp_url_fetch(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        preceded(
+            tag_no_case("URLFETCH"),
+            many1(map(
+                tuple((char(' '), astring_utf8, char(' '), nstring)),
+                |(_, url, _, data)| (url, data),
+            )),
+        ),
+        Response::UrlFetch,
+    )(i)
+}
+
+// mechanism       = "INTERNAL" / 1*(ALPHA / DIGIT / "-" / ".")
+fn is_mechanism_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'-' || c == b'.'
+}
+
+fn is_base64_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'+' || c == b'/' || c == b'='
+}
+
+type Mechanism<'a> = (Cow<'a, str>, Option<Cow<'a, str>>);
+
+// mechanism [ "=" base64 ]
+fn mechanism(i: &[u8]) -> IResult<&[u8], Mechanism<'_>> {
+    tuple((
+        map(
+            map_res(take_while1(is_mechanism_char), from_utf8),
+            Cow::Borrowed,
+        ),
+        opt(preceded(
+            char('='),
+            map(
+                map_res(take_while1(is_base64_char), from_utf8),
+                Cow::Borrowed,
+            ),
+        )),
+    ))(i)
+}
+
+// resp-text-code  =/ "URLMECH" SP "INTERNAL" *(SP mechanism ["=" base64])
+// The INTERNAL mechanism is included in the returned list.
+// [RFC4467 - 9. Formal Syntax](https://tools.ietf.org/html/rfc4467#section-9)
+pub(crate) fn resp_text_code_url_mech(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(
+        preceded(
+            tag_no_case("URLMECH "),
+            tuple((mechanism, many0(preceded(char(' '), mechanism)))),
+        ),
+        |(first, mut rest)| {
+            rest.insert(0, first);
+            ResponseCode::UrlMech(rest)
+        },
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_gen_url_auth() {
+        match parse_response(
+            b"* GENURLAUTH \"imap://joe@example.com/INBOX/;uid=20/;section=1.2;urlauth=submit+fred:internal:91354a473744909de610943775f92038\"\r\n",
+        ) {
+            Ok((_, Response::GenUrlAuth(urls))) => {
+                assert_eq!(
+                    urls,
+                    vec!["imap://joe@example.com/INBOX/;uid=20/;section=1.2;urlauth=submit+fred:internal:91354a473744909de610943775f92038"]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_url_fetch() {
+        match parse_response(
+            b"* URLFETCH \"imap://joe@example.com/INBOX/;uid=20\" {3}\r\nfoo \"imap://x\" NIL\r\n",
+        ) {
+            Ok((_, Response::UrlFetch(data))) => {
+                assert_eq!(
+                    data,
+                    vec![
+                        (
+                            Cow::Borrowed("imap://joe@example.com/INBOX/;uid=20"),
+                            Some(Cow::Borrowed(&b"foo"[..]))
+                        ),
+                        (Cow::Borrowed("imap://x"), None),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_url_mech() {
+        match parse_response(b"A1 OK [URLMECH INTERNAL XSAMPLE=P34OKhO7VEkCbsiYY8rGEg==] done\r\n")
+        {
+            Ok((
+                _,
+                Response::Done {
+                    code: Some(ResponseCode::UrlMech(mechs)),
+                    ..
+                },
+            )) => {
+                assert_eq!(
+                    mechs,
+                    vec![
+                        (Cow::Borrowed("INTERNAL"), None),
+                        (
+                            Cow::Borrowed("XSAMPLE"),
+                            Some(Cow::Borrowed("P34OKhO7VEkCbsiYY8rGEg=="))
+                        ),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
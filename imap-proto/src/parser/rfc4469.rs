@@ -0,0 +1,71 @@
+//!
+//! https://tools.ietf.org/html/rfc4469
+//!
+//! Internet Message Access Protocol (IMAP) CATENATE Extension
+//!
+
+use std::borrow::Cow;
+use std::str::from_utf8;
+
+use nom::{
+    bytes::streaming::{tag_no_case, take_while1},
+    combinator::{map, map_res},
+    sequence::preceded,
+    IResult,
+};
+
+use crate::{parser::core::is_text_char, types::*};
+
+// resp-text-code  =/ "TOOBIG"
+// [RFC4469 - 5. Formal Syntax](https://tools.ietf.org/html/rfc4469#section-5)
+pub(crate) fn resp_text_code_too_big(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(tag_no_case("TOOBIG"), |_| ResponseCode::TooBig)(i)
+}
+
+// resp-text-code  =/ "BADURL" SP url-resp-text
+// url-resp-text   = 1*(%x01-09 / %x0B-0C / %x0E-5B / %x5D-FE)
+//                   ; Any TEXT-CHAR except "]"
+// [RFC4469 - 5. Formal Syntax](https://tools.ietf.org/html/rfc4469#section-5)
+pub(crate) fn resp_text_code_bad_url(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(
+        preceded(
+            tag_no_case("BADURL "),
+            map_res(take_while1(|c| is_text_char(c) && c != b']'), from_utf8),
+        ),
+        |url| ResponseCode::BadUrl(Cow::Borrowed(url)),
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_catenate_codes() {
+        match parse_response(b"A003 NO [BADURL /INBOX;UIDVALIDITY=785799047/;UID=113330/;section=1.5.9] CATENATE append has failed, one message expunged\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    status: Status::No,
+                    code: Some(ResponseCode::BadUrl(url)),
+                    ..
+                },
+            )) => {
+                assert_eq!(url, "/INBOX;UIDVALIDITY=785799047/;UID=113330/;section=1.5.9");
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"A004 NO [TOOBIG] Message is too large\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    code: Some(ResponseCode::TooBig),
+                    ..
+                },
+            )) => {}
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -5,29 +5,60 @@
 //! or Quick Flag Changes Resynchronization
 //!
 
-use nom::{bytes::streaming::tag_no_case, sequence::tuple, IResult};
+use nom::{
+    branch::alt,
+    bytes::streaming::tag_no_case,
+    character::streaming::char,
+    combinator::map,
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
 
-use crate::{parser::core::number_64, types::*};
+use crate::{
+    parser::core::{number_64, sequence_set},
+    types::*,
+};
 
 // The highest mod-sequence value of all messages in the mailbox.
 // Extends resp-test-code defined in rfc3501.
 // [RFC4551 - 3.6 HIGHESTMODSEQ Status Data Items](https://tools.ietf.org/html/rfc4551#section-3.6)
 // [RFC4551 - 4. Formal Syntax - resp-text-code](https://tools.ietf.org/html/rfc4551#section-4)
-pub(crate) fn resp_text_code_highest_mod_seq(i: &[u8]) -> IResult<&[u8], ResponseCode> {
+pub(crate) fn resp_text_code_highest_mod_seq(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
     let (i, (_, num)) = tuple((tag_no_case("HIGHESTMODSEQ "), number_64))(i)?;
     Ok((i, ResponseCode::HighestModSeq(num)))
 }
 
+// Sent in response to SELECT/EXAMINE if the mailbox doesn't support mod-sequences.
+// [RFC4551 - 3.1.2 NOMODSEQ Response Code](https://tools.ietf.org/html/rfc4551#section-3.1.2)
+pub(crate) fn resp_text_code_no_mod_seq(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(tag_no_case("NOMODSEQ"), |_| ResponseCode::NoModSeq)(i)
+}
+
+// Lists the messages that failed the UNCHANGEDSINCE test of a STORE command.
+// [RFC4551 - 3.2 STORE and UID STORE Commands](https://tools.ietf.org/html/rfc4551#section-3.2)
+pub(crate) fn resp_text_code_modified(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(
+        preceded(tag_no_case("MODIFIED "), sequence_set),
+        ResponseCode::Modified,
+    )(i)
+}
+
 // Extends status-att/status-att-list defined in rfc3501
 // [RFC4551 - 3.6 - HIGHESTMODSEQ Status Data Items](https://tools.ietf.org/html/rfc4551#section-3.6)
 // [RFC4551 - 4. Formal Syntax - status-att-val](https://tools.ietf.org/html/rfc4551#section-4)
-pub(crate) fn status_att_val_highest_mod_seq(i: &[u8]) -> IResult<&[u8], StatusAttribute> {
+pub(crate) fn status_att_val_highest_mod_seq(i: &[u8]) -> IResult<&[u8], StatusAttribute<'_>> {
     let (i, (_, num)) = tuple((tag_no_case("HIGHESTMODSEQ "), number_64))(i)?;
     Ok((i, StatusAttribute::HighestModSeq(num)))
 }
 
+// fetch-mod-resp      = "MODSEQ" SP "(" permsg-modsequence ")"
+// The unparenthesized form was accepted by earlier versions of this parser
+// and is still allowed for compatibility.
 // [RFC4551 - 4. Formal Syntax - fetch-mod-resp](https://tools.ietf.org/html/rfc4551#section-4)
-pub(crate) fn msg_att_mod_seq(i: &[u8]) -> IResult<&[u8], AttributeValue> {
-    let (i, (_, num)) = tuple((tag_no_case("MODSEQ "), number_64))(i)?;
+pub(crate) fn msg_att_mod_seq(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    let (i, (_, num)) = tuple((
+        tag_no_case("MODSEQ "),
+        alt((delimited(char('('), number_64, char(')')), number_64)),
+    ))(i)?;
     Ok((i, AttributeValue::ModSeq(num)))
 }
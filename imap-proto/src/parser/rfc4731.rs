@@ -0,0 +1,109 @@
+//!
+//! https://tools.ietf.org/html/rfc4731
+//!
+//! IMAP4 Extension to SEARCH Command for Controlling What Kind of
+//! Information Is Returned
+//!
+
+use std::borrow::Cow;
+
+use nom::{
+    branch::alt,
+    bytes::streaming::tag_no_case,
+    character::streaming::char,
+    combinator::{map, opt},
+    multi::many0,
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+use crate::{
+    parser::{core::*, rfc3501::mailbox, rfc9394},
+    types::*,
+};
+
+// search-correlator    = SP "(" "TAG" SP tag-string ")"
+// [RFC4466 - 2.6.2 ESEARCH untagged response](https://tools.ietf.org/html/rfc4466#section-2.6.2)
+// search-correlator =/ SP "(" "TAG" SP tag-string SP "MAILBOX" SP astring
+//                      SP "UIDVALIDITY" SP nz-number ")"
+// [RFC7377 - 7. Formal Syntax](https://tools.ietf.org/html/rfc7377#section-7)
+fn search_correlator(i: &[u8]) -> IResult<&[u8], (Cow<'_, str>, Option<MailboxCorrelator<'_>>)> {
+    delimited(
+        tag_no_case(" (TAG "),
+        tuple((
+            string_utf8,
+            opt(tuple((
+                preceded(tag_no_case(" MAILBOX "), mailbox),
+                preceded(tag_no_case(" UIDVALIDITY "), nz_number),
+            ))),
+        )),
+        char(')'),
+    )(i)
+}
+
+// The mailbox name and UIDVALIDITY of a MULTISEARCH correlator.
+type MailboxCorrelator<'a> = (Cow<'a, str>, u32);
+
+// search-return-data = "MIN" SP nz-number /
+//                      "MAX" SP nz-number /
+//                      "ALL" SP sequence-set /
+//                      "COUNT" SP number
+// [RFC4731 - 3.1 Formal Syntax](https://tools.ietf.org/html/rfc4731#section-3.1)
+fn search_return_data(i: &[u8]) -> IResult<&[u8], SearchReturnData> {
+    alt((
+        map(
+            preceded(tag_no_case("MIN "), nz_number),
+            SearchReturnData::Min,
+        ),
+        map(
+            preceded(tag_no_case("MAX "), nz_number),
+            SearchReturnData::Max,
+        ),
+        map(
+            preceded(tag_no_case("ALL "), sequence_set),
+            SearchReturnData::All,
+        ),
+        map(
+            preceded(tag_no_case("COUNT "), number),
+            SearchReturnData::Count,
+        ),
+        // RFC 7162 extends search-return-data with the highest mod-sequence
+        // of all messages being returned.
+        map(
+            preceded(tag_no_case("MODSEQ "), number_64),
+            SearchReturnData::ModSeq,
+        ),
+        rfc9394::search_return_data_partial,
+    ))(i)
+}
+
+// esearch-response  = "ESEARCH" [search-correlator] [SP "UID"]
+//                     *(SP search-return-data)
+// [RFC4466 - 2.6.2 ESEARCH untagged response](https://tools.ietf.org/html/rfc4466#section-2.6.2)
+pub(crate) fn resp_esearch(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        tuple((
+            tag_no_case("ESEARCH"),
+            opt(search_correlator),
+            opt(tag_no_case(" UID")),
+            many0(preceded(char(' '), search_return_data)),
+        )),
+        |(_, correlator, uid, data)| {
+            let (tag, mailbox) = match correlator {
+                Some((tag, mailbox)) => (Some(tag), mailbox),
+                None => (None, None),
+            };
+            let (mailbox, uid_validity) = match mailbox {
+                Some((name, uid_validity)) => (Some(name), Some(uid_validity)),
+                None => (None, None),
+            };
+            Response::ESearch(ESearch {
+                tag,
+                mailbox,
+                uid_validity,
+                uid: uid.is_some(),
+                data,
+            })
+        },
+    )(i)
+}
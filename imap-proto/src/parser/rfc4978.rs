@@ -0,0 +1,48 @@
+//!
+//! https://tools.ietf.org/html/rfc4978
+//!
+//! The IMAP COMPRESS Extension
+//!
+
+use nom::{bytes::streaming::tag_no_case, combinator::map, IResult};
+
+use crate::types::*;
+
+// resp-text-code  =/ "COMPRESSIONACTIVE"
+// [RFC4978 - 4. Formal Syntax](https://tools.ietf.org/html/rfc4978#section-4)
+pub(crate) fn resp_text_code_compression_active(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(tag_no_case("COMPRESSIONACTIVE"), |_| {
+        ResponseCode::CompressionActive
+    })(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_compression_active() {
+        match parse_response(b"a2 NO [COMPRESSIONACTIVE] DEFLATE active via TLS\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    status: Status::No,
+                    code: Some(ResponseCode::CompressionActive),
+                    ..
+                },
+            )) => {}
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* CAPABILITY IMAP4rev1 COMPRESS=DEFLATE\r\n") {
+            Ok((_, Response::Capabilities(caps))) => {
+                assert_eq!(caps[1], Capability::Compress(Cow::Borrowed("DEFLATE")));
+                assert_eq!(caps[1].to_string(), "COMPRESS=DEFLATE");
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
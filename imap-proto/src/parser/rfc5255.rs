@@ -0,0 +1,50 @@
+//!
+//! https://tools.ietf.org/html/rfc5255
+//!
+//! Internet Message Access Protocol Internationalization
+//!
+
+use nom::{bytes::streaming::tag_no_case, combinator::map, sequence::preceded, IResult};
+
+use crate::{
+    parser::core::{astring_utf8, parenthesized_nonempty_list},
+    types::*,
+};
+
+// language-data   = "LANGUAGE" SP "(" lang-tag *(SP lang-tag) ")"
+// lang-tag        = astring
+// The list holds either the supported languages or the one in effect,
+// depending on the command that triggered it.
+// [RFC5255 - 3.8. Formal Syntax](https://tools.ietf.org/html/rfc5255#section-3.8)
+pub(crate) fn resp_language(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        preceded(
+            tag_no_case("LANGUAGE "),
+            parenthesized_nonempty_list(astring_utf8),
+        ),
+        Response::Language,
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_language() {
+        match parse_response(b"* LANGUAGE (EN DE IT i-default)\r\n") {
+            Ok((_, Response::Language(tags))) => {
+                assert_eq!(tags, vec!["EN", "DE", "IT", "i-default"]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* CAPABILITY IMAP4rev1 LANGUAGE\r\n") {
+            Ok((_, Response::Capabilities(caps))) => {
+                assert_eq!(caps, vec![Capability::Imap4rev1, Capability::Language]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//!
+//! https://tools.ietf.org/html/rfc5256
+//!
+//! SORT and THREAD Extensions
+//!
+
+use nom::{
+    branch::alt,
+    bytes::streaming::tag_no_case,
+    character::streaming::char,
+    combinator::{map, opt},
+    multi::{many0, many1, separated_nonempty_list},
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+use crate::{
+    parser::core::{number, nz_number},
+    types::*,
+};
+
+// sort-data = "SORT" *(SP nz-number)
+// The numbers are message sequence numbers, or UIDs if the command
+// was UID SORT.
+// [RFC5256 - 5. Formal Syntax](https://tools.ietf.org/html/rfc5256#section-5)
+pub(crate) fn resp_sort(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        preceded(tag_no_case("SORT"), many0(preceded(char(' '), number))),
+        Response::Sort,
+    )(i)
+}
+
+// Thread lists are nested this many levels deep at most. Deeper threads are
+// rejected rather than risking a stack overflow, both while parsing and when
+// the tree is dropped. Runs of messages don't count, as they are kept flat.
+const MAX_THREAD_DEPTH: usize = 256;
+
+// thread-members  = nz-number *(SP nz-number) [SP thread-nested]
+// Each message is the parent of the one that follows it, and the last
+// message is the parent of the nested threads.
+fn thread_members(i: &[u8], depth: usize) -> IResult<&[u8], Thread> {
+    map(
+        tuple((
+            separated_nonempty_list(char(' '), nz_number),
+            opt(preceded(char(' '), |i| thread_nested(i, depth))),
+        )),
+        |(ids, nested)| Thread {
+            ids,
+            children: nested.unwrap_or_default(),
+        },
+    )(i)
+}
+
+// thread-nested   = 2*thread-list
+// This is a bit more lenient than the RFC, in that it also accepts a single
+// nested thread-list.
+fn thread_nested(i: &[u8], depth: usize) -> IResult<&[u8], Vec<Thread>> {
+    many1(|i| thread_list(i, depth + 1))(i)
+}
+
+// thread-list     = "(" (thread-members / thread-nested) ")"
+// A thread-nested directly inside a thread-list indicates that the top-level
+// message is missing, which is represented here by a thread without ids.
+fn thread_list(i: &[u8], depth: usize) -> IResult<&[u8], Thread> {
+    if depth > MAX_THREAD_DEPTH {
+        return Err(nom::Err::Failure((i, nom::error::ErrorKind::TooLarge)));
+    }
+    delimited(
+        char('('),
+        alt((
+            |i| thread_members(i, depth),
+            map(
+                |i| thread_nested(i, depth),
+                |children| Thread {
+                    ids: vec![],
+                    children,
+                },
+            ),
+        )),
+        char(')'),
+    )(i)
+}
+
+// thread-data     = "THREAD" [SP 1*thread-list]
+// [RFC5256 - 5. Formal Syntax](https://tools.ietf.org/html/rfc5256#section-5)
+pub(crate) fn resp_thread(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        preceded(
+            tag_no_case("THREAD"),
+            map(
+                opt(preceded(char(' '), many1(|i| thread_list(i, 1)))),
+                |threads| threads.unwrap_or_default(),
+            ),
+        ),
+        Response::Thread,
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thread(ids: &[u32], children: Vec<Thread>) -> Thread {
+        Thread {
+            ids: ids.to_vec(),
+            children,
+        }
+    }
+
+    #[test]
+    fn test_thread() {
+        match resp_thread(b"THREAD (2)(3 6 (4 23)(44 7 96))\r\n") {
+            Ok((_, Response::Thread(threads))) => {
+                assert_eq!(
+                    threads,
+                    vec![
+                        thread(&[2], vec![]),
+                        thread(
+                            &[3, 6],
+                            vec![thread(&[4, 23], vec![]), thread(&[44, 7, 96], vec![])]
+                        ),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_thread_missing_parent() {
+        match resp_thread(b"THREAD ((3)(5))\r\n") {
+            Ok((_, Response::Thread(threads))) => {
+                assert_eq!(
+                    threads,
+                    vec![thread(
+                        &[],
+                        vec![thread(&[3], vec![]), thread(&[5], vec![])]
+                    )]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_thread_long_chain() {
+        let ids: Vec<_> = (1..=10_000).map(|id| id.to_string()).collect();
+        let chain = format!("THREAD ({})\r\n", ids.join(" "));
+        match resp_thread(chain.as_bytes()) {
+            Ok((_, Response::Thread(threads))) => {
+                assert_eq!(threads.len(), 1);
+                assert_eq!(threads[0].ids, (1..=10_000).collect::<Vec<_>>());
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_thread_too_deep() {
+        let nested = |depth| format!("THREAD {}1{}\r\n", "(".repeat(depth), ")".repeat(depth));
+        assert!(resp_thread(nested(MAX_THREAD_DEPTH).as_bytes()).is_ok());
+        assert!(resp_thread(nested(MAX_THREAD_DEPTH + 1).as_bytes()).is_err());
+        assert!(resp_thread(nested(100_000).as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_thread_empty() {
+        match resp_thread(b"THREAD\r\n") {
+            Ok((_, Response::Thread(threads))) => assert!(threads.is_empty()),
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
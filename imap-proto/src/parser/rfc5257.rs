@@ -0,0 +1,158 @@
+//!
+//! https://tools.ietf.org/html/rfc5257
+//!
+//! Internet Message Access Protocol - ANNOTATE Extension
+//!
+
+use nom::{
+    branch::alt,
+    bytes::streaming::tag_no_case,
+    character::streaming::char,
+    combinator::map,
+    sequence::{preceded, tuple},
+    IResult,
+};
+
+use crate::{
+    parser::{
+        core::{astring_utf8, nstring8, parenthesized_nonempty_list},
+        rfc3501::mailbox,
+    },
+    types::*,
+};
+
+// att-value       = attrib SP value
+// attrib          = astring
+// value           = nstring / literal8
+fn att_value(i: &[u8]) -> IResult<&[u8], AnnotationAttribute<'_>> {
+    map(
+        tuple((astring_utf8, char(' '), nstring8)),
+        |(attrib, _, value)| (attrib, value),
+    )(i)
+}
+
+// entry-att       = entry SP "(" att-value *(SP att-value) ")"
+// entry           = astring
+fn entry_att(i: &[u8]) -> IResult<&[u8], Annotation<'_>> {
+    map(
+        tuple((
+            astring_utf8,
+            char(' '),
+            parenthesized_nonempty_list(att_value),
+        )),
+        |(entry, _, attributes)| Annotation { entry, attributes },
+    )(i)
+}
+
+// A bare entry, as returned by a FETCH that only asks which entries exist.
+fn entry(i: &[u8]) -> IResult<&[u8], Annotation<'_>> {
+    map(astring_utf8, |entry| Annotation {
+        entry,
+        attributes: vec![],
+    })(i)
+}
+
+// msg-att-dynamic =/ "ANNOTATION" SP
+//                    ( "(" entry-att *(SP entry-att) ")" /
+//                      "(" entry *(SP entry) ")" )
+// [RFC5257 - 7. Formal Syntax](https://tools.ietf.org/html/rfc5257#section-7)
+pub(crate) fn msg_att_annotation(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(
+        preceded(
+            tag_no_case("ANNOTATION "),
+            alt((
+                parenthesized_nonempty_list(entry_att),
+                parenthesized_nonempty_list(entry),
+            )),
+        ),
+        AttributeValue::Annotation,
+    )(i)
+}
+
+// annotation-data = "ANNOTATION" SP mailbox SP entry-att
+// This untagged response was defined by the ANNOTATEMORE draft that
+// preceded RFC 5464 and is still sent by some servers.
+// [draft-daboo-imap-annotatemore-17 - 6. Formal Syntax](https://tools.ietf.org/html/draft-daboo-imap-annotatemore-17#section-6)
+pub(crate) fn resp_annotation(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        tuple((tag_no_case("ANNOTATION "), mailbox, char(' '), entry_att)),
+        |(_, mailbox, _, annotation)| Response::Annotation {
+            mailbox,
+            annotation,
+        },
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_fetch_annotation() {
+        match parse_response(
+            b"* 12 FETCH (UID 1123 ANNOTATION (/comment (value.priv \"My comment\" value.shared NIL) /altsubject (value.priv \"Rhinoceroses!\")))\r\n",
+        ) {
+            Ok((_, Response::Fetch(Seq(12), attrs))) => {
+                assert_eq!(
+                    attrs,
+                    vec![
+                        AttributeValue::Uid(Uid(1123)),
+                        AttributeValue::Annotation(vec![
+                            Annotation {
+                                entry: Cow::Borrowed("/comment"),
+                                attributes: vec![
+                                    (Cow::Borrowed("value.priv"), Some(Cow::Borrowed(&b"My comment"[..]))),
+                                    (Cow::Borrowed("value.shared"), None),
+                                ],
+                            },
+                            Annotation {
+                                entry: Cow::Borrowed("/altsubject"),
+                                attributes: vec![(Cow::Borrowed("value.priv"), Some(Cow::Borrowed(&b"Rhinoceroses!"[..])))],
+                            },
+                        ]),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* 12 FETCH (ANNOTATION (/comment /altsubject))\r\n") {
+            Ok((_, Response::Fetch(Seq(12), attrs))) => match attrs[0] {
+                AttributeValue::Annotation(ref entries) => {
+                    assert_eq!(entries.len(), 2);
+                    assert_eq!(entries[1].entry, "/altsubject");
+                    assert!(entries[1].attributes.is_empty());
+                }
+                ref attr => panic!("unexpected attribute {:?}", attr),
+            },
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_resp_annotation() {
+        match parse_response(b"* ANNOTATION INBOX \"/comment\" (\"value.shared\" \"Shared\")\r\n") {
+            Ok((
+                _,
+                Response::Annotation {
+                    mailbox,
+                    annotation,
+                },
+            )) => {
+                assert_eq!(mailbox, "INBOX");
+                assert_eq!(annotation.entry, "/comment");
+                assert_eq!(
+                    annotation.attributes,
+                    vec![(
+                        Cow::Borrowed("value.shared"),
+                        Some(Cow::Borrowed(&b"Shared"[..]))
+                    )]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
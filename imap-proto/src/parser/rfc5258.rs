@@ -0,0 +1,28 @@
+//!
+//! https://tools.ietf.org/html/rfc5258
+//!
+//! IMAP4 - LIST Command Extensions
+//!
+
+use nom::{character::streaming::char, combinator::map, sequence::tuple, IResult};
+
+use crate::{
+    parser::{core::*, rfc4466::tagged_ext_val},
+    types::*,
+};
+
+// mbox-list-extended-item =  mbox-list-extended-item-tag SP tagged-ext-val
+// mbox-list-extended-item-tag =  astring
+fn mbox_list_extended_item(i: &[u8]) -> IResult<&[u8], ListExtendedItem<'_>> {
+    map(
+        tuple((astring_utf8, char(' '), tagged_ext_val)),
+        |(tag, _, value)| ListExtendedItem { tag, value },
+    )(i)
+}
+
+// mbox-list-extended =  "(" [mbox-list-extended-item
+//                       *(SP mbox-list-extended-item)] ")"
+// [RFC5258 - 6. Formal Syntax](https://tools.ietf.org/html/rfc5258#section-6)
+pub(crate) fn mbox_list_extended(i: &[u8]) -> IResult<&[u8], Vec<ListExtendedItem<'_>>> {
+    parenthesized_list(mbox_list_extended_item)(i)
+}
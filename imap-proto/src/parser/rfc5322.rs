@@ -0,0 +1,242 @@
+//!
+//! https://tools.ietf.org/html/rfc5322
+//!
+//! Internet Message Format
+//!
+//! Only the date-time syntax of the Date header is parsed here, as found in
+//! envelopes. Unlike the response parsers, these parse a complete value.
+//!
+
+use std::str::from_utf8;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag_no_case, take_while, take_while_m_n},
+    character::complete::char,
+    combinator::{map, map_opt, opt, value},
+    sequence::{preceded, tuple},
+    IResult,
+};
+
+use crate::{parser::rfc3501::date_time::date_month, types::DateTime};
+
+// CFWS = (1*([FWS] comment) [FWS]) / FWS
+// Skips any folding white space and comments, which may be nested.
+fn cfws(i: &[u8]) -> IResult<&[u8], ()> {
+    let mut i = i;
+    loop {
+        let (rest, _) = take_while(|c| c == b' ' || c == b'\t' || c == b'\r' || c == b'\n')(i)?;
+        i = rest;
+        if i.first() != Some(&b'(') {
+            return Ok((i, ()));
+        }
+        let mut depth = 0;
+        let mut escape = false;
+        let mut end = None;
+        for (n, &c) in i.iter().enumerate() {
+            match c {
+                _ if escape => escape = false,
+                b'\\' => escape = true,
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(n + 1);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        match end {
+            Some(end) => i = &i[end..],
+            None => return Ok((i, ())),
+        }
+    }
+}
+
+fn number(min: usize, max: usize) -> impl Fn(&[u8]) -> IResult<&[u8], u32> {
+    move |i| {
+        map_opt(take_while_m_n(min, max, |c: u8| c.is_ascii_digit()), |b| {
+            from_utf8(b).ok()?.parse().ok()
+        })(i)
+    }
+}
+
+// day-of-week = ([FWS] day-name) / obs-day-of-week
+// day-name    = "Mon" / "Tue" / "Wed" / "Thu" / "Fri" / "Sat" / "Sun"
+// The day of the week is redundant, so it is not checked against the date.
+fn day_of_week(i: &[u8]) -> IResult<&[u8], ()> {
+    value(
+        (),
+        tuple((
+            cfws,
+            alt((
+                tag_no_case("Mon"),
+                tag_no_case("Tue"),
+                tag_no_case("Wed"),
+                tag_no_case("Thu"),
+                tag_no_case("Fri"),
+                tag_no_case("Sat"),
+                tag_no_case("Sun"),
+            )),
+            cfws,
+            char(','),
+        )),
+    )(i)
+}
+
+// year     = (FWS 4*DIGIT FWS) / obs-year
+// obs-year = [CFWS] 2*DIGIT [CFWS]
+// Two-digit years below 50 are in the 2000s, and three-digit years are
+// counted from 1900 (RFC 5322, section 4.3).
+fn year(i: &[u8]) -> IResult<&[u8], u32> {
+    map(
+        preceded(cfws, take_while_m_n(2, 4, |c: u8| c.is_ascii_digit())),
+        |digits: &[u8]| {
+            let year = from_utf8(digits)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0);
+            match digits.len() {
+                2 if year < 50 => 2000 + year,
+                2 | 3 => 1900 + year,
+                _ => year,
+            }
+        },
+    )(i)
+}
+
+// time-of-day = hour ":" minute [ ":" second ]
+fn time_of_day(i: &[u8]) -> IResult<&[u8], (u32, u32, u32)> {
+    map(
+        tuple((
+            preceded(cfws, number(1, 2)),
+            preceded(tuple((cfws, char(':'), cfws)), number(2, 2)),
+            opt(preceded(tuple((cfws, char(':'), cfws)), number(2, 2))),
+        )),
+        |(hour, minute, second)| (hour, minute, second.unwrap_or(0)),
+    )(i)
+}
+
+// zone     = (FWS ( "+" / "-" ) 4DIGIT) / obs-zone
+// obs-zone = "UT" / "GMT" / "EST" / "EDT" / "CST" / "CDT" / "MST" / "MDT" /
+//            "PST" / "PDT" / %d65-73 / %d75-90 / %d97-105 / %d107-122
+// Military zones were used inconsistently, so like `-0000` they are
+// taken to mean UTC.
+fn zone(i: &[u8]) -> IResult<&[u8], i16> {
+    preceded(
+        cfws,
+        alt((
+            map(
+                tuple((
+                    alt((value(1, char('+')), value(-1, char('-')))),
+                    number(4, 4),
+                )),
+                |(sign, hhmm)| sign * (hhmm / 100 * 60 + hhmm % 100) as i16,
+            ),
+            value(
+                0,
+                alt((tag_no_case("UTC"), tag_no_case("UT"), tag_no_case("GMT"))),
+            ),
+            value(-300, alt((tag_no_case("EST"), tag_no_case("CDT")))),
+            value(-240, tag_no_case("EDT")),
+            value(-360, alt((tag_no_case("CST"), tag_no_case("MDT")))),
+            value(-420, alt((tag_no_case("MST"), tag_no_case("PDT")))),
+            value(-480, tag_no_case("PST")),
+            value(0, take_while_m_n(1, 1, |c: u8| c.is_ascii_alphabetic())),
+        )),
+    )(i)
+}
+
+// date-time = [ day-of-week "," ] date time [CFWS]
+// date      = day month year
+// time      = time-of-day zone
+// [RFC5322 - 3.3. Date and Time Specification](https://tools.ietf.org/html/rfc5322#section-3.3)
+pub fn date_time(i: &[u8]) -> IResult<&[u8], DateTime> {
+    map_opt(
+        tuple((
+            opt(day_of_week),
+            preceded(cfws, number(1, 2)),
+            preceded(cfws, date_month),
+            year,
+            time_of_day,
+            zone,
+            cfws,
+        )),
+        |(_, day, month, year, (hour, minute, second), offset, _)| {
+            let dt = DateTime {
+                year: year as u16,
+                month,
+                day: day as u8,
+                hour: hour as u8,
+                minute: minute as u8,
+                second: second as u8,
+                offset,
+            };
+            if (1..=31).contains(&dt.day) && dt.hour < 24 && dt.minute < 60 && dt.second < 61 {
+                Some(dt)
+            } else {
+                None
+            }
+        },
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::DateTime;
+
+    #[test]
+    fn test_date_time() {
+        let dt = DateTime {
+            year: 1996,
+            month: 7,
+            day: 17,
+            hour: 2,
+            minute: 44,
+            second: 25,
+            offset: -420,
+        };
+        assert_eq!(
+            DateTime::parse_rfc5322("Wed, 17 Jul 1996 02:44:25 -0700"),
+            Some(dt)
+        );
+        assert_eq!(
+            DateTime::parse_rfc5322("17 Jul 1996 02:44:25 -0700 (PDT)"),
+            Some(dt)
+        );
+        assert_eq!(
+            DateTime::parse_rfc5322("Wed,17 jul 96 02:44:25 PDT"),
+            Some(dt)
+        );
+        assert_eq!(
+            DateTime::parse_rfc5322(" Wed , 17\r\n Jul 1996 2:44 : 25 -0700"),
+            Some(dt)
+        );
+        assert_eq!(
+            DateTime::parse_rfc5322("1 Feb 2020 23:59 Z"),
+            Some(DateTime {
+                year: 2020,
+                month: 2,
+                day: 1,
+                hour: 23,
+                minute: 59,
+                second: 0,
+                offset: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_date_time() {
+        assert_eq!(DateTime::parse_rfc5322(""), None);
+        assert_eq!(DateTime::parse_rfc5322("17 Jul 1996 02:44:25"), None);
+        assert_eq!(DateTime::parse_rfc5322("32 Jul 1996 02:44:25 +0000"), None);
+        assert_eq!(
+            DateTime::parse_rfc5322("17 Jul 1996 02:44:25 +0000 x"),
+            None
+        );
+        assert_eq!(DateTime::parse_rfc5322("1996-07-17T02:44:25Z"), None);
+    }
+}
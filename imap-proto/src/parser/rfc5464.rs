@@ -4,23 +4,28 @@
 //! IMAP METADATA extension
 //!
 
+use std::borrow::Cow;
+
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
-    combinator::{map, map_opt},
+    combinator::map,
     multi::separated_list,
-    sequence::tuple,
+    sequence::{preceded, tuple},
     IResult,
 };
 
-use crate::{parser::core::*, types::*};
+use crate::{
+    parser::{core::*, rfc3501::mailbox},
+    types::*,
+};
 
 fn is_entry_component_char(c: u8) -> bool {
     c < 0x80 && c > 0x19 && c != b'*' && c != b'%' && c != b'/'
 }
 
 enum EntryParseStage<'a> {
-    PrivateShared(usize),
+    PrivateShared,
     Admin(usize),
     VendorComment(usize),
     Path(usize),
@@ -28,7 +33,7 @@ enum EntryParseStage<'a> {
     Fail(nom::Err<&'a [u8]>),
 }
 
-fn check_private_shared(i: &[u8]) -> EntryParseStage {
+fn check_private_shared(i: &[u8]) -> EntryParseStage<'_> {
     if i.starts_with(b"/private") {
         EntryParseStage::VendorComment(8)
     } else if i.starts_with(b"/shared") {
@@ -40,7 +45,7 @@ fn check_private_shared(i: &[u8]) -> EntryParseStage {
     }
 }
 
-fn check_admin(i: &[u8], l: usize) -> EntryParseStage {
+fn check_admin(i: &[u8], l: usize) -> EntryParseStage<'_> {
     if i[l..].starts_with(b"/admin") {
         EntryParseStage::Path(l + 6)
     } else {
@@ -48,7 +53,7 @@ fn check_admin(i: &[u8], l: usize) -> EntryParseStage {
     }
 }
 
-fn check_vendor_comment(i: &[u8], l: usize) -> EntryParseStage {
+fn check_vendor_comment(i: &[u8], l: usize) -> EntryParseStage<'_> {
     if i[l..].starts_with(b"/comment") {
         EntryParseStage::Path(l + 8)
     } else if i[l..].starts_with(b"/vendor") {
@@ -65,7 +70,7 @@ fn check_vendor_comment(i: &[u8], l: usize) -> EntryParseStage {
     }
 }
 
-fn check_path(i: &[u8], l: usize) -> EntryParseStage {
+fn check_path(i: &[u8], l: usize) -> EntryParseStage<'_> {
     if i.len() == l || i[l] == b' ' || i[l] == b'\r' {
         return EntryParseStage::Done(l);
     } else if i[l] != b'/' {
@@ -80,10 +85,10 @@ fn check_path(i: &[u8], l: usize) -> EntryParseStage {
 }
 
 fn check_entry_name(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    let mut stage = EntryParseStage::PrivateShared(0);
+    let mut stage = EntryParseStage::PrivateShared;
     loop {
         match stage {
-            EntryParseStage::PrivateShared(_) => {
+            EntryParseStage::PrivateShared => {
                 stage = check_private_shared(i);
             }
             EntryParseStage::Admin(l) => {
@@ -112,81 +117,82 @@ fn check_entry_name(i: &[u8]) -> IResult<&[u8], &[u8]> {
     }
 }
 
-fn entry_name(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    let astring_res = astring(i)?;
-    check_entry_name(astring_res.1)?;
-    Ok(astring_res)
-}
-
-fn slice_to_str(i: &[u8]) -> &str {
-    std::str::from_utf8(i).unwrap()
-}
-
-fn nil_value(i: &[u8]) -> IResult<&[u8], Option<String>> {
-    map_opt(tag_no_case("NIL"), |_| None)(i)
-}
-
-fn string_value(i: &[u8]) -> IResult<&[u8], Option<String>> {
-    map(alt((quoted, literal)), |s| {
-        Some(slice_to_str(s).to_string())
-    })(i)
+fn entry_name(i: &[u8]) -> IResult<&[u8], Cow<'_, [u8]>> {
+    let (rest, name) = astring(i)?;
+    if let Err(err) = check_entry_name(&name) {
+        return Err(err.map(|(_, kind)| (i, kind)));
+    }
+    Ok((rest, name))
 }
 
-fn keyval_list(i: &[u8]) -> IResult<&[u8], Vec<Metadata>> {
+fn keyval_list(i: &[u8]) -> IResult<&[u8], Vec<Metadata<'_>>> {
     parenthesized_nonempty_list(map(
-        tuple((
-            map(entry_name, slice_to_str),
-            tag(" "),
-            alt((nil_value, string_value)),
-        )),
-        |(key, _, value)| Metadata {
-            entry: key.to_string(),
-            value,
-        },
+        tuple((map(entry_name, cow_utf8), tag(" "), nstring)),
+        |(entry, _, value)| Metadata { entry, value },
     ))(i)
 }
 
-fn entry_list(i: &[u8]) -> IResult<&[u8], Vec<&str>> {
-    separated_list(tag(" "), map(entry_name, slice_to_str))(i)
+fn entry_list(i: &[u8]) -> IResult<&[u8], Vec<Cow<'_, str>>> {
+    separated_list(tag(" "), map(entry_name, cow_utf8))(i)
 }
 
-fn metadata_common(i: &[u8]) -> IResult<&[u8], &[u8]> {
-    let (i, (_, mbox, _)) = tuple((tag_no_case("METADATA "), quoted, tag(" ")))(i)?;
+fn metadata_common(i: &[u8]) -> IResult<&[u8], Cow<'_, str>> {
+    let (i, (_, mbox, _)) = tuple((tag_no_case("METADATA "), mailbox, tag(" ")))(i)?;
     Ok((i, mbox))
 }
 
 // [RFC5464 - 4.4.1 METADATA Response with values]
-fn metadata_solicited(i: &[u8]) -> IResult<&[u8], Response> {
+fn metadata_solicited(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     let (i, (mailbox, values)) = tuple((metadata_common, keyval_list))(i)?;
     Ok((
         i,
-        Response::MailboxData(MailboxDatum::MetadataSolicited {
-            mailbox: slice_to_str(mailbox),
-            values,
-        }),
+        Response::MailboxData(MailboxDatum::MetadataSolicited { mailbox, values }),
     ))
 }
 
 // [RFC5464 - 4.4.2 Unsolicited METADATA Response without values]
-fn metadata_unsolicited(i: &[u8]) -> IResult<&[u8], Response> {
+fn metadata_unsolicited(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     let (i, (mailbox, values)) = tuple((metadata_common, entry_list))(i)?;
     Ok((
         i,
-        Response::MailboxData(MailboxDatum::MetadataUnsolicited {
-            mailbox: slice_to_str(mailbox),
-            values,
-        }),
+        Response::MailboxData(MailboxDatum::MetadataUnsolicited { mailbox, values }),
     ))
 }
 
 // Parse solicited or unsolicited METADATA response.
-pub fn resp_metadata(i: &[u8]) -> IResult<&[u8], Response> {
+pub fn resp_metadata(i: &[u8]) -> IResult<&[u8], Response<'_>> {
     alt((metadata_solicited, metadata_unsolicited))(i)
 }
 
+// resp-text-code =/ "METADATA" SP "LONGENTRIES" SP number /
+//                   "METADATA" SP "MAXSIZE" SP number /
+//                   "METADATA" SP "TOOMANY" /
+//                   "METADATA" SP "NOPRIVATE"
+// [RFC5464 - 4.2.1 MAXSIZE, 4.3 GETMETADATA, 5. Formal Syntax]
+pub(crate) fn resp_text_code_metadata(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    preceded(
+        tag_no_case("METADATA "),
+        alt((
+            map(
+                preceded(tag_no_case("LONGENTRIES "), number_64),
+                ResponseCode::MetadataLongEntries,
+            ),
+            map(
+                preceded(tag_no_case("MAXSIZE "), number_64),
+                ResponseCode::MetadataMaxSize,
+            ),
+            map(tag_no_case("TOOMANY"), |_| ResponseCode::MetadataTooMany),
+            map(tag_no_case("NOPRIVATE"), |_| {
+                ResponseCode::MetadataNoPrivate
+            }),
+        )),
+    )(i)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{metadata_solicited, metadata_unsolicited};
+    use crate::parser::rfc3501::parse_response;
     use crate::types::*;
 
     #[test]
@@ -238,21 +244,9 @@ mod tests {
                 assert_eq!(i, b"\r\n");
                 assert_eq!(values.len(), 2);
                 assert_eq!(values[0].entry, "/shared/vendor/vendorname");
-                assert_eq!(
-                    values[0]
-                        .value
-                        .as_ref()
-                        .expect("None value is not expected"),
-                    "asdf"
-                );
+                assert_eq!(values[0].value.as_deref(), Some(&b"asdf"[..]));
                 assert_eq!(values[1].entry, "/private/comment/a");
-                assert_eq!(
-                    values[1]
-                        .value
-                        .as_ref()
-                        .expect("None value is not expected"),
-                    "bbb"
-                );
+                assert_eq!(values[1].value.as_deref(), Some(&b"bbb"[..]));
             }
             _ => panic!("Correct METADATA response is not parsed properly."),
         }
@@ -267,19 +261,27 @@ mod tests {
                 assert_eq!(i, b"\r\n");
                 assert_eq!(values.len(), 1);
                 assert_eq!(values[0].entry, "/shared/vendor/vendor.coi/a");
-                assert_eq!(
-                    values[0]
-                        .value
-                        .as_ref()
-                        .expect("None value is not expected"),
-                    "AAA"
-                );
+                assert_eq!(values[0].value.as_deref(), Some(&b"AAA"[..]));
             }
             Err(e) => panic!("ERR: {:?}", e),
             _ => panic!("Strange failure"),
         }
     }
 
+    #[test]
+    fn test_binary_value() {
+        match metadata_solicited(
+            b"METADATA \"\" (/private/comment {2}\r\n\xff\xfe /shared/comment NIL)\r\n",
+        ) {
+            Ok((_, Response::MailboxData(MailboxDatum::MetadataSolicited { values, .. }))) => {
+                assert_eq!(values[0].value.as_deref(), Some(&b"\xff\xfe"[..]));
+                assert_eq!(values[1].entry, "/shared/comment");
+                assert_eq!(values[1].value, None);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
     #[test]
     fn test_unsolicited_success() {
         match metadata_unsolicited(b"METADATA \"theBox\" /shared/admin/qwe /private/comment/a\r\n")
@@ -297,4 +299,65 @@ mod tests {
             _ => panic!("Correct METADATA response is not parsed properly."),
         }
     }
+
+    #[test]
+    fn test_atom_mailbox() {
+        match metadata_unsolicited(b"METADATA INBOX /shared/comment\r\n") {
+            Ok((
+                _,
+                Response::MailboxData(MailboxDatum::MetadataUnsolicited { mailbox, values }),
+            )) => {
+                assert_eq!(mailbox, "INBOX");
+                assert_eq!(values, vec!["/shared/comment"]);
+            }
+            _ => panic!("Correct METADATA response is not parsed properly."),
+        }
+    }
+
+    #[test]
+    fn test_response_codes() {
+        match parse_response(b"a OK [METADATA LONGENTRIES 2199] GETMETADATA complete\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    code: Some(ResponseCode::MetadataLongEntries(2199)),
+                    ..
+                },
+            )) => {}
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"a NO [METADATA MAXSIZE 1024] Annotation too large\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    code: Some(ResponseCode::MetadataMaxSize(1024)),
+                    ..
+                },
+            )) => {}
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"a NO [METADATA TOOMANY] Too many annotations\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    code: Some(ResponseCode::MetadataTooMany),
+                    ..
+                },
+            )) => {}
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"a NO [METADATA NOPRIVATE] No private annotations\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    code: Some(ResponseCode::MetadataNoPrivate),
+                    ..
+                },
+            )) => {}
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
 }
@@ -0,0 +1,96 @@
+//!
+//! https://tools.ietf.org/html/rfc5465
+//!
+//! The IMAP NOTIFY Extension
+//!
+
+use std::borrow::Cow;
+
+use nom::{bytes::streaming::tag_no_case, combinator::map, sequence::preceded, IResult};
+
+use crate::{
+    parser::core::{atom, parenthesized_nonempty_list},
+    types::*,
+};
+
+// resp-text-code =/ "NOTIFICATIONOVERFLOW"
+// [RFC5465 - 8. Formal Syntax](https://tools.ietf.org/html/rfc5465#section-8)
+pub(crate) fn resp_text_code_notification_overflow(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(tag_no_case("NOTIFICATIONOVERFLOW"), |_| {
+        ResponseCode::NotificationOverflow
+    })(i)
+}
+
+// unsupported-events-code = "BADEVENT"
+//                           SP "(" event *(SP event) ")"
+// All event names, including extensions, are atoms.
+// [RFC5465 - 8. Formal Syntax](https://tools.ietf.org/html/rfc5465#section-8)
+pub(crate) fn resp_text_code_bad_event(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(
+        preceded(
+            tag_no_case("BADEVENT "),
+            parenthesized_nonempty_list(map(atom, Cow::Borrowed)),
+        ),
+        ResponseCode::BadEvent,
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_notify_codes() {
+        match parse_response(b"* OK [NOTIFICATIONOVERFLOW] Too many events\r\n") {
+            Ok((
+                _,
+                Response::Data {
+                    code: Some(ResponseCode::NotificationOverflow),
+                    ..
+                },
+            )) => {}
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"A1 NO [BADEVENT (MessageNew MessageExpunge FlagChange)] Bad\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    code: Some(ResponseCode::BadEvent(events)),
+                    ..
+                },
+            )) => {
+                assert_eq!(events, vec!["MessageNew", "MessageExpunge", "FlagChange"]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_notify_status() {
+        match parse_response(
+            b"* STATUS Lists/Lemonade (UIDVALIDITY 4 UIDNEXT 9999 MESSAGES 500)\r\n",
+        ) {
+            Ok((_, Response::MailboxData(MailboxDatum::Status { mailbox, status }))) => {
+                assert_eq!(mailbox, "Lists/Lemonade");
+                assert_eq!(
+                    status,
+                    vec![
+                        StatusAttribute::UidValidity(4),
+                        StatusAttribute::UidNext(9999),
+                        StatusAttribute::Messages(500),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* STATUS INBOX ()\r\n") {
+            Ok((_, Response::MailboxData(MailboxDatum::Status { status, .. }))) => {
+                assert!(status.is_empty());
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//!
+//! https://tools.ietf.org/html/rfc7162
+//!
+//! The IMAP QRESYNC Extensions
+//!
+
+use nom::{
+    bytes::streaming::tag_no_case,
+    combinator::{map, opt},
+    sequence::tuple,
+    IResult,
+};
+
+use crate::{parser::core::sequence_set, types::*};
+
+// The VANISHED response reports that the specified UIDs have been
+// permanently removed from the mailbox.
+// expunged-resp = "VANISHED" [SP "(EARLIER)"] SP known-uids
+// [RFC7162 - 3.2.10 VANISHED Response](https://tools.ietf.org/html/rfc7162#section-3.2.10)
+pub(crate) fn resp_vanished(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    map(
+        tuple((
+            tag_no_case("VANISHED "),
+            opt(tag_no_case("(EARLIER) ")),
+            sequence_set,
+        )),
+        |(_, earlier, uids)| Response::Vanished {
+            earlier: earlier.is_some(),
+            uids,
+        },
+    )(i)
+}
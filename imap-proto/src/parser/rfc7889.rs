@@ -0,0 +1,64 @@
+//!
+//! https://tools.ietf.org/html/rfc7889
+//!
+//! The IMAP APPENDLIMIT Extension
+//!
+
+use nom::{
+    branch::alt, bytes::streaming::tag_no_case, combinator::map, sequence::preceded, IResult,
+};
+
+use crate::{
+    parser::core::{nil, number_64},
+    types::*,
+};
+
+// status-att-val =/ "APPENDLIMIT" SP (number / nil)
+// The limit is parsed as a 64-bit number, like the capability.
+// [RFC7889 - 5. Formal Syntax](https://tools.ietf.org/html/rfc7889#section-5)
+pub(crate) fn status_att_val_append_limit(i: &[u8]) -> IResult<&[u8], StatusAttribute<'_>> {
+    map(
+        preceded(
+            tag_no_case("APPENDLIMIT "),
+            alt((map(number_64, Some), map(nil, |_| None))),
+        ),
+        StatusAttribute::AppendLimit,
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_append_limit() {
+        match parse_response(b"* CAPABILITY IMAP4rev1 APPENDLIMIT=35651584 APPENDLIMIT\r\n") {
+            Ok((_, Response::Capabilities(caps))) => {
+                assert_eq!(
+                    caps,
+                    vec![
+                        Capability::Imap4rev1,
+                        Capability::AppendLimit(Some(35_651_584)),
+                        Capability::AppendLimit(None),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* STATUS INBOX (APPENDLIMIT 257890)\r\n") {
+            Ok((_, Response::MailboxData(MailboxDatum::Status { status, .. }))) => {
+                assert_eq!(status, vec![StatusAttribute::AppendLimit(Some(257_890))]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* STATUS INBOX (APPENDLIMIT NIL)\r\n") {
+            Ok((_, Response::MailboxData(MailboxDatum::Status { status, .. }))) => {
+                assert_eq!(status, vec![StatusAttribute::AppendLimit(None)]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
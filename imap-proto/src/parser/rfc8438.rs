@@ -0,0 +1,19 @@
+//!
+//! https://tools.ietf.org/html/rfc8438
+//!
+//! IMAP Extension for STATUS=SIZE
+//!
+
+use nom::{bytes::streaming::tag_no_case, combinator::map, sequence::preceded, IResult};
+
+use crate::{parser::core::number_64, types::*};
+
+// status-att =/ "SIZE"
+// status-att-val =/ "SIZE" SP number64
+// [RFC8438 - 3. Formal Syntax](https://tools.ietf.org/html/rfc8438#section-3)
+pub(crate) fn status_att_val_size(i: &[u8]) -> IResult<&[u8], StatusAttribute<'_>> {
+    map(
+        preceded(tag_no_case("SIZE "), number_64),
+        StatusAttribute::Size,
+    )(i)
+}
@@ -0,0 +1,135 @@
+//!
+//! https://tools.ietf.org/html/rfc8474
+//!
+//! IMAP Extension for Object Identifiers
+//!
+
+use std::borrow::Cow;
+use std::str::from_utf8;
+
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag_no_case, take_while1},
+    character::streaming::char,
+    combinator::{map, map_res},
+    sequence::{delimited, preceded},
+    IResult,
+};
+
+use crate::{parser::core::nil, types::*};
+
+fn is_objectid_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'-'
+}
+
+// objectid = 1*255(ALPHA / DIGIT / "_" / "-")
+//         ; characters in object identifiers are case
+//         ; significant
+pub(crate) fn objectid(i: &[u8]) -> IResult<&[u8], Cow<'_, str>> {
+    map(
+        map_res(take_while1(is_objectid_char), from_utf8),
+        Cow::Borrowed,
+    )(i)
+}
+
+// resp-text-code =/ "MAILBOXID" SP "(" objectid ")"
+// [RFC8474 - 4.1. New Response Code for SELECT/EXAMINE/CREATE](https://tools.ietf.org/html/rfc8474#section-4.1)
+pub(crate) fn resp_text_code_mailbox_id(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(
+        preceded(
+            tag_no_case("MAILBOXID "),
+            delimited(char('('), objectid, char(')')),
+        ),
+        ResponseCode::MailboxId,
+    )(i)
+}
+
+// status-att-val =/ "MAILBOXID" SP "(" objectid ")"
+// [RFC8474 - 4.2. New Status Data Item](https://tools.ietf.org/html/rfc8474#section-4.2)
+pub(crate) fn status_att_val_mailbox_id(i: &[u8]) -> IResult<&[u8], StatusAttribute<'_>> {
+    map(
+        preceded(
+            tag_no_case("MAILBOXID "),
+            delimited(char('('), objectid, char(')')),
+        ),
+        StatusAttribute::MailboxId,
+    )(i)
+}
+
+// fetch-emailid-resp = "EMAILID" SP "(" objectid ")"
+// [RFC8474 - 5.1. EMAILID Identifier](https://tools.ietf.org/html/rfc8474#section-5.1)
+pub(crate) fn msg_att_email_id(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(
+        preceded(
+            tag_no_case("EMAILID "),
+            delimited(char('('), objectid, char(')')),
+        ),
+        AttributeValue::EmailId,
+    )(i)
+}
+
+// fetch-threadid-resp = "THREADID" SP ( "(" objectid ")" / nil )
+// [RFC8474 - 5.2. THREADID Identifier](https://tools.ietf.org/html/rfc8474#section-5.2)
+pub(crate) fn msg_att_thread_id(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(
+        preceded(
+            tag_no_case("THREADID "),
+            alt((
+                map(delimited(char('('), objectid, char(')')), Some),
+                map(nil, |_| None),
+            )),
+        ),
+        AttributeValue::ThreadId,
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_mailbox_id() {
+        match parse_response(b"3 OK [MAILBOXID (F2212ea87-6097-4256-9d51-71338625)] Completed\r\n")
+        {
+            Ok((
+                _,
+                Response::Done {
+                    code: Some(ResponseCode::MailboxId(id)),
+                    information: Some(Cow::Borrowed("Completed")),
+                    ..
+                },
+            )) => {
+                assert_eq!(id, "F2212ea87-6097-4256-9d51-71338625");
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_email_thread_id() {
+        match parse_response(
+            b"* 3 FETCH (EMAILID (M5fdc09b49ea703) THREADID (T11863d02dd95b5))\r\n",
+        ) {
+            Ok((_, Response::Fetch(Seq(3), attrs))) => {
+                assert_eq!(
+                    attrs,
+                    vec![
+                        AttributeValue::EmailId(Cow::Borrowed("M5fdc09b49ea703")),
+                        AttributeValue::ThreadId(Some(Cow::Borrowed("T11863d02dd95b5"))),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* 4 FETCH (THREADID NIL)\r\n") {
+            Ok((_, Response::Fetch(Seq(4), attrs))) => {
+                assert_eq!(attrs, vec![AttributeValue::ThreadId(None)]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//!
+//! https://tools.ietf.org/html/rfc8514
+//!
+//! IMAP SAVEDATE Extension
+//!
+
+use nom::{
+    branch::alt, bytes::streaming::tag_no_case, combinator::map, sequence::preceded, IResult,
+};
+
+use crate::{
+    parser::{core::nil, rfc3501::date_time::quoted_date_time},
+    types::*,
+};
+
+// msg-att-dynamic =/ "SAVEDATE" SP (date-time / nil)
+// [RFC8514 - 5. Formal Syntax](https://tools.ietf.org/html/rfc8514#section-5)
+pub(crate) fn msg_att_save_date(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(
+        preceded(
+            tag_no_case("SAVEDATE "),
+            alt((map(quoted_date_time, Some), map(nil, |_| None))),
+        ),
+        AttributeValue::SaveDate,
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_save_date() {
+        match parse_response(b"* 1 FETCH (UID 1 SAVEDATE \"17-Jul-1996 02:44:25 -0700\")\r\n") {
+            Ok((_, Response::Fetch(Seq(1), attrs))) => {
+                assert_eq!(
+                    attrs,
+                    vec![
+                        AttributeValue::Uid(Uid(1)),
+                        AttributeValue::SaveDate(DateTime::parse("17-Jul-1996 02:44:25 -0700")),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* 2 FETCH (SAVEDATE NIL)\r\n") {
+            Ok((_, Response::Fetch(Seq(2), attrs))) => {
+                assert_eq!(attrs, vec![AttributeValue::SaveDate(None)]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//!
+//! https://tools.ietf.org/html/rfc8970
+//!
+//! IMAP4 Extension: Message Preview Generation
+//!
+
+use nom::{bytes::streaming::tag_no_case, combinator::map, sequence::preceded, IResult};
+
+use crate::{parser::core::nstring_utf8, types::*};
+
+// msg-att-dynamic =/ "PREVIEW" SP nstring
+// The preview text is required to be UTF-8.
+// [RFC8970 - 7. Formal Syntax](https://tools.ietf.org/html/rfc8970#section-7)
+pub(crate) fn msg_att_preview(i: &[u8]) -> IResult<&[u8], AttributeValue<'_>> {
+    map(preceded(tag_no_case("PREVIEW "), nstring_utf8), |preview| {
+        AttributeValue::Preview(preview)
+    })(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_preview() {
+        match parse_response(b"* 1 FETCH (UID 1 PREVIEW \"Hello, this is a preview\")\r\n") {
+            Ok((_, Response::Fetch(Seq(1), attrs))) => {
+                assert_eq!(
+                    attrs,
+                    vec![
+                        AttributeValue::Uid(Uid(1)),
+                        AttributeValue::Preview(Some(Cow::Borrowed("Hello, this is a preview"))),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* 2 FETCH (PREVIEW NIL)\r\n") {
+            Ok((_, Response::Fetch(Seq(2), attrs))) => {
+                assert_eq!(attrs, vec![AttributeValue::Preview(None)]);
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//!
+//! https://tools.ietf.org/html/rfc9394
+//!
+//! IMAP PARTIAL Extension for Paged SEARCH and FETCH
+//!
+
+use nom::{
+    branch::alt,
+    bytes::streaming::tag_no_case,
+    character::streaming::char,
+    combinator::map,
+    sequence::{delimited, preceded, separated_pair, tuple},
+    IResult,
+};
+
+use crate::{parser::core::*, types::*};
+
+// partial-range-first = nz-number ":" nz-number
+// partial-range-last  = MINUS nz-number ":" MINUS nz-number
+// partial-range       = partial-range-first / partial-range-last
+// [RFC9394 - 5. Formal Syntax](https://tools.ietf.org/html/rfc9394#section-5)
+fn partial_range(i: &[u8]) -> IResult<&[u8], PartialRange> {
+    alt((
+        map(
+            separated_pair(nz_number, char(':'), nz_number),
+            |(first, last)| PartialRange::First(first, last),
+        ),
+        map(
+            separated_pair(preceded(char('-'), nz_number), tag_no_case(":-"), nz_number),
+            |(first, last)| PartialRange::Last(first, last),
+        ),
+    ))(i)
+}
+
+// ret-data-partial = "PARTIAL" SP "(" partial-range SP partial-results ")"
+// partial-results  = sequence-set / "NIL"
+// [RFC9394 - 5. Formal Syntax](https://tools.ietf.org/html/rfc9394#section-5)
+pub(crate) fn search_return_data_partial(i: &[u8]) -> IResult<&[u8], SearchReturnData> {
+    map(
+        preceded(
+            tag_no_case("PARTIAL "),
+            delimited(
+                char('('),
+                tuple((
+                    partial_range,
+                    preceded(
+                        char(' '),
+                        alt((map(nil, |_| None), map(sequence_set, Some))),
+                    ),
+                )),
+                char(')'),
+            ),
+        ),
+        |(range, results)| SearchReturnData::Partial(range, results),
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_partial() {
+        match parse_response(b"* ESEARCH (TAG \"A02\") UID PARTIAL (-1:-100 200:250,252:300)\r\n") {
+            Ok((_, Response::ESearch(esearch))) => {
+                assert_eq!(
+                    esearch.data,
+                    vec![SearchReturnData::Partial(
+                        PartialRange::Last(1, 100),
+                        Some(SequenceSet::Set(vec![
+                            SeqRange::new(200.into(), 250.into()),
+                            SeqRange::new(252.into(), 300.into()),
+                        ])),
+                    )]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        match parse_response(b"* ESEARCH (TAG \"A04\") UID PARTIAL (23500:24000 NIL)\r\n") {
+            Ok((_, Response::ESearch(esearch))) => {
+                assert_eq!(
+                    esearch.data,
+                    vec![SearchReturnData::Partial(
+                        PartialRange::First(23500, 24000),
+                        None
+                    )]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
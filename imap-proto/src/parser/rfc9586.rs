@@ -0,0 +1,71 @@
+//!
+//! https://tools.ietf.org/html/rfc9586
+//!
+//! IMAP Extension for Using and Returning Unique Identifiers (UIDs) Only
+//!
+
+use nom::{bytes::streaming::tag_no_case, combinator::map, sequence::tuple, IResult};
+
+use crate::{
+    parser::{core::number, error::context, rfc3501::msg_att_list},
+    types::*,
+};
+
+// message-data    =/ uidfetch-resp
+// uidfetch-resp   = uniqueid SP "UIDFETCH" SP msg-att
+// [RFC9586 - 7. Formal Syntax](https://tools.ietf.org/html/rfc9586#section-7)
+pub(crate) fn resp_uidfetch(i: &[u8]) -> IResult<&[u8], Response<'_>> {
+    context(
+        "message-data",
+        map(
+            tuple((number, tag_no_case(" UIDFETCH "), msg_att_list)),
+            |(uid, _, attrs)| Response::UidFetch(Uid(uid), attrs),
+        ),
+    )(i)
+}
+
+// resp-text-code  =/ "UIDREQUIRED"
+// [RFC9586 - 7. Formal Syntax](https://tools.ietf.org/html/rfc9586#section-7)
+pub(crate) fn resp_text_code_uid_required(i: &[u8]) -> IResult<&[u8], ResponseCode<'_>> {
+    map(tag_no_case("UIDREQUIRED"), |_| ResponseCode::UidRequired)(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_uidfetch() {
+        match parse_response(b"* 25996 UIDFETCH (FLAGS (\\Seen) RFC822.SIZE 44)\r\n") {
+            Ok((_, Response::UidFetch(Uid(25996), attrs))) => {
+                assert_eq!(
+                    attrs,
+                    vec![
+                        AttributeValue::Flags(vec![Flag::Seen]),
+                        AttributeValue::Rfc822Size(44),
+                    ]
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_uid_required() {
+        match parse_response(b"a2 BAD [UIDREQUIRED] Message numbers are not allowed\r\n") {
+            Ok((
+                _,
+                Response::Done {
+                    status: Status::Bad,
+                    code: Some(ResponseCode::UidRequired),
+                    information: Some(Cow::Borrowed("Message numbers are not allowed")),
+                    ..
+                },
+            )) => {}
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
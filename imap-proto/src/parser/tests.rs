@@ -1,4 +1,10 @@
-use super::rfc3501::parse_response;
+use std::borrow::Cow;
+
+use super::rfc3501::{
+    parse_response, parse_response_complete, parse_response_incremental,
+    parse_response_incremental_with, parse_response_with, parse_responses_complete,
+};
+use super::{Options, Parsed};
 use crate::types::*;
 
 #[test]
@@ -17,7 +23,7 @@ fn test_unseen() {
             Response::Data {
                 status: Status::Ok,
                 code: Some(ResponseCode::Unseen(3)),
-                information: Some("Message 3 is first unseen"),
+                information: Some(Cow::Borrowed("Message 3 is first unseen")),
             },
         ) => {}
         rsp => panic!("unexpected response {:?}", rsp),
@@ -34,7 +40,7 @@ fn test_body_text() {
                 &AttributeValue::BodySection {
                     section: Some(SectionPath::Full(MessageSection::Text)),
                     index: None,
-                    data: Some(b"foo"),
+                    data: Some(Cow::Borrowed(b"foo")),
                 },
                 "body = {:?}",
                 body
@@ -51,11 +57,7 @@ fn test_body_structure() {
         Ok((_, Response::Fetch(_, attrs))) => {
             let body = &attrs[0];
             assert!(
-                if let AttributeValue::BodyStructure(_) = *body {
-                    true
-                } else {
-                    false
-                },
+                matches!(*body, AttributeValue::BodyStructure(_)),
                 "body = {:?}",
                 body
             );
@@ -64,6 +66,70 @@ fn test_body_structure() {
     }
 }
 
+#[test]
+fn test_body_non_extensible() {
+    const RESPONSE: &[u8] = b"* 15 FETCH (BODY (\"TEXT\" \"PLAIN\" (\"CHARSET\" \"us-ascii\") NIL NIL \"7BIT\" 3028 92) UID 42)\r\n";
+    match parse_response(RESPONSE) {
+        Ok((_, Response::Fetch(_, attrs))) => {
+            assert_eq!(attrs.len(), 2);
+            match attrs[0] {
+                AttributeValue::BodyStructure(BodyStructure::Text {
+                    ref common,
+                    ref other,
+                    lines: 92,
+                    ref extensions,
+                }) => {
+                    assert!(extensions.is_empty());
+                    assert_eq!(common.ty.subtype, "PLAIN");
+                    assert_eq!(other.octets, 3028);
+                    assert_eq!(other.md5, None);
+                }
+                ref body => panic!("unexpected body {:?}", body),
+            }
+            assert_eq!(attrs[1], AttributeValue::Uid(Uid(42)));
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_flags() {
+    match parse_response(b"* FLAGS (\\Answered \\seen $Forwarded \\X-Custom)\r\n") {
+        Ok((_, Response::MailboxData(MailboxDatum::Flags(flags)))) => {
+            assert_eq!(
+                flags,
+                vec![
+                    Flag::Answered,
+                    Flag::Seen,
+                    Flag::Keyword(Cow::Borrowed("$Forwarded")),
+                    Flag::Extension(Cow::Borrowed("\\X-Custom")),
+                ]
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* OK [PERMANENTFLAGS (\\Deleted \\*)] Limited\r\n") {
+        Ok((
+            _,
+            Response::Data {
+                code: Some(ResponseCode::PermanentFlags(flags)),
+                ..
+            },
+        )) => {
+            assert_eq!(flags, vec![Flag::Deleted, Flag::MayCreate]);
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* 1 FETCH (FLAGS (\\Recent))\r\n") {
+        Ok((_, Response::Fetch(Seq(1), attrs))) => {
+            assert_eq!(attrs, vec![AttributeValue::Flags(vec![Flag::Recent])]);
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
 #[test]
 fn test_status() {
     match parse_response(b"* STATUS blurdybloop (MESSAGES 231 UIDNEXT 44292)\r\n") {
@@ -81,10 +147,31 @@ fn test_status() {
     }
 }
 
+#[cfg(feature = "condstore")]
+#[test]
+fn test_status_extended() {
+    match parse_response(
+        b"* STATUS INBOX (SIZE 8589934592 MAILBOXID (F2212ea87) HIGHESTMODSEQ 90060128194045007)\r\n",
+    ) {
+        Ok((_, Response::MailboxData(MailboxDatum::Status { mailbox, status }))) => {
+            assert_eq!(mailbox, "INBOX");
+            assert_eq!(
+                status,
+                [
+                    StatusAttribute::Size(8_589_934_592),
+                    StatusAttribute::MailboxId(Cow::Borrowed("F2212ea87")),
+                    StatusAttribute::HighestModSeq(90_060_128_194_045_007),
+                ]
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
 #[test]
 fn test_notify() {
     match parse_response(b"* 3501 EXPUNGE\r\n") {
-        Ok((_, Response::Expunge(3501))) => {}
+        Ok((_, Response::Expunge(Seq(3501)))) => {}
         rsp => panic!("unexpected response {:?}", rsp),
     }
     match parse_response(b"* 3501 EXISTS\r\n") {
@@ -94,10 +181,10 @@ fn test_notify() {
     match parse_response(b"+ idling\r\n") {
         Ok((
             _,
-            Response::Continue {
+            Response::Continue(ContinueRequest {
                 code: None,
-                information: Some("idling"),
-            },
+                information: Some(Cow::Borrowed("idling")),
+            }),
         )) => {}
         rsp => panic!("unexpected response {:?}", rsp),
     }
@@ -120,6 +207,22 @@ fn test_search() {
     }
 }
 
+#[test]
+fn test_sort() {
+    match parse_response(b"* SORT 2 84 882\r\n") {
+        Ok((_, Response::Sort(ids))) => {
+            assert_eq!(ids, vec![2, 84, 882]);
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+    match parse_response(b"* SORT\r\n") {
+        Ok((_, Response::Sort(ids))) => {
+            assert!(ids.is_empty());
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
 #[test]
 fn test_uid_fetch() {
     match parse_response(b"* 4 FETCH (UID 71372 RFC822.HEADER {10275}\r\n") {
@@ -142,7 +245,35 @@ fn test_header_fields() {
     const RESPONSE: &[u8] = b"* 1 FETCH (UID 1 BODY[HEADER.FIELDS (CHAT-VERSION)] {21}\r\nChat-Version: 1.0\r\n\r\n)\r\n";
 
     match parse_response(RESPONSE) {
-        Ok((_, Response::Fetch(_, _))) => {}
+        Ok((_, Response::Fetch(_, attrs))) => match attrs[1] {
+            AttributeValue::BodySection {
+                section: Some(SectionPath::Full(MessageSection::HeaderFields(ref fields))),
+                index: None,
+                ..
+            } => assert_eq!(fields, &["CHAT-VERSION"]),
+            ref attr => panic!("unexpected attribute {:?}", attr),
+        },
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    const PARTIAL: &[u8] =
+        b"* 2 FETCH (BODY[1.2.HEADER.FIELDS.NOT (SUBJECT DATE)]<128> {3}\r\nfoo)\r\n";
+
+    match parse_response(PARTIAL) {
+        Ok((_, Response::Fetch(_, attrs))) => assert_eq!(
+            attrs,
+            vec![AttributeValue::BodySection {
+                section: Some(SectionPath::Part(
+                    vec![1, 2],
+                    Some(MessageSection::HeaderFieldsNot(vec![
+                        Cow::Borrowed("SUBJECT"),
+                        Cow::Borrowed("DATE")
+                    ])),
+                )),
+                index: Some(128),
+                data: Some(Cow::Borrowed(b"foo")),
+            }]
+        ),
         rsp => panic!("unexpected response {:?}", rsp),
     }
 }
@@ -155,7 +286,7 @@ fn test_response_codes() {
             Response::Data {
                 status: Status::Ok,
                 code: Some(ResponseCode::Alert),
-                information: Some("Alert!"),
+                information: Some(Cow::Borrowed("Alert!")),
             },
         )) => {}
         rsp => panic!("unexpected response {:?}", rsp),
@@ -167,7 +298,7 @@ fn test_response_codes() {
             Response::Data {
                 status: Status::No,
                 code: Some(ResponseCode::Parse),
-                information: Some("Something"),
+                information: Some(Cow::Borrowed("Something")),
             },
         )) => {}
         rsp => panic!("unexpected response {:?}", rsp),
@@ -179,12 +310,12 @@ fn test_response_codes() {
             Response::Data {
                 status: Status::Ok,
                 code: Some(ResponseCode::Capabilities(c)),
-                information: Some("Logged in"),
+                information: Some(Cow::Borrowed("Logged in")),
             },
         )) => {
             assert_eq!(c.len(), 2);
             assert_eq!(c[0], Capability::Imap4rev1);
-            assert_eq!(c[1], Capability::Atom("IDLE"));
+            assert_eq!(c[1], Capability::Idle);
         }
         rsp => panic!("unexpected response {:?}", rsp),
     }
@@ -195,13 +326,13 @@ fn test_response_codes() {
             Response::Data {
                 status: Status::Ok,
                 code: Some(ResponseCode::Capabilities(c)),
-                information: Some("Logged in"),
+                information: Some(Cow::Borrowed("Logged in")),
             },
         )) => {
             assert_eq!(c.len(), 3);
-            assert_eq!(c[0], Capability::Atom("UIDPLUS"));
+            assert_eq!(c[0], Capability::UidPlus);
             assert_eq!(c[1], Capability::Imap4rev1);
-            assert_eq!(c[2], Capability::Atom("IDLE"));
+            assert_eq!(c[2], Capability::Idle);
         }
         rsp => panic!("unexpected response {:?}", rsp),
     }
@@ -213,7 +344,7 @@ fn test_response_codes() {
             Response::Data {
                 status: Status::Ok,
                 code: None,
-                information: Some("[CAPABILITY UIDPLUS IDLE] Logged in"),
+                information: Some(Cow::Borrowed("[CAPABILITY UIDPLUS IDLE] Logged in")),
             },
         )) => {}
         rsp => panic!("unexpected response {:?}", rsp),
@@ -225,7 +356,7 @@ fn test_response_codes() {
             Response::Data {
                 status: Status::No,
                 code: Some(ResponseCode::BadCharset(None)),
-                information: Some("error"),
+                information: Some(Cow::Borrowed("error")),
             },
         )) => {}
         rsp => panic!("unexpected response {:?}", rsp),
@@ -237,7 +368,7 @@ fn test_response_codes() {
             Response::Data {
                 status: Status::No,
                 code: Some(ResponseCode::BadCharset(Some(v))),
-                information: Some("error"),
+                information: Some(Cow::Borrowed("error")),
             },
         )) => {
             assert_eq!(v.len(), 2);
@@ -247,13 +378,41 @@ fn test_response_codes() {
         rsp => panic!("unexpected response {:?}", rsp),
     }
 
+    match parse_response(b"* OK [X-SOMETHING-NEW 1 2] Unknown\r\n") {
+        Ok((
+            _,
+            Response::Data {
+                status: Status::Ok,
+                code:
+                    Some(ResponseCode::Other(
+                        Cow::Borrowed("X-SOMETHING-NEW"),
+                        Some(Cow::Borrowed("1 2")),
+                    )),
+                information: Some(Cow::Borrowed("Unknown")),
+            },
+        )) => {}
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* NO [UNAVAILABLE] Try again\r\n") {
+        Ok((
+            _,
+            Response::Data {
+                status: Status::No,
+                code: Some(ResponseCode::Other(Cow::Borrowed("UNAVAILABLE"), None)),
+                information: Some(Cow::Borrowed("Try again")),
+            },
+        )) => {}
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
     match parse_response(b"* NO [BADCHARSET ()] error\r\n") {
         Ok((
             _,
             Response::Data {
                 status: Status::No,
                 code: None,
-                information: Some("[BADCHARSET ()] error"),
+                information: Some(Cow::Borrowed("[BADCHARSET ()] error")),
             },
         )) => {}
         rsp => panic!("unexpected response {:?}", rsp),
@@ -274,10 +433,10 @@ fn test_continuation() {
     match parse_response(b"+ \r\n") {
         Ok((
             _,
-            Response::Continue {
+            Response::Continue(ContinueRequest {
                 code: None,
                 information: None,
-            },
+            }),
         )) => {}
         rsp => panic!("unexpected response {:?}", rsp),
     }
@@ -286,25 +445,690 @@ fn test_continuation() {
     match parse_response(b"+\r\n") {
         Ok((
             _,
-            Response::Continue {
+            Response::Continue(ContinueRequest {
                 code: None,
                 information: None,
-            },
+            }),
         )) => {}
         rsp => panic!("unexpected response {:?}", rsp),
     }
 }
 
+#[test]
+fn test_continuation_challenge() {
+    match parse_response(b"+ PDE4OTYuNjk3MTcwOTUyQHBvc3RvZmZpY2UuZXhhbXBsZS5uZXQ+\r\n") {
+        Ok((_, Response::Continue(req))) => {
+            assert_eq!(
+                req.challenge().as_deref(),
+                Some(&b"<1896.697170952@postoffice.example.net>"[..])
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"+ \r\n") {
+        Ok((_, Response::Continue(req))) => assert_eq!(req.challenge(), Some(vec![])),
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"+ idling\r\n") {
+        Ok((_, Response::Continue(req))) => assert_eq!(req.challenge(), None),
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
 #[test]
 fn test_enabled() {
     match parse_response(b"* ENABLED QRESYNC X-GOOD-IDEA\r\n") {
         Ok((_, capabilities)) => assert_eq!(
             capabilities,
-            Response::Capabilities(vec![
-                Capability::Atom("QRESYNC"),
-                Capability::Atom("X-GOOD-IDEA"),
+            Response::Enabled(vec![
+                Capability::Qresync,
+                Capability::Atom(Cow::Borrowed("X-GOOD-IDEA")),
             ])
         ),
         rsp => panic!("Unexpected response: {:?}", rsp),
     }
 }
+
+#[cfg(feature = "condstore")]
+#[test]
+fn test_condstore() {
+    match parse_response(b"* 7 FETCH (MODSEQ (12121231000) UID 5)\r\n") {
+        Ok((_, Response::Fetch(Seq(7), attrs))) => {
+            assert_eq!(
+                attrs,
+                vec![
+                    AttributeValue::ModSeq(12121231000),
+                    AttributeValue::Uid(Uid(5))
+                ]
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* OK [HIGHESTMODSEQ 715194045007] Highest\r\n") {
+        Ok((
+            _,
+            Response::Data {
+                status: Status::Ok,
+                code: Some(ResponseCode::HighestModSeq(715194045007)),
+                information: Some(Cow::Borrowed("Highest")),
+            },
+        )) => {}
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(
+        b"* OK [NOMODSEQ] Sorry, this mailbox format doesn't support modsequences\r\n",
+    ) {
+        Ok((
+            _,
+            Response::Data {
+                status: Status::Ok,
+                code: Some(ResponseCode::NoModSeq),
+                ..
+            },
+        )) => {}
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"d105 OK [MODIFIED 7,9] Conditional STORE failed\r\n") {
+        Ok((
+            _,
+            Response::Done {
+                status: Status::Ok,
+                code: Some(ResponseCode::Modified(set)),
+                information: Some(Cow::Borrowed("Conditional STORE failed")),
+                ..
+            },
+        )) => {
+            assert_eq!(set, SequenceSet::from(vec![7..=7, 9..=9]));
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[cfg(feature = "condstore")]
+#[test]
+fn test_vanished() {
+    match parse_response(b"* VANISHED (EARLIER) 300:310,405,411\r\n") {
+        Ok((_, Response::Vanished { earlier, uids })) => {
+            assert!(earlier);
+            assert_eq!(
+                uids,
+                SequenceSet::from(vec![300..=310, 405..=405, 411..=411])
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* VANISHED 405,407,410,425\r\n") {
+        Ok((_, Response::Vanished { earlier, uids })) => {
+            assert!(!earlier);
+            assert_eq!(
+                uids,
+                SequenceSet::from(vec![405..=405, 407..=407, 410..=410, 425..=425])
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_esearch() {
+    match parse_response(b"* ESEARCH (TAG \"A282\") UID MIN 2 MAX 47 COUNT 25 ALL 2:10,47\r\n") {
+        Ok((_, Response::ESearch(esearch))) => {
+            assert_eq!(
+                esearch,
+                ESearch {
+                    tag: Some(Cow::Borrowed("A282")),
+                    mailbox: None,
+                    uid_validity: None,
+                    uid: true,
+                    data: vec![
+                        SearchReturnData::Min(2),
+                        SearchReturnData::Max(47),
+                        SearchReturnData::Count(25),
+                        SearchReturnData::All(SequenceSet::from(vec![2..=10, 47..=47])),
+                    ],
+                }
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(
+        b"* ESEARCH (TAG \"B01\" MAILBOX \"folder1\" UIDVALIDITY 1) UID ALL 3001:3004\r\n",
+    ) {
+        Ok((_, Response::ESearch(esearch))) => {
+            assert_eq!(esearch.tag.as_deref(), Some("B01"));
+            assert_eq!(esearch.mailbox.as_deref(), Some("folder1"));
+            assert_eq!(esearch.uid_validity, Some(1));
+            assert!(esearch.uid);
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* ESEARCH (TAG \"A285\") UID\r\n") {
+        Ok((_, Response::ESearch(esearch))) => {
+            assert_eq!(esearch.tag.as_deref(), Some("A285"));
+            assert!(esearch.uid);
+            assert!(esearch.data.is_empty());
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* ESEARCH COUNT 3\r\n") {
+        Ok((_, Response::ESearch(esearch))) => {
+            assert_eq!(esearch.tag, None);
+            assert!(!esearch.uid);
+            assert_eq!(esearch.data, vec![SearchReturnData::Count(3)]);
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_enabled_empty() {
+    match parse_response(b"* ENABLED\r\n") {
+        Ok((_, Response::Enabled(capabilities))) => assert!(capabilities.is_empty()),
+        rsp => panic!("Unexpected response: {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_list_extended() {
+    match parse_response(b"* LIST () \"/\" \"Foo\" (\"CHILDINFO\" (\"SUBSCRIBED\"))\r\n") {
+        Ok((
+            _,
+            Response::MailboxData(MailboxDatum::List {
+                flags,
+                delimiter,
+                name,
+                extended_data,
+            }),
+        )) => {
+            assert!(flags.is_empty());
+            assert_eq!(delimiter.as_deref(), Some("/"));
+            assert_eq!(name, "Foo");
+            assert_eq!(
+                extended_data,
+                vec![ListExtendedItem {
+                    tag: Cow::Borrowed("CHILDINFO"),
+                    value: TaggedExtVal::Comp(vec![TaggedExtComp::Astring(Cow::Borrowed(
+                        "SUBSCRIBED"
+                    ))]),
+                }]
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* LIST (\\HasNoChildren) \".\" INBOX.Tests\r\n") {
+        Ok((_, Response::MailboxData(MailboxDatum::List { extended_data, .. }))) => {
+            assert!(extended_data.is_empty());
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    let nested = format!(
+        "* LIST () \"/\" INBOX (\"X\" ({}a{}))\r\n",
+        "(".repeat(5000),
+        ")".repeat(5000)
+    );
+    assert!(parse_response(nested.as_bytes()).is_err());
+}
+
+#[test]
+fn test_list_utf7_name() {
+    match parse_response(b"* LSUB () \"/\" \"&APw-bermut\"\r\n") {
+        Ok((_, Response::MailboxData(datum))) => {
+            assert_eq!(datum.decoded_name().as_deref(), Some("übermut"));
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_utf8_accept() {
+    let opts = Options {
+        utf8_accept: true,
+        ..Options::default()
+    };
+
+    let rsp = b"* OK [ALERT] Mailbox f\xc3\xbcr Sie\r\n";
+    assert!(parse_response(rsp).is_err());
+    match parse_response_with(rsp, opts) {
+        Ok((_, Response::Data { information, .. })) => {
+            assert_eq!(information.as_deref(), Some("Mailbox für Sie"));
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    let rsp = b"* LIST () \"/\" \xc3\x9cbermut\r\n";
+    assert!(parse_response(rsp).is_err());
+    match parse_response_with(rsp, opts) {
+        Ok((_, Response::MailboxData(MailboxDatum::List { name, .. }))) => {
+            assert_eq!(name, "Übermut");
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response_with(b"* LIST () \"/\" \"\xc3\x9cbermut\"\r\n", opts) {
+        Ok((_, Response::MailboxData(MailboxDatum::List { name, .. }))) => {
+            assert_eq!(name, "Übermut");
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_imap4rev2() {
+    let opts = Options {
+        imap4rev2: true,
+        ..Options::default()
+    };
+
+    match parse_response_with(b"* CAPABILITY IMAP4rev2 AUTH=PLAIN\r\n", opts) {
+        Ok((_, Response::Capabilities(c))) => {
+            assert_eq!(
+                c,
+                vec![
+                    Capability::Imap4rev2,
+                    Capability::Auth(Cow::Borrowed("PLAIN"))
+                ]
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    let rsp = b"* 1 FETCH (RFC822.TEXT {3}\r\nfoo)\r\n";
+    assert!(parse_response(rsp).is_ok());
+    assert!(parse_response_with(rsp, opts).is_err());
+    assert!(parse_response_with(b"* 1 FETCH (RFC822.SIZE 3)\r\n", opts).is_ok());
+    assert!(parse_response_with(b"* SEARCH 1 2\r\n", opts).is_err());
+    assert!(parse_response_with(b"* 3 RECENT\r\n", opts).is_err());
+    assert!(parse_response_with(b"* ESEARCH (TAG \"A1\") ALL 1:2\r\n", opts).is_ok());
+
+    let rsp = b"* LIST () \"/\" \"NewName\" (\"OLDNAME\" (\"OldName\"))\r\n";
+    match parse_response_with(rsp, opts) {
+        Ok((_, Response::MailboxData(datum))) => {
+            assert_eq!(datum.old_name(), Some("OldName"));
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_rfc822_size_64() {
+    match parse_response(b"* 1 FETCH (RFC822.SIZE 5000000000)\r\n") {
+        Ok((_, Response::Fetch(_, attrs))) => {
+            assert_eq!(attrs, vec![AttributeValue::Rfc822Size(5_000_000_000)]);
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_internal_date() {
+    match parse_response(b"* 1 FETCH (INTERNALDATE \" 7-Feb-1994 21:52:25 -0800\")\r\n") {
+        Ok((_, Response::Fetch(_, attrs))) => {
+            assert_eq!(
+                attrs,
+                vec![AttributeValue::InternalDate(
+                    DateTime::parse(" 7-Feb-1994 21:52:25 -0800").unwrap()
+                )]
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    assert!(parse_response(b"* 1 FETCH (INTERNALDATE NIL)\r\n").is_err());
+    assert!(parse_response(b"* 1 FETCH (INTERNALDATE \"yesterday\")\r\n").is_err());
+}
+
+#[test]
+fn test_lenient() {
+    let opts = Options {
+        lenient: true,
+        ..Options::default()
+    };
+
+    let rsp = b"* OK[ALERT]  System shutdown\r\n";
+    assert!(parse_response(rsp).is_err());
+    match parse_response_with(rsp, opts) {
+        Ok((
+            _,
+            Response::Data {
+                code, information, ..
+            },
+        )) => {
+            assert_eq!(code, Some(ResponseCode::Alert));
+            assert_eq!(information.as_deref(), Some("System shutdown"));
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    let rsp = b"*  1 FETCH (UID 4  FLAGS (\\Seen))  \r\n";
+    assert!(parse_response(rsp).is_err());
+    match parse_response_with(rsp, opts) {
+        Ok((_, Response::Fetch(Seq(1), attrs))) => {
+            assert_eq!(
+                attrs,
+                vec![
+                    AttributeValue::Uid(Uid(4)),
+                    AttributeValue::Flags(vec![Flag::Seen])
+                ]
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    let rsp = b"A1 OK\r\n";
+    assert!(parse_response(rsp).is_err());
+    match parse_response_with(rsp, opts) {
+        Ok((_, Response::Done { information, .. })) => assert_eq!(information, None),
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    let rsp = b"* NO Zugriff verweigert f\xfcr INBOX\r\n";
+    assert!(parse_response(rsp).is_err());
+    match parse_response_with(rsp, opts) {
+        Ok((_, Response::Data { information, .. })) => {
+            assert_eq!(
+                information.as_deref(),
+                Some("Zugriff verweigert f\u{fffd}r INBOX")
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_invalid_utf8_strings() {
+    let rsp = b"* 1 FETCH (BODYSTRUCTURE (\"APPLICATION\" \"PDF\" (\"NAME\" \"M\xe4rz.pdf\") \
+        NIL NIL \"BASE64\" 30 NIL NIL NIL))\r\n";
+    match parse_response(rsp) {
+        Ok((_, Response::Fetch(_, attrs))) => match &attrs[0] {
+            AttributeValue::BodyStructure(body) => {
+                assert_eq!(body.filename().as_deref(), Some("M\u{fffd}rz.pdf"));
+                assert_eq!(
+                    body.content_type().param_raw("name"),
+                    Some(&b"M\xe4rz.pdf"[..])
+                );
+            }
+            attr => panic!("unexpected attribute {:?}", attr),
+        },
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* LIST () \"/\" \"J\xf6rg\"\r\n") {
+        Ok((_, Response::MailboxData(MailboxDatum::List { name, .. }))) => {
+            assert_eq!(name, "J\u{fffd}rg");
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_literal_mailbox_names() {
+    match parse_response(b"* LIST (\\HasNoChildren) \"/\" {11}\r\nFoo \"Bar\"/1\r\n") {
+        Ok((_, Response::MailboxData(MailboxDatum::List { name, .. }))) => {
+            assert_eq!(name, "Foo \"Bar\"/1");
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* LSUB () NIL {5}\r\ninbox\r\n") {
+        Ok((_, Response::MailboxData(MailboxDatum::List { name, .. }))) => {
+            assert_eq!(name, "INBOX");
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* STATUS {7}\r\nA (B) C (MESSAGES 2)\r\n") {
+        Ok((_, Response::MailboxData(MailboxDatum::Status { mailbox, status }))) => {
+            assert_eq!(mailbox, "A (B) C");
+            assert_eq!(status, vec![StatusAttribute::Messages(2)]);
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(b"* LIST () \"/\" {6}\r\n") {
+        Err(nom::Err::Incomplete(_)) => {}
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_incremental() {
+    let rsp = b"* 4 FETCH (UID 7 BODY[] {10}\r\nabcd";
+    match parse_response_incremental(rsp) {
+        Parsed::Incomplete(nom::Needed::Size(6)) => {}
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response_incremental(b"* 4 FETCH (UID 7 BODY[] {10}\r\nabcdefghij") {
+        Parsed::Incomplete(nom::Needed::Unknown) => {}
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response_incremental(b"* 4 FETCH (UID 7 BODY[] {10}\r\nabcdefghij)\r\nA1") {
+        Parsed::Complete(b"A1", Response::Fetch(Seq(4), _)) => {}
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response_incremental(b"* 4 FETCH (UID 7 BODY[] {10}\r\nabc\0efghij)\r\n") {
+        Parsed::Error(..) => {}
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    let opts = Options {
+        lenient: true,
+        ..Options::default()
+    };
+    let rsp = b"* 4 FETCH (UID 7  FLAGS (\\Seen))  \r\n";
+    assert!(matches!(parse_response_incremental(rsp), Parsed::Error(..)));
+    match parse_response_incremental_with(rsp, opts) {
+        Parsed::Complete(b"", Response::Fetch(Seq(4), _)) => {}
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_parse_error() {
+    let rsp = b"* 12 FETCH (UID 7 BODY[HEADER.FIELDS BAD] NIL)\r\n";
+    match parse_response(rsp) {
+        Err(nom::Err::Error(err)) => {
+            assert_eq!(err.offset(), 29);
+            assert_eq!(err.snippet(), b".FIELDS BAD] NIL)\r\n");
+            assert_eq!(
+                err.context(),
+                &[
+                    "response",
+                    "response-data",
+                    "message-data",
+                    "msg-att",
+                    "body-section",
+                    "section"
+                ]
+            );
+            assert_eq!(
+                err.to_string(),
+                "Char error at byte 29 in response > response-data > message-data > \
+                 msg-att > body-section > section: \".FIELDS BAD] NIL)\\r\\n\""
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_complete() {
+    let log = b"* 2 EXISTS\r\n* 1 FETCH (UID 4)\r\nA1 OK done\r\n";
+    match parse_responses_complete(log) {
+        Ok(responses) => {
+            assert_eq!(responses.len(), 3);
+            assert_eq!(
+                responses[1],
+                Response::Fetch(Seq(1), vec![AttributeValue::Uid(Uid(4))])
+            );
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    let truncated = b"* 1 FETCH (UID 4 BODY[] {10}\r\nabc";
+    assert!(matches!(
+        parse_response(truncated),
+        Err(nom::Err::Incomplete(_))
+    ));
+    match parse_response_complete(truncated) {
+        Err(err) => {
+            assert_eq!(err.offset(), truncated.len());
+            assert_eq!(err.kind(), nom::error::ErrorKind::Eof);
+            assert_eq!(err.context().last(), Some(&"body-section"));
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    assert!(parse_responses_complete(&log[..log.len() - 1]).is_err());
+}
+
+#[test]
+fn test_mailbox_update() {
+    let update = |rsp: &'static [u8]| match parse_response(rsp) {
+        Ok((_, rsp)) => rsp.mailbox_update(),
+        rsp => panic!("unexpected response {:?}", rsp),
+    };
+
+    assert_eq!(update(b"* 23 EXISTS\r\n"), Some(MailboxUpdate::Exists(23)));
+    assert_eq!(update(b"* 1 RECENT\r\n"), Some(MailboxUpdate::Recent(1)));
+    assert_eq!(
+        update(b"* 3 EXPUNGE\r\n"),
+        Some(MailboxUpdate::Expunge(Seq(3)))
+    );
+    assert_eq!(
+        update(b"* 4 FETCH (FLAGS (\\Seen) UID 9)\r\n"),
+        Some(MailboxUpdate::Flags {
+            seq: Seq(4),
+            uid: Some(Uid(9)),
+            mod_seq: None,
+            flags: vec![Flag::Seen],
+        })
+    );
+    assert_eq!(update(b"* 4 FETCH (UID 9)\r\n"), None);
+    assert_eq!(update(b"* 4 FETCH (FLAGS () RFC822.SIZE 44)\r\n"), None);
+    assert_eq!(
+        update(b"* 9 UIDFETCH (FLAGS (\\Seen))\r\n"),
+        Some(MailboxUpdate::UidFlags {
+            uid: Uid(9),
+            mod_seq: None,
+            flags: vec![Flag::Seen],
+        })
+    );
+    assert_eq!(update(b"* OK done\r\n"), None);
+}
+
+#[test]
+fn test_into_owned() {
+    let owned = {
+        let buf = b"* 1 FETCH (FLAGS (\\Seen $Junk) ENVELOPE (NIL \"hi\" NIL NIL NIL NIL NIL NIL NIL NIL) BODY[TEXT] {3}\r\nabc)\r\n".to_vec();
+        let (_, rsp) = parse_response(&buf).unwrap();
+        rsp.into_owned()
+    };
+    match owned {
+        Response::Fetch(Seq(1), attrs) => {
+            assert_eq!(
+                attrs[0],
+                AttributeValue::Flags(vec![Flag::Seen, Flag::Keyword("$Junk".into())])
+            );
+            match &attrs[1] {
+                AttributeValue::Envelope(env) => {
+                    assert_eq!(env.subject, Some(Cow::Owned(b"hi".to_vec())));
+                }
+                attr => panic!("unexpected attribute {:?}", attr),
+            }
+            match &attrs[2] {
+                AttributeValue::BodySection { data, .. } => {
+                    assert!(matches!(data, Some(Cow::Owned(_))));
+                    assert_eq!(data.as_deref(), Some(&b"abc"[..]));
+                }
+                attr => panic!("unexpected attribute {:?}", attr),
+            }
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_constructed_owned_response() {
+    let keyword = String::from("$Forwarded");
+    let expected = Response::Fetch(
+        Seq(7),
+        vec![
+            AttributeValue::Flags(vec![
+                Flag::system(String::from("\\Answered")),
+                Flag::system(String::from("\\X-Custom")),
+                Flag::Keyword(keyword.into()),
+            ]),
+            AttributeValue::Rfc822(Some(b"abc".to_vec().into())),
+        ],
+    );
+    match parse_response(
+        b"* 7 FETCH (FLAGS (\\Answered \\X-Custom $Forwarded) RFC822 {3}\r\nabc)\r\n",
+    ) {
+        Ok((_, rsp)) => assert_eq!(rsp, expected),
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[test]
+fn test_quoted_escapes() {
+    match parse_response(b"* LIST () \"/\" \"Foo \\\"Bar\\\"/1\"\r\n") {
+        Ok((_, Response::MailboxData(MailboxDatum::List { name, .. }))) => {
+            assert_eq!(name, "Foo \"Bar\"/1");
+        }
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+
+    match parse_response(
+        b"* 1 FETCH (ENVELOPE (NIL \"a \\\"quoted\\\" word\" NIL NIL NIL NIL NIL NIL NIL NIL))\r\n",
+    ) {
+        Ok((_, Response::Fetch(_, attrs))) => match &attrs[0] {
+            AttributeValue::Envelope(envelope) => {
+                assert_eq!(envelope.subject.as_deref(), Some(&b"a \"quoted\" word"[..]));
+            }
+            attr => panic!("unexpected attribute {:?}", attr),
+        },
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    const RESPONSE: &[u8] = b"* 15 FETCH (UID 42 FLAGS (\\Seen $Forwarded) \
+        ENVELOPE (\"Wed, 17 Jul 1996 02:23:25 -0700\" \"IMAP4rev1 WG mtg\" \
+        ((\"Terry Gray\" NIL \"gray\" \"cac.washington.edu\")) NIL NIL NIL NIL NIL NIL \
+        \"<B27397-0100000@cac.washington.edu>\") \
+        BODYSTRUCTURE (\"TEXT\" \"PLAIN\" (\"CHARSET\" \"US-ASCII\") NIL NIL \"7BIT\" 3028 92))\r\n";
+    let (_, rsp) = parse_response(RESPONSE).unwrap();
+    let json = serde_json::to_string(&rsp).unwrap();
+    let parsed: Response<'_> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, rsp);
+
+    // Plain `Cow` fields borrow from the serialized data.
+    match parsed {
+        Response::Fetch(_, attrs) => match &attrs[1] {
+            AttributeValue::Flags(flags) => {
+                assert!(matches!(
+                    flags[1],
+                    Flag::Keyword(Cow::Borrowed("$Forwarded"))
+                ))
+            }
+            attr => panic!("unexpected attribute {:?}", attr),
+        },
+        rsp => panic!("unexpected response {:?}", rsp),
+    }
+}
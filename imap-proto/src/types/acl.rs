@@ -0,0 +1,202 @@
+// ACL rights: mapping them to their letters and collecting them in sets, as
+// used by the SETACL command.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, BitOrAssign, Sub};
+
+use super::{AclRight, AclRights};
+
+impl AclRight {
+    pub fn from_char(c: char) -> Self {
+        match c {
+            'l' => AclRight::Lookup,
+            'r' => AclRight::Read,
+            's' => AclRight::Seen,
+            'w' => AclRight::Write,
+            'i' => AclRight::Insert,
+            'p' => AclRight::Post,
+            'k' => AclRight::CreateMailbox,
+            'x' => AclRight::DeleteMailbox,
+            't' => AclRight::DeleteMessage,
+            'e' => AclRight::Expunge,
+            'a' => AclRight::Administer,
+            'c' => AclRight::OldCreate,
+            'd' => AclRight::OldDelete,
+            _ => AclRight::Custom(c),
+        }
+    }
+
+    /// The letter of the right, as it is written on the wire.
+    pub fn as_char(self) -> char {
+        match self {
+            AclRight::Lookup => 'l',
+            AclRight::Read => 'r',
+            AclRight::Seen => 's',
+            AclRight::Write => 'w',
+            AclRight::Insert => 'i',
+            AclRight::Post => 'p',
+            AclRight::CreateMailbox => 'k',
+            AclRight::DeleteMailbox => 'x',
+            AclRight::DeleteMessage => 't',
+            AclRight::Expunge => 'e',
+            AclRight::Administer => 'a',
+            AclRight::OldCreate => 'c',
+            AclRight::OldDelete => 'd',
+            AclRight::Custom(c) => c,
+        }
+    }
+}
+
+impl AclRights {
+    pub const LOOKUP: AclRights = AclRights::bit('l');
+    pub const READ: AclRights = AclRights::bit('r');
+    pub const SEEN: AclRights = AclRights::bit('s');
+    pub const WRITE: AclRights = AclRights::bit('w');
+    pub const INSERT: AclRights = AclRights::bit('i');
+    pub const POST: AclRights = AclRights::bit('p');
+    pub const CREATE_MAILBOX: AclRights = AclRights::bit('k');
+    pub const DELETE_MAILBOX: AclRights = AclRights::bit('x');
+    pub const DELETE_MESSAGE: AclRights = AclRights::bit('t');
+    pub const EXPUNGE: AclRights = AclRights::bit('e');
+    pub const ADMINISTER: AclRights = AclRights::bit('a');
+
+    // Rights are lowercase letters and digits, each with its own bit.
+    const fn bit(c: char) -> AclRights {
+        match c {
+            'a'..='z' => AclRights(1 << (c as u32 - 'a' as u32)),
+            '0'..='9' => AclRights(1 << (26 + c as u32 - '0' as u32)),
+            _ => AclRights(0),
+        }
+    }
+
+    pub const fn empty() -> Self {
+        AclRights(0)
+    }
+
+    /// Parses rights as they are written on the wire, such as `lrswi`.
+    /// Returns `None` if there is anything but lowercase letters and
+    /// digits.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut rights = AclRights::empty();
+        for c in s.chars() {
+            let right = AclRight::from_char(c);
+            if !rights.try_insert(right) {
+                return None;
+            }
+        }
+        Some(rights)
+    }
+
+    pub fn contains(self, other: AclRights) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The rights in the set, in alphabetical order followed by digits.
+    pub fn iter(self) -> impl Iterator<Item = AclRight> {
+        ('a'..='z')
+            .chain('0'..='9')
+            .filter(move |&c| self.contains(AclRights::bit(c)))
+            .map(AclRight::from_char)
+    }
+
+    // Adds a right, returning false if it can't be represented.
+    fn try_insert(&mut self, right: AclRight) -> bool {
+        let bit = AclRights::bit(right.as_char());
+        self.0 |= bit.0;
+        !bit.is_empty()
+    }
+}
+
+impl From<AclRight> for AclRights {
+    fn from(right: AclRight) -> Self {
+        AclRights::bit(right.as_char())
+    }
+}
+
+/// Collects rights into a set, leaving out custom rights that aren't a
+/// lowercase letter or a digit.
+impl FromIterator<AclRight> for AclRights {
+    fn from_iter<I: IntoIterator<Item = AclRight>>(iter: I) -> Self {
+        let mut rights = AclRights::empty();
+        for right in iter {
+            rights.try_insert(right);
+        }
+        rights
+    }
+}
+
+impl BitOr for AclRights {
+    type Output = AclRights;
+
+    fn bitor(self, other: AclRights) -> AclRights {
+        AclRights(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for AclRights {
+    fn bitor_assign(&mut self, other: AclRights) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for AclRights {
+    type Output = AclRights;
+
+    fn bitand(self, other: AclRights) -> AclRights {
+        AclRights(self.0 & other.0)
+    }
+}
+
+impl Sub for AclRights {
+    type Output = AclRights;
+
+    fn sub(self, other: AclRights) -> AclRights {
+        AclRights(self.0 & !other.0)
+    }
+}
+
+/// Formats the rights as they are written on the wire, such as `ilrsw`.
+impl fmt::Display for AclRights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for right in self.iter() {
+            write!(f, "{}", right.as_char())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::*;
+
+    #[test]
+    fn test_acl_rights() {
+        let rights = AclRights::parse("lrswi0").unwrap();
+        assert!(rights.contains(AclRights::LOOKUP | AclRights::INSERT));
+        assert!(!rights.contains(AclRights::ADMINISTER));
+        assert_eq!(rights.to_string(), "ilrsw0");
+        assert_eq!(
+            rights.iter().collect::<Vec<_>>(),
+            vec![
+                AclRight::Insert,
+                AclRight::Lookup,
+                AclRight::Read,
+                AclRight::Seen,
+                AclRight::Write,
+                AclRight::Custom('0'),
+            ]
+        );
+        assert_eq!((rights - AclRights::SEEN).to_string(), "ilrw0");
+        assert_eq!(AclRights::parse("lR"), None);
+        assert!(AclRights::parse("").unwrap().is_empty());
+
+        let rights = vec![AclRight::Read, AclRight::Custom('X'), AclRight::OldCreate];
+        let rights: AclRights = rights.into_iter().collect();
+        assert_eq!(rights, AclRights::READ | AclRight::OldCreate.into());
+    }
+}
@@ -0,0 +1,223 @@
+// Rendering of envelope addresses for display and in RFC 5322 syntax.
+
+use std::fmt;
+
+use std::borrow::Cow;
+
+use super::envelope::lossy;
+use super::Address;
+
+impl Address<'_> {
+    /// The display name, with invalid UTF-8 replaced and encoded-words
+    /// left as they are.
+    pub fn name_lossy(&self) -> Option<Cow<'_, str>> {
+        lossy(&self.name)
+    }
+
+    /// The source route, with invalid UTF-8 replaced.
+    pub fn adl_lossy(&self) -> Option<Cow<'_, str>> {
+        lossy(&self.adl)
+    }
+
+    /// The local part of the address, with invalid UTF-8 replaced.
+    pub fn mailbox_lossy(&self) -> Option<Cow<'_, str>> {
+        lossy(&self.mailbox)
+    }
+
+    /// The domain of the address, with invalid UTF-8 replaced.
+    pub fn host_lossy(&self) -> Option<Cow<'_, str>> {
+        lossy(&self.host)
+    }
+
+    /// The address in `mailbox@host` form, or `None` for group markers.
+    pub fn email(&self) -> Option<String> {
+        let (mailbox, host) = (self.mailbox.as_deref()?, self.host.as_deref()?);
+        Some(format!(
+            "{}@{}",
+            String::from_utf8_lossy(mailbox),
+            String::from_utf8_lossy(host)
+        ))
+    }
+
+    /// The display name, or the group name for the start of a group. With
+    /// the `mime` feature, RFC 2047 encoded-words are decoded.
+    pub fn display_name(&self) -> Option<String> {
+        let name = match self.group_name() {
+            Some(name) => name,
+            None => self.name.as_deref()?,
+        };
+        Some(decode_name(name))
+    }
+
+    /// Renders the address as it would appear in a header, such as
+    /// `Terry Gray <gray@cac.washington.edu>`. The name is kept in its
+    /// encoded form and quoted if necessary. The start of a group renders
+    /// as `name:` and the end of a group as `;`.
+    pub fn to_rfc5322_string(&self) -> String {
+        if self.is_group_end() {
+            return ";".to_string();
+        }
+        if let Some(name) = self.group_name() {
+            return format!("{}:", phrase(&String::from_utf8_lossy(name)));
+        }
+        let email = self.email().unwrap_or_default();
+        match self.name.as_deref() {
+            Some(name) if !name.is_empty() => {
+                format!("{} <{}>", phrase(&String::from_utf8_lossy(name)), email)
+            }
+            _ => email,
+        }
+    }
+
+    /// Renders a list of addresses, as found in an envelope field, for use
+    /// in a header. Groups are rendered as `name: a@b, c@d;`.
+    pub fn list_to_rfc5322_string(addrs: &[Address<'_>]) -> String {
+        let mut out = String::new();
+        let mut prev: Option<&Address<'_>> = None;
+        for addr in addrs {
+            if !addr.is_group_end() {
+                match prev {
+                    Some(prev) if prev.is_group_start() => out.push(' '),
+                    Some(_) => out.push_str(", "),
+                    None => {}
+                }
+            }
+            out.push_str(&addr.to_rfc5322_string());
+            prev = Some(addr);
+        }
+        out
+    }
+}
+
+/// Formats the address for display, with the name as returned by
+/// `display_name`, such as `Jörg Meier <joerg@example.com>`.
+impl fmt::Display for Address<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_group_end() {
+            return write!(f, ";");
+        }
+        if self.is_group_start() {
+            return write!(f, "{}:", self.display_name().unwrap_or_default());
+        }
+        let email = self.email().unwrap_or_default();
+        match self.display_name() {
+            Some(name) if !name.is_empty() => write!(f, "{} <{}>", name, email),
+            _ => write!(f, "{}", email),
+        }
+    }
+}
+
+#[cfg(feature = "mime")]
+fn decode_name(name: &[u8]) -> String {
+    crate::mime::decode_encoded_words(name)
+}
+
+#[cfg(not(feature = "mime"))]
+fn decode_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).into_owned()
+}
+
+// phrase = 1*word, where word = atom / quoted-string. Names that are not a
+// sequence of atoms are sent as a quoted-string.
+fn phrase(name: &str) -> String {
+    let is_atext = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c);
+    if name
+        .split(' ')
+        .all(|word| !word.is_empty() && word.chars().all(is_atext))
+    {
+        return name.to_string();
+    }
+    let mut quoted = String::with_capacity(name.len() + 2);
+    quoted.push('"');
+    for c in name.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::types::Address;
+
+    fn addr<'a>(
+        name: Option<&'a str>,
+        mailbox: Option<&'a str>,
+        host: Option<&'a str>,
+    ) -> Address<'a> {
+        let bytes = |s: Option<&'a str>| s.map(|s| Cow::Borrowed(s.as_bytes()));
+        Address {
+            name: bytes(name),
+            adl: None,
+            mailbox: bytes(mailbox),
+            host: bytes(host),
+        }
+    }
+
+    #[test]
+    fn test_address() {
+        let a = addr(Some("Terry Gray"), Some("gray"), Some("cac.washington.edu"));
+        assert_eq!(a.email().as_deref(), Some("gray@cac.washington.edu"));
+        assert_eq!(a.display_name().as_deref(), Some("Terry Gray"));
+        assert_eq!(
+            a.to_rfc5322_string(),
+            "Terry Gray <gray@cac.washington.edu>"
+        );
+        assert_eq!(a.to_string(), "Terry Gray <gray@cac.washington.edu>");
+
+        let a = addr(None, Some("imap"), Some("cac.washington.edu"));
+        assert_eq!(a.display_name(), None);
+        assert_eq!(a.to_rfc5322_string(), "imap@cac.washington.edu");
+        assert_eq!(a.to_string(), "imap@cac.washington.edu");
+    }
+
+    #[test]
+    #[cfg(feature = "mime")]
+    fn test_address_names() {
+        let a = addr(
+            Some("=?UTF-8?B?SsO2cmc=?= Meier"),
+            Some("j"),
+            Some("example.com"),
+        );
+        assert_eq!(a.display_name().as_deref(), Some("Jörg Meier"));
+        assert_eq!(a.to_string(), "Jörg Meier <j@example.com>");
+        assert_eq!(
+            a.to_rfc5322_string(),
+            "=?UTF-8?B?SsO2cmc=?= Meier <j@example.com>"
+        );
+
+        let a = addr(
+            Some("Gray, Terry \"TG\""),
+            Some("gray"),
+            Some("example.com"),
+        );
+        assert_eq!(
+            a.to_rfc5322_string(),
+            "\"Gray, Terry \\\"TG\\\"\" <gray@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_address_groups() {
+        let addrs = vec![
+            addr(None, Some("a"), Some("example.com")),
+            addr(None, Some("Friends"), None),
+            addr(Some("B"), Some("b"), Some("example.com")),
+            addr(None, Some("c"), Some("example.com")),
+            addr(None, None, None),
+            addr(None, Some("Empty"), None),
+            addr(None, None, None),
+        ];
+        assert_eq!(addrs[1].display_name().as_deref(), Some("Friends"));
+        assert_eq!(addrs[1].email(), None);
+        assert_eq!(
+            Address::list_to_rfc5322_string(&addrs),
+            "a@example.com, Friends: B <b@example.com>, c@example.com;, Empty:;"
+        );
+    }
+}
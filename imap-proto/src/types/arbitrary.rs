@@ -0,0 +1,265 @@
+// proptest strategies for the protocol types, for property-based testing of
+// this crate and of code built on it.
+//
+// The values are the ones that can be sent on the wire: flags are atoms,
+// sequence numbers are non-zero, and responses are limited to the kinds
+// that can be written out and parsed back without loss.
+
+use std::borrow::Cow;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use super::{
+    AttributeValue, Flag, MailboxDatum, RequestId, Response, Seq, SeqNumber, SeqRange, SequenceSet,
+    Status, StoreMode, Uid,
+};
+use crate::builders::command::{Command, CommandBuilder};
+
+impl Arbitrary for Flag<'static> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Any flag but `\*`, which only appears in PERMANENTFLAGS.
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            Just(Flag::Seen),
+            Just(Flag::Answered),
+            Just(Flag::Flagged),
+            Just(Flag::Deleted),
+            Just(Flag::Draft),
+            Just(Flag::Recent),
+            "[A-Za-z$][A-Za-z0-9$_.-]{0,11}".prop_map(|s| Flag::Keyword(Cow::Owned(s))),
+            "[A-Za-z][A-Za-z0-9]{0,11}".prop_map(|s| Flag::system(format!("\\{}", s))),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for SeqNumber {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            4 => (1..=u32::MAX).prop_map(SeqNumber::Value),
+            1 => Just(SeqNumber::Largest),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for SeqRange {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (any::<SeqNumber>(), any::<SeqNumber>())
+            .prop_map(|(start, end)| SeqRange::new(start, end))
+            .boxed()
+    }
+}
+
+impl Arbitrary for SequenceSet {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// `$` or a set of at least one range, since an empty set can't be sent.
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            8 => vec(any::<SeqRange>(), 1..8).prop_map(SequenceSet::Set),
+            1 => Just(SequenceSet::LastResult),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Response<'static> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Status responses, EXPUNGE, EXISTS, RECENT, FLAGS, SEARCH, and FETCH
+    /// responses with UID, FLAGS and RFC822.SIZE.
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let status = prop_oneof![Just(Status::Ok), Just(Status::No), Just(Status::Bad)];
+        let text = "[A-Za-z][A-Za-z0-9 .,!?-]{0,30}".prop_map(|s| Some(Cow::Owned(s)));
+        let attribute = prop_oneof![
+            (1..=u32::MAX).prop_map(|uid| AttributeValue::Uid(Uid(uid))),
+            vec(any::<Flag<'static>>(), 0..4).prop_map(AttributeValue::Flags),
+            any::<u64>().prop_map(AttributeValue::Rfc822Size),
+        ];
+        prop_oneof![
+            ("[A-Za-z0-9]{1,8}", status.clone(), text.clone()).prop_map(
+                |(tag, status, information)| Response::Done {
+                    tag: RequestId(tag),
+                    status,
+                    code: None,
+                    information,
+                }
+            ),
+            (prop_oneof![status, Just(Status::Bye)], text).prop_map(|(status, information)| {
+                Response::Data {
+                    status,
+                    code: None,
+                    information,
+                }
+            }),
+            (1..=u32::MAX).prop_map(|n| Response::Expunge(Seq(n))),
+            any::<u32>().prop_map(|n| Response::MailboxData(MailboxDatum::Exists(n))),
+            any::<u32>().prop_map(|n| Response::MailboxData(MailboxDatum::Recent(n))),
+            vec(any::<Flag<'static>>(), 0..8)
+                .prop_map(|flags| Response::MailboxData(MailboxDatum::Flags(flags))),
+            vec(1..=u32::MAX, 0..8).prop_map(Response::IDs),
+            (1..=u32::MAX, vec(attribute, 1..4))
+                .prop_map(|(seq, attrs)| Response::Fetch(Seq(seq), attrs)),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for StoreMode {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            Just(StoreMode::Replace),
+            Just(StoreMode::Add),
+            Just(StoreMode::Remove),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Command {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Commands built with `CommandBuilder` from arbitrary mailbox names,
+    /// credentials, sequence sets and flags.
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            any::<String>().prop_map(CommandBuilder::select),
+            any::<String>().prop_map(CommandBuilder::examine),
+            any::<String>().prop_map(CommandBuilder::create),
+            any::<String>().prop_map(CommandBuilder::delete),
+            (any::<String>(), any::<String>())
+                .prop_map(|(user, password)| CommandBuilder::login(&user, &password)),
+            (any::<SequenceSet>(), any::<String>())
+                .prop_map(|(set, mailbox)| CommandBuilder::copy(&set, mailbox)),
+            (
+                any::<SequenceSet>(),
+                any::<StoreMode>(),
+                vec(any::<Flag<'static>>(), 0..4)
+            )
+                .prop_map(|(set, mode, flags)| CommandBuilder::store(&set, mode, &flags).build()),
+        ]
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::super::request::next_literal;
+    use crate::builders::command::Command;
+    use crate::parser::core::sequence_set;
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    // Writes the responses generated above as a server would send them.
+    fn encode(response: &Response<'_>) -> Vec<u8> {
+        fn status(status: &Status) -> &'static str {
+            match status {
+                Status::Ok => "OK",
+                Status::No => "NO",
+                Status::Bad => "BAD",
+                Status::PreAuth => "PREAUTH",
+                Status::Bye => "BYE",
+            }
+        }
+        fn flags(flags: &[Flag<'_>]) -> String {
+            let flags = flags.iter().map(Flag::as_str).collect::<Vec<_>>();
+            format!("({})", flags.join(" "))
+        }
+
+        let line = match response {
+            Response::Done {
+                tag,
+                status: s,
+                information,
+                ..
+            } => format!(
+                "{} {} {}",
+                tag.0,
+                status(s),
+                information.as_deref().unwrap()
+            ),
+            Response::Data {
+                status: s,
+                information,
+                ..
+            } => format!("* {} {}", status(s), information.as_deref().unwrap()),
+            Response::Expunge(seq) => format!("* {} EXPUNGE", seq),
+            Response::MailboxData(MailboxDatum::Exists(n)) => format!("* {} EXISTS", n),
+            Response::MailboxData(MailboxDatum::Recent(n)) => format!("* {} RECENT", n),
+            Response::MailboxData(MailboxDatum::Flags(f)) => format!("* FLAGS {}", flags(f)),
+            Response::IDs(ids) => ids.iter().fold("* SEARCH".to_string(), |line, id| {
+                format!("{} {}", line, id)
+            }),
+            Response::Fetch(seq, attrs) => {
+                let attrs = attrs
+                    .iter()
+                    .map(|attr| match attr {
+                        AttributeValue::Uid(uid) => format!("UID {}", uid),
+                        AttributeValue::Flags(f) => format!("FLAGS {}", flags(f)),
+                        AttributeValue::Rfc822Size(size) => format!("RFC822.SIZE {}", size),
+                        _ => unreachable!(),
+                    })
+                    .collect::<Vec<_>>();
+                format!("* {} FETCH ({})", seq, attrs.join(" "))
+            }
+            _ => unreachable!(),
+        };
+        format!("{}\r\n", line).into_bytes()
+    }
+
+    proptest! {
+        #[test]
+        fn sequence_set_round_trip(set: SequenceSet) {
+            // The parser needs to see where the number ends.
+            let s = format!("{} ", set);
+            prop_assert_eq!(sequence_set(s.as_bytes()), Ok((&b" "[..], set)));
+        }
+
+        #[test]
+        fn flag_set_round_trip(flags: Vec<Flag<'static>>) {
+            let set = flags.into_iter().collect::<FlagSet<'_>>();
+            let s = set.to_string();
+            prop_assert_eq!(FlagSet::parse(&s), Some(set));
+        }
+
+        #[test]
+        fn response_round_trip(response: Response<'static>) {
+            let bytes = encode(&response);
+            let (rest, parsed) = parse_response(&bytes).unwrap();
+            prop_assert!(rest.is_empty());
+            prop_assert_eq!(parsed, response);
+        }
+
+        // Outside of literals, the only CRLF is the one that ends the header
+        // of a literal, so commands can't be split or smuggled.
+        #[test]
+        fn command_has_no_crlf_outside_literals(command: Command) {
+            let (args, _) = command.into_parts();
+            let mut rest = &args[..];
+            while let Some((data, len, _)) = next_literal(rest) {
+                let header = &rest[..data - 2];
+                prop_assert!(!header.contains(&b'\r') && !header.contains(&b'\n'));
+                rest = &rest[data + len..];
+            }
+            prop_assert!(!rest.contains(&b'\r') && !rest.contains(&b'\n'));
+        }
+    }
+}
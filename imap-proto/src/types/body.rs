@@ -0,0 +1,295 @@
+// Traversal of body structures, to find the sections worth fetching.
+
+use std::borrow::Cow;
+
+use super::{
+    BodyContentCommon, BodyContentSinglePart, BodyParams, BodyStructure, ContentDisposition,
+    ContentType,
+};
+
+impl<'a> BodyStructure<'a> {
+    pub fn common(&self) -> &BodyContentCommon<'a> {
+        match self {
+            BodyStructure::Basic { common, .. }
+            | BodyStructure::Text { common, .. }
+            | BodyStructure::Message { common, .. }
+            | BodyStructure::Multipart { common, .. } => common,
+        }
+    }
+
+    /// The fields shared by all non-multipart bodies.
+    pub fn single_part(&self) -> Option<&BodyContentSinglePart<'a>> {
+        match self {
+            BodyStructure::Basic { other, .. }
+            | BodyStructure::Text { other, .. }
+            | BodyStructure::Message { other, .. } => Some(other),
+            BodyStructure::Multipart { .. } => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &ContentType<'a> {
+        &self.common().ty
+    }
+
+    pub fn disposition(&self) -> Option<&ContentDisposition<'a>> {
+        self.common().disposition.as_ref()
+    }
+
+    /// The file name from the `filename` disposition parameter, or else the
+    /// `name` content type parameter.
+    pub fn filename(&self) -> Option<Cow<'_, str>> {
+        self.disposition()
+            .and_then(|disposition| disposition.param("filename"))
+            .or_else(|| self.content_type().param("name"))
+    }
+
+    /// Whether this is a single part that would be shown as an attachment:
+    /// one with an `attachment` disposition, or with a file name and no
+    /// `inline` disposition.
+    pub fn is_attachment(&self) -> bool {
+        if let BodyStructure::Multipart { .. } = self {
+            return false;
+        }
+        match self.disposition() {
+            Some(disposition) if disposition.ty.eq_ignore_ascii_case("attachment") => true,
+            Some(disposition) if disposition.ty.eq_ignore_ascii_case("inline") => false,
+            _ => self.filename().is_some(),
+        }
+    }
+
+    /// Iterates over all parts in depth-first order, together with their
+    /// part numbers as used in `BODY[1.2]`. Parts of attached messages are
+    /// included.
+    ///
+    /// Following RFC 3501, section 6.4.5, a message that is not multipart
+    /// has one part, numbered 1, and a multipart message has no part number
+    /// of its own. The body of an attached message is numbered like a
+    /// message, relative to the attached message's part number.
+    pub fn parts(&self) -> impl Iterator<Item = (Vec<u32>, &BodyStructure<'a>)> {
+        let mut parts = vec![];
+        self.collect(&[], true, true, &mut parts);
+        parts.into_iter()
+    }
+
+    /// The parts that are attachments, not including parts of attached
+    /// messages.
+    pub fn attachments(&self) -> impl Iterator<Item = (Vec<u32>, &BodyStructure<'a>)> {
+        let mut parts = vec![];
+        self.collect(&[], true, false, &mut parts);
+        parts.into_iter().filter(|(_, part)| part.is_attachment())
+    }
+
+    /// The first `text/plain` part that is not an attachment, not including
+    /// parts of attached messages.
+    pub fn text_body(&self) -> Option<(Vec<u32>, &BodyStructure<'a>)> {
+        self.find_body("plain")
+    }
+
+    /// The first `text/html` part that is not an attachment, not including
+    /// parts of attached messages.
+    pub fn html_body(&self) -> Option<(Vec<u32>, &BodyStructure<'a>)> {
+        self.find_body("html")
+    }
+
+    fn find_body(&self, subtype: &str) -> Option<(Vec<u32>, &BodyStructure<'a>)> {
+        let mut parts = vec![];
+        self.collect(&[], true, false, &mut parts);
+        parts
+            .into_iter()
+            .find(|(_, part)| !part.is_attachment() && part.content_type().is("text", subtype))
+    }
+
+    // `root` is set for the body of the message or of an attached message,
+    // which is numbered relative to `path` unless it is multipart.
+    fn collect<'s>(
+        &'s self,
+        path: &[u32],
+        root: bool,
+        nested: bool,
+        out: &mut Vec<(Vec<u32>, &'s BodyStructure<'a>)>,
+    ) {
+        if let BodyStructure::Multipart { bodies, .. } = self {
+            out.push((path.to_vec(), self));
+            for (i, body) in bodies.iter().enumerate() {
+                let mut child = path.to_vec();
+                child.push(i as u32 + 1);
+                body.collect(&child, false, nested, out);
+            }
+            return;
+        }
+
+        let mut path = path.to_vec();
+        if root {
+            path.push(1);
+        }
+        out.push((path.clone(), self));
+        if let (BodyStructure::Message { body, .. }, true) = (self, nested) {
+            body.collect(&path, true, nested, out);
+        }
+    }
+}
+
+impl ContentType<'_> {
+    /// Whether this is the given type and subtype, ignoring case.
+    pub fn is(&self, ty: &str, subtype: &str) -> bool {
+        self.ty.eq_ignore_ascii_case(ty) && self.subtype.eq_ignore_ascii_case(subtype)
+    }
+
+    /// The type and subtype in lower case, such as `text/plain`.
+    pub fn mime_type(&self) -> String {
+        format!("{}/{}", self.ty, self.subtype).to_ascii_lowercase()
+    }
+
+    /// The value of a parameter such as `charset`, ignoring case in the name.
+    /// With the `mime` feature, RFC 2231 continuations and charsets are
+    /// decoded. Invalid UTF-8 is replaced.
+    pub fn param(&self, name: &str) -> Option<Cow<'_, str>> {
+        find_param(&self.params, name)
+    }
+
+    /// The value of a parameter as it was sent, ignoring case in the name.
+    pub fn param_raw(&self, name: &str) -> Option<&[u8]> {
+        find_param_raw(&self.params, name)
+    }
+}
+
+impl ContentDisposition<'_> {
+    /// The value of a parameter such as `filename`, ignoring case in the name.
+    /// With the `mime` feature, RFC 2231 continuations and charsets are
+    /// decoded. Invalid UTF-8 is replaced.
+    pub fn param(&self, name: &str) -> Option<Cow<'_, str>> {
+        find_param(&self.params, name)
+    }
+
+    /// The value of a parameter as it was sent, ignoring case in the name.
+    pub fn param_raw(&self, name: &str) -> Option<&[u8]> {
+        find_param_raw(&self.params, name)
+    }
+}
+
+#[cfg(feature = "mime")]
+fn find_param<'p>(params: &'p BodyParams<'_>, name: &str) -> Option<Cow<'p, str>> {
+    crate::mime::find_param(params.as_ref()?, name)
+}
+
+#[cfg(not(feature = "mime"))]
+fn find_param<'p>(params: &'p BodyParams<'_>, name: &str) -> Option<Cow<'p, str>> {
+    find_param_raw(params, name).map(String::from_utf8_lossy)
+}
+
+fn find_param_raw<'p>(params: &'p BodyParams<'_>, name: &str) -> Option<&'p [u8]> {
+    params
+        .as_ref()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, val)| val.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    // A mixed message with an alternative text/HTML body, a PDF attachment
+    // and a forwarded message.
+    const RESPONSE: &[u8] = b"* 1 FETCH (BODYSTRUCTURE (\
+        ((\"TEXT\" \"PLAIN\" (\"CHARSET\" \"utf-8\") NIL NIL \"7BIT\" 10 1 NIL NIL NIL)\
+        (\"TEXT\" \"HTML\" (\"CHARSET\" \"utf-8\") NIL NIL \"7BIT\" 20 1 NIL NIL NIL) \
+        \"ALTERNATIVE\" (\"BOUNDARY\" \"b2\") NIL NIL)\
+        (\"APPLICATION\" \"PDF\" (\"NAME\" \"a.pdf\") NIL NIL \"BASE64\" 30 NIL \
+        (\"ATTACHMENT\" (\"FILENAME\" \"report.pdf\")) NIL)\
+        (\"MESSAGE\" \"RFC822\" NIL NIL NIL \"7BIT\" 40 \
+        (NIL \"fwd\" NIL NIL NIL NIL NIL NIL NIL NIL) \
+        (\"TEXT\" \"PLAIN\" NIL NIL NIL \"7BIT\" 5 1) 3 NIL (\"INLINE\" NIL) NIL) \
+        \"MIXED\" (\"BOUNDARY\" \"b1\") NIL NIL))\r\n";
+
+    fn with_body(f: impl FnOnce(&BodyStructure<'_>)) {
+        match parse_response(RESPONSE) {
+            Ok((_, Response::Fetch(_, attrs))) => match &attrs[0] {
+                AttributeValue::BodyStructure(body) => f(body),
+                attr => panic!("unexpected attribute {:?}", attr),
+            },
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_parts() {
+        with_body(|body| {
+            let parts = body
+                .parts()
+                .map(|(path, part)| (path, part.content_type().mime_type()))
+                .collect::<Vec<_>>();
+            let expected: Vec<(Vec<u32>, &str)> = vec![
+                (vec![], "multipart/mixed"),
+                (vec![1], "multipart/alternative"),
+                (vec![1, 1], "text/plain"),
+                (vec![1, 2], "text/html"),
+                (vec![2], "application/pdf"),
+                (vec![3], "message/rfc822"),
+                (vec![3, 1], "text/plain"),
+            ];
+            let expected = expected
+                .into_iter()
+                .map(|(path, ty)| (path, ty.to_string()))
+                .collect::<Vec<_>>();
+            assert_eq!(parts, expected);
+        });
+    }
+
+    #[test]
+    fn test_selectors() {
+        with_body(|body| {
+            let attachments = body.attachments().collect::<Vec<_>>();
+            assert_eq!(attachments.len(), 1);
+            assert_eq!(attachments[0].0, vec![2]);
+            assert_eq!(attachments[0].1.filename().as_deref(), Some("report.pdf"));
+
+            let (path, text) = body.text_body().unwrap();
+            assert_eq!(path, vec![1, 1]);
+            assert_eq!(
+                text.content_type().param("charset").as_deref(),
+                Some("utf-8")
+            );
+            assert_eq!(body.html_body().unwrap().0, vec![1, 2]);
+            assert!(body.single_part().is_none());
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "mime")]
+    fn test_encoded_filename() {
+        let body = b"* 1 FETCH (BODYSTRUCTURE (\"APPLICATION\" \"PDF\" NIL NIL NIL \"BASE64\" 30 \
+            NIL (\"ATTACHMENT\" (\"FILENAME*0*\" \"utf-8''Gr%C3%BC%C3%9Fe\" \"FILENAME*1\" \".pdf\")) \
+            NIL))\r\n";
+        match parse_response(body) {
+            Ok((_, Response::Fetch(_, attrs))) => match &attrs[0] {
+                AttributeValue::BodyStructure(body) => {
+                    assert_eq!(body.filename().as_deref(), Some("Grüße.pdf"));
+                    assert!(body.is_attachment());
+                }
+                attr => panic!("unexpected attribute {:?}", attr),
+            },
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_single_part() {
+        let body = b"* 1 FETCH (BODYSTRUCTURE (\"TEXT\" \"PLAIN\" NIL NIL NIL \"7BIT\" 5 1))\r\n";
+        match parse_response(body) {
+            Ok((_, Response::Fetch(_, attrs))) => match &attrs[0] {
+                AttributeValue::BodyStructure(body) => {
+                    assert_eq!(
+                        body.parts().map(|(path, _)| path).collect::<Vec<_>>(),
+                        vec![vec![1]]
+                    );
+                    assert_eq!(body.text_body().unwrap().0, vec![1]);
+                    assert_eq!(body.single_part().unwrap().octets, 5);
+                }
+                attr => panic!("unexpected attribute {:?}", attr),
+            },
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
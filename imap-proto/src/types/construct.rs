@@ -0,0 +1,228 @@
+// Constructors for response values, e.g. to build the expected responses in
+// tests. Fields that are not set default to NIL or their RFC defaults.
+
+use std::borrow::Cow;
+
+use super::*;
+
+impl<'a> Response<'a> {
+    /// A tagged response that completes a command, such as `a1 OK done`.
+    pub fn done(tag: &str, status: Status, information: impl Into<Cow<'a, str>>) -> Self {
+        Response::Done {
+            tag: RequestId(tag.to_string()),
+            status,
+            code: None,
+            information: Some(information.into()),
+        }
+    }
+
+    /// An untagged status response, such as `* OK still here`.
+    pub fn data(status: Status, information: impl Into<Cow<'a, str>>) -> Self {
+        Response::Data {
+            status,
+            code: None,
+            information: Some(information.into()),
+        }
+    }
+
+    /// Sets the response code of a status response. Other responses are
+    /// returned unchanged.
+    pub fn with_code(mut self, new: ResponseCode<'a>) -> Self {
+        if let Response::Done { code, .. } | Response::Data { code, .. } = &mut self {
+            *code = Some(new);
+        }
+        self
+    }
+}
+
+impl<'a> Address<'a> {
+    pub fn new(name: Option<&'a str>, mailbox: &'a str, host: &'a str) -> Self {
+        Address {
+            name: name.map(|name| Cow::Borrowed(name.as_bytes())),
+            adl: None,
+            mailbox: Some(Cow::Borrowed(mailbox.as_bytes())),
+            host: Some(Cow::Borrowed(host.as_bytes())),
+        }
+    }
+}
+
+impl<'a> SectionPath<'a> {
+    /// The part with the given part number, such as `[1, 2]` for `1.2`.
+    pub fn part(path: impl IntoIterator<Item = u32>) -> Self {
+        SectionPath::Part(path.into_iter().collect(), None)
+    }
+
+    /// The header of the message, or of the message in this part.
+    pub fn header(self) -> Self {
+        self.with(MessageSection::Header)
+    }
+
+    /// Only the given header fields.
+    pub fn header_fields<S: Into<Cow<'a, str>>>(self, fields: impl IntoIterator<Item = S>) -> Self {
+        self.with(MessageSection::HeaderFields(
+            fields.into_iter().map(Into::into).collect(),
+        ))
+    }
+
+    /// All header fields except the given ones.
+    pub fn header_fields_not<S: Into<Cow<'a, str>>>(
+        self,
+        fields: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.with(MessageSection::HeaderFieldsNot(
+            fields.into_iter().map(Into::into).collect(),
+        ))
+    }
+
+    /// The MIME header of this part.
+    pub fn mime(self) -> Self {
+        self.with(MessageSection::Mime)
+    }
+
+    /// The body of the message, or of the message in this part.
+    pub fn text(self) -> Self {
+        self.with(MessageSection::Text)
+    }
+
+    fn with(self, section: MessageSection<'a>) -> Self {
+        match self {
+            SectionPath::Full(_) => SectionPath::Full(section),
+            SectionPath::Part(path, _) => SectionPath::Part(path, Some(section)),
+        }
+    }
+}
+
+impl<'a> ContentType<'a> {
+    pub fn new(ty: impl Into<Cow<'a, str>>, subtype: impl Into<Cow<'a, str>>) -> Self {
+        ContentType {
+            ty: ty.into(),
+            subtype: subtype.into(),
+            params: None,
+        }
+    }
+
+    /// Adds a parameter, such as `charset`.
+    pub fn with_param(
+        mut self,
+        key: impl Into<Cow<'a, str>>,
+        val: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        let val = match val.into() {
+            Cow::Borrowed(val) => Cow::Borrowed(val.as_bytes()),
+            Cow::Owned(val) => Cow::Owned(val.into_bytes()),
+        };
+        self.params
+            .get_or_insert_with(Vec::new)
+            .push((key.into(), val));
+        self
+    }
+}
+
+impl<'a> BodyContentCommon<'a> {
+    pub fn new(ty: ContentType<'a>) -> Self {
+        BodyContentCommon {
+            ty,
+            disposition: None,
+            language: None,
+            location: None,
+        }
+    }
+}
+
+impl<'a> BodyStructure<'a> {
+    /// A `text/<subtype>` body of the given size in octets and lines.
+    pub fn text(subtype: impl Into<Cow<'a, str>>, octets: u32, lines: u32) -> Self {
+        BodyStructure::Text {
+            common: BodyContentCommon::new(ContentType::new("TEXT", subtype)),
+            other: BodyContentSinglePart {
+                octets,
+                ..Default::default()
+            },
+            lines,
+            extensions: vec![],
+        }
+    }
+
+    /// A non-text, non-message body of the given type and size in octets.
+    pub fn basic(ty: ContentType<'a>, octets: u32) -> Self {
+        BodyStructure::Basic {
+            common: BodyContentCommon::new(ty),
+            other: BodyContentSinglePart {
+                octets,
+                ..Default::default()
+            },
+            extensions: vec![],
+        }
+    }
+
+    /// A `multipart/<subtype>` body with the given parts.
+    pub fn multipart(subtype: impl Into<Cow<'a, str>>, bodies: Vec<BodyStructure<'a>>) -> Self {
+        BodyStructure::Multipart {
+            common: BodyContentCommon::new(ContentType::new("MULTIPART", subtype)),
+            bodies,
+            extensions: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_status_responses() {
+        assert_eq!(
+            parse_response(b"a1 OK [READ-WRITE] SELECT completed\r\n")
+                .unwrap()
+                .1,
+            Response::done("a1", Status::Ok, "SELECT completed").with_code(ResponseCode::ReadWrite)
+        );
+        assert_eq!(
+            parse_response(b"* NO disk almost full\r\n").unwrap().1,
+            Response::data(Status::No, "disk almost full")
+        );
+    }
+
+    #[test]
+    fn test_fetch_response() {
+        let rsp = b"* 2 FETCH (ENVELOPE (NIL \"hi\" ((\"Terry\" NIL \"gray\" \"example.com\")) \
+            NIL NIL NIL NIL NIL NIL NIL) BODYSTRUCTURE ((\"TEXT\" \"PLAIN\" (\"CHARSET\" \"us-ascii\") \
+            NIL NIL \"7BIT\" 12 1)(\"APPLICATION\" \"PDF\" NIL NIL NIL \"7BIT\" 30) \"MIXED\"))\r\n";
+        let expected = Response::Fetch(
+            Seq(2),
+            vec![
+                AttributeValue::Envelope(Box::new(Envelope {
+                    subject: Some(b"hi"[..].into()),
+                    from: Some(vec![Address::new(Some("Terry"), "gray", "example.com")]),
+                    ..Default::default()
+                })),
+                AttributeValue::BodyStructure(BodyStructure::multipart(
+                    "MIXED",
+                    vec![
+                        BodyStructure::Text {
+                            common: BodyContentCommon::new(
+                                ContentType::new("TEXT", "PLAIN").with_param("CHARSET", "us-ascii"),
+                            ),
+                            other: BodyContentSinglePart {
+                                octets: 12,
+                                ..Default::default()
+                            },
+                            lines: 1,
+                            extensions: vec![],
+                        },
+                        BodyStructure::basic(ContentType::new("APPLICATION", "PDF"), 30),
+                    ],
+                )),
+            ],
+        );
+        let parsed = parse_response(rsp).unwrap().1;
+        assert_eq!(parsed, expected);
+
+        let mut set = HashSet::new();
+        set.insert(parsed.clone());
+        assert!(set.contains(&expected));
+    }
+}
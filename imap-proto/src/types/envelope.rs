@@ -0,0 +1,152 @@
+// Access to the text fields of envelopes, which servers send as bytes in
+// whatever charset the message used.
+
+use std::borrow::Cow;
+
+use super::{DateTime, Envelope};
+
+impl Envelope<'_> {
+    /// The `Date` header parsed as an RFC 5322 date-time, or the header as
+    /// it was sent if it can't be parsed.
+    pub fn date_time(&self) -> Option<Result<DateTime, Cow<'_, str>>> {
+        let date = self.date_lossy()?;
+        Some(DateTime::parse_rfc5322(&date).ok_or(date))
+    }
+
+    /// The `Date` header as a chrono date-time, or `None` if it is missing
+    /// or can't be parsed.
+    #[cfg(feature = "chrono")]
+    pub fn chrono_date_time(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        self.date_time()?.ok()?.to_chrono()
+    }
+
+    /// The `Date` header, with invalid UTF-8 replaced.
+    pub fn date_lossy(&self) -> Option<Cow<'_, str>> {
+        lossy(&self.date)
+    }
+
+    /// The `Subject` header, with invalid UTF-8 replaced. Encoded-words
+    /// are left as they are.
+    pub fn subject_lossy(&self) -> Option<Cow<'_, str>> {
+        lossy(&self.subject)
+    }
+
+    /// The `Subject` header with any RFC 2047 encoded-words decoded, such
+    /// as `=?UTF-8?Q?Gr=C3=BC=C3=9Fe?=` to `Grüße`.
+    #[cfg(feature = "mime")]
+    pub fn subject_decoded(&self) -> Option<String> {
+        self.subject
+            .as_deref()
+            .map(crate::mime::decode_encoded_words)
+    }
+
+    /// The `In-Reply-To` header, with invalid UTF-8 replaced.
+    pub fn in_reply_to_lossy(&self) -> Option<Cow<'_, str>> {
+        lossy(&self.in_reply_to)
+    }
+
+    /// The `Message-ID` header, with invalid UTF-8 replaced.
+    pub fn message_id_lossy(&self) -> Option<Cow<'_, str>> {
+        lossy(&self.message_id)
+    }
+}
+
+// Borrows the field if it is valid UTF-8.
+pub(super) fn lossy<'s>(field: &'s Option<Cow<'_, [u8]>>) -> Option<Cow<'s, str>> {
+    field.as_deref().map(String::from_utf8_lossy)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    #[test]
+    fn test_lossy() {
+        let rsp = b"* 1 FETCH (ENVELOPE (\"Mon, 7 Feb 1994 21:52:25 -0800\" \
+            {11}\r\nGr\xfc\xdfe aus Z \
+            ((\"J\xf6rg\" NIL \"joerg\" \"example.com\")) NIL NIL NIL NIL NIL NIL \
+            \"<B27397-0100000@cac.washington.edu>\"))\r\n";
+        match parse_response(rsp) {
+            Ok((_, Response::Fetch(_, attrs))) => match &attrs[0] {
+                AttributeValue::Envelope(envelope) => {
+                    assert_eq!(envelope.subject.as_deref(), Some(&b"Gr\xfc\xdfe aus Z"[..]));
+                    assert_eq!(
+                        envelope.subject_lossy().as_deref(),
+                        Some("Gr\u{fffd}\u{fffd}e aus Z")
+                    );
+                    assert!(matches!(envelope.date_lossy(), Some(Cow::Borrowed(_))));
+                    assert_eq!(envelope.in_reply_to_lossy(), None);
+                    assert_eq!(
+                        envelope.message_id_lossy().as_deref(),
+                        Some("<B27397-0100000@cac.washington.edu>")
+                    );
+
+                    let from = &envelope.from.as_ref().unwrap()[0];
+                    assert_eq!(from.name_lossy().as_deref(), Some("J\u{fffd}rg"));
+                    assert_eq!(from.mailbox_lossy().as_deref(), Some("joerg"));
+                    assert_eq!(from.host_lossy().as_deref(), Some("example.com"));
+                }
+                attr => panic!("unexpected attribute {:?}", attr),
+            },
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+
+    #[test]
+    fn test_date_time() {
+        let envelope = |date: &'static str| Envelope {
+            date: Some(Cow::Borrowed(date.as_bytes())),
+            ..Envelope::default()
+        };
+        let a = envelope("Mon, 7 Feb 1994 21:52:25 -0800");
+        let b = envelope("Tue, 08 Feb 1994 05:52:26 GMT");
+        let (a, b) = (a.date_time().unwrap(), b.date_time().unwrap());
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert_eq!(a.timestamp(), 760_686_745);
+        assert_eq!(b.timestamp() - a.timestamp(), 1);
+
+        assert_eq!(
+            envelope("sometime last week").date_time(),
+            Some(Err(Cow::Borrowed("sometime last week")))
+        );
+        assert_eq!(Envelope::default().date_time(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_chrono_date_time() {
+        let envelope = Envelope {
+            date: Some(Cow::Borrowed(b"Mon, 7 Feb 1994 21:52:25 -0800")),
+            ..Envelope::default()
+        };
+        let date = envelope.chrono_date_time().unwrap();
+        assert_eq!(date.timestamp(), 760_686_745);
+        assert_eq!(date.offset().local_minus_utc(), -8 * 3600);
+
+        let leap = DateTime::parse("31-Dec-2016 23:59:60 +0000").unwrap();
+        assert_eq!(leap.to_chrono().unwrap().timestamp(), leap.timestamp() - 1);
+        assert_eq!(
+            DateTime::parse("31-Feb-2024 12:00:00 +0000")
+                .unwrap()
+                .to_chrono(),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "mime")]
+    fn test_subject_decoded() {
+        let envelope = Envelope {
+            subject: Some(Cow::Borrowed(b"Re: =?UTF-8?Q?Gr=C3=BC=C3=9Fe?= aus Z")),
+            ..Envelope::default()
+        };
+        assert_eq!(
+            envelope.subject_decoded().as_deref(),
+            Some("Re: Grüße aus Z")
+        );
+        assert_eq!(Envelope::default().subject_decoded(), None);
+    }
+}
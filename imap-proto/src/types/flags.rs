@@ -0,0 +1,202 @@
+// Flag sets: parsing and formatting flag lists, and the STORE operations
+// needed to get from one set of flags to another.
+
+use std::fmt;
+use std::iter::FromIterator;
+
+use super::{Flag, FlagDiff, FlagSet};
+
+impl<'a> FlagSet<'a> {
+    pub fn new() -> Self {
+        FlagSet(vec![])
+    }
+
+    /// Parses a parenthesized flag list, such as `(\Seen $Forwarded)`.
+    pub fn parse(s: &'a str) -> Option<Self> {
+        match crate::parser::rfc3501::flag_list(s.as_bytes()) {
+            Ok(([], flags)) => Some(flags.into_iter().collect()),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, flag: &Flag<'_>) -> bool {
+        self.0.iter().any(|f| same_flag(f, flag))
+    }
+
+    /// Adds a flag, returning whether it was not in the set yet.
+    pub fn insert(&mut self, flag: Flag<'a>) -> bool {
+        if self.contains(&flag) {
+            return false;
+        }
+        self.0.push(flag);
+        true
+    }
+
+    /// Removes a flag, returning whether it was in the set.
+    pub fn remove(&mut self, flag: &Flag<'_>) -> bool {
+        let len = self.0.len();
+        self.0.retain(|f| !same_flag(f, flag));
+        self.0.len() != len
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Flag<'a>> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The flags to add to and remove from this set to get `target`.
+    ///
+    /// `\Recent` and `\*` are left out, since they can't be set by a client,
+    /// and so are flags that can't be sent, such as keywords that aren't
+    /// atoms.
+    pub fn diff(&self, target: &FlagSet<'a>) -> FlagDiff<'a> {
+        let changes = |from: &FlagSet<'a>, to: &FlagSet<'a>| {
+            from.iter()
+                .filter(|f| !matches!(f, Flag::Recent) && f.is_valid() && !to.contains(f))
+                .cloned()
+                .collect()
+        };
+        FlagDiff {
+            add: changes(target, self),
+            remove: changes(self, target),
+        }
+    }
+}
+
+impl PartialEq for FlagSet<'_> {
+    fn eq(&self, other: &FlagSet<'_>) -> bool {
+        self.len() == other.len() && self.iter().all(|f| other.contains(f))
+    }
+}
+
+impl Eq for FlagSet<'_> {}
+
+impl<'a> FromIterator<Flag<'a>> for FlagSet<'a> {
+    fn from_iter<I: IntoIterator<Item = Flag<'a>>>(iter: I) -> Self {
+        let mut set = FlagSet::new();
+        for flag in iter {
+            set.insert(flag);
+        }
+        set
+    }
+}
+
+impl<'a> From<Vec<Flag<'a>>> for FlagSet<'a> {
+    fn from(flags: Vec<Flag<'a>>) -> Self {
+        flags.into_iter().collect()
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s FlagSet<'a> {
+    type Item = &'s Flag<'a>;
+    type IntoIter = std::slice::Iter<'s, Flag<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Formats the set as a flag list, such as `(\Seen $Forwarded)`.
+impl fmt::Display for FlagSet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, flag) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", flag.as_str())?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl FlagDiff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.remove.is_empty()
+    }
+
+    /// The data items of the STORE commands that apply the changes, such
+    /// as `+FLAGS (\Seen)` and `-FLAGS (\Draft)`, leaving out those with no
+    /// flags. With `silent`, the `.SILENT` forms are used, so that the
+    /// server doesn't send the new flags back.
+    pub fn store_items(&self, silent: bool) -> Vec<String> {
+        let suffix = if silent { ".SILENT" } else { "" };
+        let mut items = vec![];
+        if !self.add.is_empty() {
+            items.push(format!("+FLAGS{} {}", suffix, self.add));
+        }
+        if !self.remove.is_empty() {
+            items.push(format!("-FLAGS{} {}", suffix, self.remove));
+        }
+        items
+    }
+}
+
+// Flags, including keywords, are case-insensitive (RFC 3501, section 2.3.2).
+fn same_flag(a: &Flag<'_>, b: &Flag<'_>) -> bool {
+    a.as_str().eq_ignore_ascii_case(b.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::types::*;
+
+    fn keyword(s: &str) -> Flag<'_> {
+        Flag::Keyword(Cow::Borrowed(s))
+    }
+
+    #[test]
+    fn test_flag_set() {
+        let flags = FlagSet::parse("(\\Seen $Forwarded \\seen $forwarded \\Recent)").unwrap();
+        assert_eq!(flags.len(), 3);
+        assert!(flags.contains(&keyword("$FORWARDED")));
+        assert_eq!(flags.to_string(), "(\\Seen $Forwarded \\Recent)");
+        assert_eq!(FlagSet::parse("()"), Some(FlagSet::new()));
+        assert_eq!(FlagSet::new().to_string(), "()");
+        assert_eq!(FlagSet::parse("(\\Seen"), None);
+        assert_eq!(FlagSet::parse("\\Seen"), None);
+
+        let mut flags = FlagSet::from(vec![Flag::Seen]);
+        assert!(!flags.insert(Flag::system("\\SEEN")));
+        assert!(flags.insert(Flag::Deleted));
+        assert!(flags.remove(&Flag::Seen));
+        assert!(!flags.remove(&Flag::Seen));
+        assert_eq!(flags, FlagSet::from(vec![Flag::Deleted]));
+    }
+
+    #[test]
+    fn test_flag_diff() {
+        let current = FlagSet::parse("(\\Seen \\Draft \\Recent Junk)").unwrap();
+        let target = FlagSet::parse("(\\Seen \\Flagged junk)").unwrap();
+        let diff = current.diff(&target);
+        assert_eq!(diff.add, FlagSet::from(vec![Flag::Flagged]));
+        assert_eq!(diff.remove, FlagSet::from(vec![Flag::Draft]));
+        assert_eq!(
+            diff.store_items(false),
+            vec!["+FLAGS (\\Flagged)", "-FLAGS (\\Draft)"]
+        );
+
+        let diff = target.diff(&FlagSet::new());
+        assert_eq!(
+            diff.store_items(true),
+            vec!["-FLAGS.SILENT (\\Seen \\Flagged junk)"]
+        );
+        assert!(current.diff(&current).is_empty());
+        assert!(current.diff(&current).store_items(true).is_empty());
+
+        let target = FlagSet::from(vec![keyword("Junk"), keyword("a)\r\nA1 LOGOUT")]);
+        assert_eq!(
+            FlagSet::new().diff(&target).store_items(false),
+            vec!["+FLAGS (Junk)"]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+// Mailbox names: INBOX handling, modified UTF-7 and hierarchy delimiters.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use super::MailboxName;
+use crate::utf7::{decode_utf7_imap, encode_utf7_imap};
+
+impl<'a> MailboxName<'a> {
+    /// Wraps a name in its wire form, without encoding it.
+    pub fn new(name: impl Into<Cow<'a, str>>) -> Self {
+        MailboxName(name.into())
+    }
+
+    /// Encodes a UTF-8 name into modified UTF-7, for servers that have not
+    /// enabled `UTF8=ACCEPT`.
+    pub fn encode(name: &str) -> MailboxName<'static> {
+        MailboxName(Cow::Owned(encode_utf7_imap(name)))
+    }
+
+    /// The name in its wire form.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The name decoded from modified UTF-7, or `None` if it is not validly
+    /// encoded. Names sent as UTF-8 decode to themselves.
+    pub fn decoded(&self) -> Option<String> {
+        decode_utf7_imap(&self.0)
+    }
+
+    /// Whether this is the special name `INBOX`, in any case.
+    pub fn is_inbox(&self) -> bool {
+        self.0.eq_ignore_ascii_case("INBOX")
+    }
+
+    /// The levels of the hierarchy, as separated by `delimiter`, which is
+    /// the one reported in the mailbox's LIST response.
+    pub fn segments(&self, delimiter: char) -> impl Iterator<Item = &str> {
+        self.0.split(delimiter)
+    }
+
+    /// The name of the parent mailbox, or `None` at the top level.
+    pub fn parent(&self, delimiter: char) -> Option<MailboxName<'_>> {
+        let (parent, _) = self.0.rsplit_once(delimiter)?;
+        Some(MailboxName(Cow::Borrowed(parent)))
+    }
+
+    /// The name of a child mailbox of this one.
+    pub fn child(&self, delimiter: char, name: &str) -> MailboxName<'static> {
+        MailboxName(Cow::Owned(format!("{}{}{}", self.0, delimiter, name)))
+    }
+
+    // The name used for comparisons, with INBOX in a canonical case.
+    fn key(&self) -> &str {
+        if self.is_inbox() {
+            "INBOX"
+        } else {
+            &self.0
+        }
+    }
+}
+
+impl PartialEq for MailboxName<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for MailboxName<'_> {}
+
+impl PartialEq<str> for MailboxName<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.key() == MailboxName::new(other).key()
+    }
+}
+
+impl PartialEq<&str> for MailboxName<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl Hash for MailboxName<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+impl AsRef<str> for MailboxName<'_> {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a str> for MailboxName<'a> {
+    fn from(name: &'a str) -> Self {
+        MailboxName::new(name)
+    }
+}
+
+impl From<String> for MailboxName<'static> {
+    fn from(name: String) -> Self {
+        MailboxName::new(name)
+    }
+}
+
+impl fmt::Display for MailboxName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::types::MailboxName;
+
+    #[test]
+    fn test_inbox() {
+        assert_eq!(MailboxName::new("inbox"), MailboxName::new("INBOX"));
+        assert_eq!(MailboxName::new("InBox"), "INBOX");
+        assert_ne!(MailboxName::new("Drafts"), MailboxName::new("drafts"));
+        assert_ne!(MailboxName::new("inbox/Sub"), "INBOX/Sub");
+
+        let names: HashSet<_> = vec!["INBOX", "Inbox", "Sent"]
+            .into_iter()
+            .map(MailboxName::new)
+            .collect();
+        assert_eq!(names.len(), 2);
+    }
+
+    #[test]
+    fn test_utf7() {
+        let name = MailboxName::encode("Entwürfe");
+        assert_eq!(name.as_str(), "Entw&APw-rfe");
+        assert_eq!(name.decoded().as_deref(), Some("Entwürfe"));
+        assert_eq!(MailboxName::new("&APw").decoded(), None);
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let name = MailboxName::new("INBOX.Lists.rust");
+        assert_eq!(
+            name.segments('.').collect::<Vec<_>>(),
+            vec!["INBOX", "Lists", "rust"]
+        );
+        assert_eq!(name.parent('.').unwrap(), "INBOX.Lists");
+        assert_eq!(MailboxName::new("INBOX").parent('.'), None);
+        assert_eq!(name.child('.', "nom"), "INBOX.Lists.rust.nom");
+    }
+}
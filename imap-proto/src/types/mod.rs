@@ -1,38 +1,498 @@
-#[derive(Clone, Debug, Eq, PartialEq)]
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+mod acl;
+mod address;
+#[cfg(feature = "proptest")]
+mod arbitrary;
+mod body;
+mod construct;
+mod envelope;
+mod flags;
+mod mailbox;
+mod owned;
+mod redact;
+mod request;
+mod search;
+mod sequence;
+
+pub(crate) use self::redact::RedactedArgs;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Request(pub RequestId, pub Vec<u8>);
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// Which literals a client may send without waiting for a continuation
+/// request, depending on the server's capabilities (RFC 7888).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LiteralMode {
+    /// Every literal waits for a continuation request.
+    #[default]
+    Sync,
+    /// `LITERAL+`: literals of any size are sent right away.
+    Plus,
+    /// `LITERAL-`: literals of up to 4096 bytes are sent right away.
+    Minus,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum AttrMacro {
     All,
     Fast,
     Full,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+/// How a STORE command changes the flags of a message, or a SETACL
+/// command the rights of an identifier.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum StoreMode {
+    /// `FLAGS`: replace the flags.
+    Replace,
+    /// `+FLAGS`: add to the flags.
+    Add,
+    /// `-FLAGS`: remove from the flags.
+    Remove,
+}
+
+/// A parameter of a SELECT or EXAMINE command (RFC 4466).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SelectParam {
+    /// Turns on CONDSTORE for the connection, so that the server reports
+    /// mod-sequences (RFC 7162, section 3.1.8).
+    Condstore,
+    /// Resynchronizes with the state the client knows, once QRESYNC has
+    /// been enabled (RFC 7162, section 3.2.5).
+    Qresync(QresyncParams),
+}
+
+/// What a client knows about a mailbox from an earlier session, so that
+/// the server only reports what changed since.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct QresyncParams {
+    /// The UIDVALIDITY of the mailbox; if it changed, nothing is reported.
+    pub uid_validity: u32,
+    /// The highest mod-sequence the client knows of.
+    pub mod_seq: u64,
+    /// The UIDs the client knows of, if not all of them.
+    pub known_uids: Option<SequenceSet>,
+    /// Some message numbers and the UIDs they correspond to, which let the
+    /// server tell the expunges the client missed more cheaply.
+    pub seq_match: Option<(SequenceSet, SequenceSet)>,
+}
+
+/// A part of a message put together by the server in an APPEND command
+/// (RFC 4469).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum CatenatePart<'a> {
+    /// An IMAP URL (RFC 5092) of a message or body part the server has,
+    /// such as `/INBOX;UIDVALIDITY=385759045/;UID=20/;SECTION=1`.
+    Url(Cow<'a, str>),
+    /// Data sent by the client.
+    Text(Cow<'a, [u8]>),
+}
+
+/// A compression algorithm for the COMPRESS command (RFC 4978).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CompressionAlgorithm {
+    /// Raw DEFLATE (RFC 1951), without zlib headers.
+    Deflate,
+}
+
+impl CompressionAlgorithm {
+    /// The algorithm as it is written on the wire.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CompressionAlgorithm::Deflate => "DEFLATE",
+        }
+    }
+}
+
+/// A search key of a SEARCH command (RFC 3501, section 6.4.4), or a
+/// combination of them. Strings match as substrings, ignoring case.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SearchCriteria<'a> {
+    All,
+    Answered,
+    Deleted,
+    Draft,
+    Flagged,
+    /// Recent and not seen.
+    New,
+    /// Not recent.
+    Old,
+    Recent,
+    Seen,
+    Unanswered,
+    Undeleted,
+    Undraft,
+    Unflagged,
+    Unseen,
+    Keyword(Cow<'a, str>),
+    Unkeyword(Cow<'a, str>),
+    Bcc(Cow<'a, str>),
+    Body(Cow<'a, str>),
+    Cc(Cow<'a, str>),
+    From(Cow<'a, str>),
+    Subject(Cow<'a, str>),
+    /// The header or the body contains the string.
+    Text(Cow<'a, str>),
+    To(Cow<'a, str>),
+    /// A header field and a string its value contains. An empty string
+    /// matches all messages that have the field.
+    Header(Cow<'a, str>, Cow<'a, str>),
+    /// The internal date is before the date.
+    Before(Date),
+    /// The internal date is on the date.
+    On(Date),
+    /// The internal date is on or after the date.
+    Since(Date),
+    /// The Date header is before the date.
+    SentBefore(Date),
+    SentOn(Date),
+    SentSince(Date),
+    /// The size is larger than the given number of octets.
+    Larger(u32),
+    Smaller(u32),
+    /// Messages with the given sequence numbers.
+    SequenceSet(SequenceSet),
+    /// Messages with the given UIDs.
+    Uid(SequenceSet),
+    Not(Box<SearchCriteria<'a>>),
+    Or(Box<SearchCriteria<'a>>, Box<SearchCriteria<'a>>),
+    /// Messages that match all of the criteria. Nested in `Not` or `Or`,
+    /// this is sent as a parenthesized list.
+    And(Vec<SearchCriteria<'a>>),
+}
+
+/// A key to sort the results of a SORT command by (RFC 5256).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SortKey {
+    Arrival,
+    Cc,
+    Date,
+    From,
+    Size,
+    Subject,
+    To,
+    DisplayFrom, // RFC 5957
+    DisplayTo,   // RFC 5957
+}
+
+impl SortKey {
+    /// The key as it is written on the wire.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortKey::Arrival => "ARRIVAL",
+            SortKey::Cc => "CC",
+            SortKey::Date => "DATE",
+            SortKey::From => "FROM",
+            SortKey::Size => "SIZE",
+            SortKey::Subject => "SUBJECT",
+            SortKey::To => "TO",
+            SortKey::DisplayFrom => "DISPLAYFROM",
+            SortKey::DisplayTo => "DISPLAYTO",
+        }
+    }
+
+    /// Sorts by the key in descending order.
+    pub fn reverse(self) -> SortCriterion {
+        SortCriterion {
+            key: self,
+            reverse: true,
+        }
+    }
+}
+
+/// A sort key, in ascending order unless `reverse` is set.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SortCriterion {
+    pub key: SortKey,
+    pub reverse: bool,
+}
+
+impl From<SortKey> for SortCriterion {
+    fn from(key: SortKey) -> Self {
+        SortCriterion {
+            key,
+            reverse: false,
+        }
+    }
+}
+
+/// How a THREAD command groups messages into threads (RFC 5256).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ThreadAlgorithm {
+    /// By base subject, ordered by date.
+    OrderedSubject,
+    /// By the References and In-Reply-To headers.
+    References,
+}
+
+impl ThreadAlgorithm {
+    /// The algorithm as it is written on the wire.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThreadAlgorithm::OrderedSubject => "ORDEREDSUBJECT",
+            ThreadAlgorithm::References => "REFERENCES",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Response<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Capabilities(Vec<Capability<'a>>),
-    Continue {
-        code: Option<ResponseCode<'a>>,
-        information: Option<&'a str>,
-    },
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Enabled(Vec<Capability<'a>>), // RFC 5161, section 3.2
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Continue(ContinueRequest<'a>),
     Done {
         tag: RequestId,
         status: Status,
+        #[cfg_attr(feature = "serde", serde(borrow))]
         code: Option<ResponseCode<'a>>,
-        information: Option<&'a str>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        information: Option<Cow<'a, str>>,
     },
     Data {
         status: Status,
+        #[cfg_attr(feature = "serde", serde(borrow))]
         code: Option<ResponseCode<'a>>,
-        information: Option<&'a str>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        information: Option<Cow<'a, str>>,
     },
-    Expunge(u32),
-    Fetch(u32, Vec<AttributeValue<'a>>),
+    Expunge(Seq),
+    Fetch(
+        Seq,
+        #[cfg_attr(feature = "serde", serde(borrow))] Vec<AttributeValue<'a>>,
+    ),
+    UidFetch(
+        Uid,
+        #[cfg_attr(feature = "serde", serde(borrow))] Vec<AttributeValue<'a>>,
+    ), // RFC 9586, section 3.3
+    #[cfg_attr(feature = "serde", serde(borrow))]
     MailboxData(MailboxDatum<'a>),
     IDs(Vec<u32>),
+    Sort(Vec<u32>),      // RFC 5256, section 4
+    Thread(Vec<Thread>), // RFC 5256, section 4
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    ESearch(ESearch<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Id(Option<BTreeMap<Cow<'a, str>, Option<Cow<'a, str>>>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Quota(Quota<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    QuotaRoot(QuotaRoot<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Acl(Acl<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    ListRights(ListRights<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    MyRights(MyRights<'a>),
+    Namespace {
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        personal: Option<Vec<NamespaceDescription<'a>>>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        other: Option<Vec<NamespaceDescription<'a>>>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        shared: Option<Vec<NamespaceDescription<'a>>>,
+    },
+    Vanished {
+        earlier: bool,
+        uids: SequenceSet,
+    },
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Language(Vec<Cow<'a, str>>), // RFC 5255, section 3.3
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    GenUrlAuth(Vec<Cow<'a, str>>), // RFC 4467, section 7
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    UrlFetch(Vec<UrlFetchData<'a>>), // RFC 4467, section 7
+    // draft-daboo-imap-annotatemore, section 4.2
+    Annotation {
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        mailbox: Cow<'a, str>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        annotation: Annotation<'a>,
+    },
+}
+
+impl<'a> Response<'a> {
+    /// Returns the change to the selected mailbox that this response
+    /// reports, if it is one of the responses a server may send at any time
+    /// to keep the client's view of the mailbox up to date.
+    ///
+    /// A FETCH response only counts if it carries FLAGS and otherwise at most
+    /// UID and MODSEQ, as sent when another client changes flags; other FETCH
+    /// responses are results of a FETCH command. `VANISHED (EARLIER)` is the
+    /// result of a command and does not count either. The same goes for
+    /// UIDFETCH responses, which replace FETCH once UIDONLY is enabled.
+    pub fn mailbox_update(&self) -> Option<MailboxUpdate<'a>> {
+        Some(match self {
+            Response::MailboxData(MailboxDatum::Exists(n)) => MailboxUpdate::Exists(*n),
+            Response::MailboxData(MailboxDatum::Recent(n)) => MailboxUpdate::Recent(*n),
+            Response::Expunge(n) => MailboxUpdate::Expunge(*n),
+            Response::Vanished {
+                earlier: false,
+                uids,
+            } => MailboxUpdate::Vanished(uids.clone()),
+            Response::Fetch(seq, attrs) => {
+                let (flags, uid, mod_seq) = flag_update(attrs)?;
+                MailboxUpdate::Flags {
+                    seq: *seq,
+                    uid,
+                    mod_seq,
+                    flags,
+                }
+            }
+            Response::UidFetch(uid, attrs) => match flag_update(attrs)? {
+                (flags, None, mod_seq) => MailboxUpdate::UidFlags {
+                    uid: *uid,
+                    mod_seq,
+                    flags,
+                },
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+}
+
+type FlagUpdate<'a> = (Vec<Flag<'a>>, Option<Uid>, Option<u64>);
+
+// Returns the flags, UID and mod-sequence of a FETCH response that carries
+// FLAGS and nothing else besides UID and MODSEQ.
+fn flag_update<'a>(attrs: &[AttributeValue<'a>]) -> Option<FlagUpdate<'a>> {
+    let (mut flags, mut uid, mut mod_seq) = (None, None, None);
+    for attr in attrs {
+        match attr {
+            AttributeValue::Flags(f) => flags = Some(f.clone()),
+            AttributeValue::Uid(u) => uid = Some(*u),
+            AttributeValue::ModSeq(m) => mod_seq = Some(*m),
+            _ => return None,
+        }
+    }
+    Some((flags?, uid, mod_seq))
+}
+
+// RFC 3501, section 7.3 and 7.4
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MailboxUpdate<'a> {
+    Exists(u32),
+    Recent(u32),
+    Expunge(Seq),
+    Flags {
+        seq: Seq,
+        uid: Option<Uid>,
+        mod_seq: Option<u64>, // RFC 7162, section 3.1.4.1
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        flags: Vec<Flag<'a>>,
+    },
+    Vanished(SequenceSet), // RFC 7162, section 3.2.10
+    // RFC 9586, section 3.3
+    UidFlags {
+        uid: Uid,
+        mod_seq: Option<u64>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        flags: Vec<Flag<'a>>,
+    },
 }
 
-#[derive(Debug, Eq, PartialEq)]
+// RFC 3501, section 7.5
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ContinueRequest<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub code: Option<ResponseCode<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub information: Option<Cow<'a, str>>,
+}
+
+impl<'a> ContinueRequest<'a> {
+    /// Decodes the text of the continuation request as a base64 SASL
+    /// challenge, as sent during AUTHENTICATE. An empty continuation request
+    /// yields an empty challenge. Returns `None` if the request carries a
+    /// response code or its text is not valid base64.
+    pub fn challenge(&self) -> Option<Vec<u8>> {
+        if self.code.is_some() {
+            return None;
+        }
+        crate::parser::core::decode_base64(self.information.as_deref().unwrap_or(""))
+    }
+}
+
+// RFC 3501, section 7.1
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Greeting<'a> {
+    pub status: GreetingStatus,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub code: Option<ResponseCode<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub information: Option<Cow<'a, str>>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GreetingStatus {
+    Ok,
+    PreAuth,
+    Bye,
+}
+
+impl<'a> Greeting<'a> {
+    /// Capabilities advertised in a CAPABILITY response code, if any.
+    pub fn capabilities(&self) -> Option<&[Capability<'a>]> {
+        match self.code {
+            Some(ResponseCode::Capabilities(ref caps)) => Some(caps),
+            _ => None,
+        }
+    }
+}
+
+/// Only untagged OK, PREAUTH and BYE responses are valid greetings; anything
+/// else is handed back unchanged.
+impl<'a> TryFrom<Response<'a>> for Greeting<'a> {
+    type Error = Response<'a>;
+
+    fn try_from(response: Response<'a>) -> Result<Self, Self::Error> {
+        match response {
+            Response::Data {
+                status,
+                code,
+                information,
+            } => {
+                let status = match status {
+                    Status::Ok => GreetingStatus::Ok,
+                    Status::PreAuth => GreetingStatus::PreAuth,
+                    Status::Bye => GreetingStatus::Bye,
+                    _ => {
+                        return Err(Response::Data {
+                            status,
+                            code,
+                            information,
+                        })
+                    }
+                };
+                Ok(Greeting {
+                    status,
+                    code,
+                    information,
+                })
+            }
+            _ => Err(response),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Status {
     Ok,
     No,
@@ -41,25 +501,101 @@ pub enum Status {
     Bye,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ResponseCode<'a> {
     Alert,
-    BadCharset(Option<Vec<&'a str>>),
+    AppendUid(u32, SequenceSet),            // RFC 4315, section 3
+    CopyUid(u32, SequenceSet, SequenceSet), // RFC 4315, section 3
+    UidNotSticky,                           // RFC 4315, section 3
+    OverQuota,                              // RFC 9208, section 4.3
+    UidRequired,                            // RFC 9586, section 3.4
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    UrlMech(Vec<(Cow<'a, str>, Option<Cow<'a, str>>)>), // RFC 4467, section 7
+    TooBig,                                 // RFC 4469, section 4
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    BadUrl(Cow<'a, str>), // RFC 4469, section 4
+    CompressionActive,                      // RFC 4978, section 3
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Referral(Vec<Cow<'a, str>>), // RFC 2221, section 4.1
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    BadCharset(Option<Vec<Cow<'a, str>>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Capabilities(Vec<Capability<'a>>),
-    HighestModSeq(u64), // RFC 4551, section 3.1.1
+    HighestModSeq(u64),       // RFC 4551, section 3.1.1
+    Modified(SequenceSet),    // RFC 4551, section 3.2
+    NoModSeq,                 // RFC 4551, section 3.1.2
+    MetadataLongEntries(u64), // RFC 5464, section 4.2.1
+    MetadataMaxSize(u64),     // RFC 5464, section 4.3
+    MetadataTooMany,          // RFC 5464, section 4.3
+    MetadataNoPrivate,        // RFC 5464, section 4.3
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    MailboxId(Cow<'a, str>), // RFC 8474, section 4.1
+    NotificationOverflow,     // RFC 5465, section 5.8
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    BadEvent(Vec<Cow<'a, str>>), // RFC 5465, section 5.9
     Parse,
-    PermanentFlags(Vec<&'a str>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    PermanentFlags(Vec<Flag<'a>>),
     ReadOnly,
     ReadWrite,
     TryCreate,
     UidNext(u32),
     UidValidity(u32),
     Unseen(u32),
+    /// A response code without a dedicated variant: the atom and any
+    /// following text up to the closing bracket, unparsed.
+    Other(
+        #[cfg_attr(feature = "serde", serde(borrow))] Cow<'a, str>,
+        #[cfg_attr(feature = "serde", serde(borrow))] Option<Cow<'a, str>>,
+    ),
+}
+
+/// A status data item to request with a STATUS command.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum StatusItem {
+    Messages,
+    Recent,
+    UidNext,
+    UidValidity,
+    Unseen,
+    HighestModSeq,  // RFC 7162
+    MailboxId,      // RFC 8474
+    Size,           // RFC 8438
+    AppendLimit,    // RFC 7889
+    Deleted,        // RFC 9208
+    DeletedStorage, // RFC 9208
+}
+
+impl StatusItem {
+    /// The item as it is written on the wire.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StatusItem::Messages => "MESSAGES",
+            StatusItem::Recent => "RECENT",
+            StatusItem::UidNext => "UIDNEXT",
+            StatusItem::UidValidity => "UIDVALIDITY",
+            StatusItem::Unseen => "UNSEEN",
+            StatusItem::HighestModSeq => "HIGHESTMODSEQ",
+            StatusItem::MailboxId => "MAILBOXID",
+            StatusItem::Size => "SIZE",
+            StatusItem::AppendLimit => "APPENDLIMIT",
+            StatusItem::Deleted => "DELETED",
+            StatusItem::DeletedStorage => "DELETED-STORAGE",
+        }
+    }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-pub enum StatusAttribute {
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StatusAttribute<'a> {
     HighestModSeq(u64), // RFC 4551
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    MailboxId(Cow<'a, str>), // RFC 8474
+    Size(u64),          // RFC 8438
+    AppendLimit(Option<u64>), // RFC 7889
+    Deleted(u32),       // RFC 9208
+    DeletedStorage(u64), // RFC 9208
     Messages(u32),
     Recent(u32),
     UidNext(u32),
@@ -67,46 +603,556 @@ pub enum StatusAttribute {
     Unseen(u32),
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-pub struct Metadata {
-    pub entry: String,
-    pub value: Option<String>,
+/// A selection option of an extended LIST command (RFC 5258), which
+/// decides which mailboxes are listed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ListSelectOption {
+    /// Only subscribed mailboxes, including ones that don't exist.
+    Subscribed,
+    /// Also mailboxes on other servers, for MAILBOX-REFERRALS.
+    Remote,
+    /// Also parents of matched mailboxes that don't match themselves, with
+    /// CHILDINFO extended data. Needs another selection option, such as
+    /// `Subscribed`.
+    RecursiveMatch,
+    /// Only mailboxes with a special use, such as `\Sent` (RFC 6154).
+    SpecialUse,
+}
+
+impl ListSelectOption {
+    /// The option as it is written on the wire.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ListSelectOption::Subscribed => "SUBSCRIBED",
+            ListSelectOption::Remote => "REMOTE",
+            ListSelectOption::RecursiveMatch => "RECURSIVEMATCH",
+            ListSelectOption::SpecialUse => "SPECIAL-USE",
+        }
+    }
+}
+
+/// A return option of an extended LIST command (RFC 5258), asking for more
+/// data about each listed mailbox.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ListReturnOption {
+    /// The `\Subscribed` attribute.
+    Subscribed,
+    /// The `\HasChildren` or `\HasNoChildren` attribute.
+    Children,
+    /// Special-use attributes, such as `\Drafts` (RFC 6154).
+    SpecialUse,
+    /// A STATUS response with the given items (RFC 5819).
+    Status(Vec<StatusItem>),
+}
+
+// RFC 4731, section 3.1
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ESearch<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub tag: Option<Cow<'a, str>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub mailbox: Option<Cow<'a, str>>, // RFC 7377, section 3
+    pub uid_validity: Option<u32>, // RFC 7377, section 3
+    pub uid: bool,
+    pub data: Vec<SearchReturnData>,
+}
+
+/// A result option of an extended SEARCH command (RFC 4731), asking for
+/// an ESEARCH response with the given data.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SearchReturn {
+    Min,
+    Max,
+    All,
+    Count,
+    /// Keeps the result on the server, to be used as
+    /// `SequenceSet::LastResult` (RFC 5182).
+    Save,
+    Partial(PartialRange), // RFC 9394
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SearchReturnData {
+    Min(u32),
+    Max(u32),
+    All(SequenceSet),
+    Count(u32),
+    ModSeq(u64), // RFC 7162, section 3.1.5
+    /// The requested range and the matching messages in it, if any.
+    Partial(PartialRange, Option<SequenceSet>), // RFC 9394, section 3.1
+}
+
+/// A range of search results, counted from the first result or, for
+/// `Last(1, 10)`, meaning `-1:-10`, from the last one.
+// RFC 9394, section 3.1
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PartialRange {
+    First(u32, u32),
+    Last(u32, u32),
+}
+
+// RFC 2087, section 5.1
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Quota<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub root_name: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub resources: Vec<QuotaResource<'a>>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuotaResource<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub name: QuotaResourceName<'a>,
+    pub usage: u64,
+    pub limit: u64,
+}
+
+// RFC 9208, section 5
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum QuotaResourceName<'a> {
+    Storage,
+    Message,
+    Mailbox,
+    AnnotationStorage,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Atom(Cow<'a, str>),
+}
+
+impl QuotaResourceName<'_> {
+    /// The resource name as it is written on the wire.
+    pub fn as_str(&self) -> &str {
+        match self {
+            QuotaResourceName::Storage => "STORAGE",
+            QuotaResourceName::Message => "MESSAGE",
+            QuotaResourceName::Mailbox => "MAILBOX",
+            QuotaResourceName::AnnotationStorage => "ANNOTATION-STORAGE",
+            QuotaResourceName::Atom(name) => name,
+        }
+    }
+}
+
+// RFC 2087, section 5.2
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuotaRoot<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub mailbox_name: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub quota_root_names: Vec<Cow<'a, str>>,
+}
+
+// RFC 5256, section 4
+// A run of messages, each the parent of the next, with the last one the
+// parent of the `children`. A thread without ids is a placeholder for a
+// missing parent message.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Thread {
+    pub ids: Vec<u32>,
+    pub children: Vec<Thread>,
+}
+
+// RFC 4314, section 3.6
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Acl<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub mailbox: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub acls: Vec<AclEntry<'a>>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AclEntry<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub identifier: Cow<'a, str>,
+    pub rights: Vec<AclRight>,
+}
+
+// RFC 4314, section 3.7
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ListRights<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub mailbox: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub identifier: Cow<'a, str>,
+    pub required: Vec<AclRight>,
+    pub optional: Vec<Vec<AclRight>>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+// RFC 4314, section 3.8
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MyRights<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub mailbox: Cow<'a, str>,
+    pub rights: Vec<AclRight>,
+}
+
+// RFC 4314, section 2.1
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AclRight {
+    Lookup,        // l
+    Read,          // r
+    Seen,          // s
+    Write,         // w
+    Insert,        // i
+    Post,          // p
+    CreateMailbox, // k
+    DeleteMailbox, // x
+    DeleteMessage, // t
+    Expunge,       // e
+    Administer,    // a
+    OldCreate,     // c, RFC 2086
+    OldDelete,     // d, RFC 2086
+    Custom(char),
+}
+
+/// A set of ACL rights, such as `AclRights::LOOKUP | AclRights::READ`.
+/// Custom rights can be added by converting an `AclRight`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AclRights(u64);
+
+// RFC 2342, section 5
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NamespaceDescription<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub prefix: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub delimiter: Option<Cow<'a, str>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub extensions: Vec<(Cow<'a, str>, Vec<Cow<'a, str>>)>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Metadata<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub entry: Cow<'a, str>,
+    /// The value as it was sent, which may be binary, or `None` for NIL.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub value: Option<Cow<'a, [u8]>>,
+}
+
+/// A mailbox name as it is sent on the wire: modified UTF-7, or UTF-8 if
+/// `UTF8=ACCEPT` is enabled (RFC 3501, section 5.1).
+///
+/// Comparisons treat `INBOX` case-insensitively, as it is a special name
+/// that always refers to the user's primary mailbox. All other names are
+/// compared exactly.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MailboxName<'a>(#[cfg_attr(feature = "serde", serde(borrow))] Cow<'a, str>);
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MailboxDatum<'a> {
     Exists(u32),
-    Flags(Vec<&'a str>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Flags(Vec<Flag<'a>>),
     List {
-        flags: Vec<&'a str>,
-        delimiter: Option<&'a str>,
-        name: &'a str,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        flags: Vec<NameAttribute<'a>>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        delimiter: Option<Cow<'a, str>>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        name: MailboxName<'a>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        extended_data: Vec<ListExtendedItem<'a>>, // RFC 5258, section 3.5
     },
     Status {
-        mailbox: &'a str,
-        status: Vec<StatusAttribute>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        mailbox: MailboxName<'a>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        status: Vec<StatusAttribute<'a>>,
     },
     Recent(u32),
     MetadataSolicited {
-        mailbox: &'a str,
-        values: Vec<Metadata>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        mailbox: Cow<'a, str>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        values: Vec<Metadata<'a>>,
     },
     MetadataUnsolicited {
-        mailbox: &'a str,
-        values: Vec<&'a str>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        mailbox: Cow<'a, str>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        values: Vec<Cow<'a, str>>,
     },
 }
 
-#[derive(Debug, Eq, PartialEq, Hash)]
+impl<'a> MailboxDatum<'a> {
+    /// Returns the name of a LIST or LSUB mailbox, decoded from modified
+    /// UTF-7. Returns `None` for other mailbox data, or if the name is not
+    /// validly encoded.
+    pub fn decoded_name(&self) -> Option<String> {
+        match self {
+            MailboxDatum::List { name, .. } => name.decoded(),
+            _ => None,
+        }
+    }
+
+    /// Returns the previous name of a renamed LIST mailbox, as reported by
+    /// the `OLDNAME` extended data item (RFC 9051, section 7.3.1).
+    pub fn old_name(&self) -> Option<&str> {
+        let extended_data = match self {
+            MailboxDatum::List { extended_data, .. } => extended_data,
+            _ => return None,
+        };
+        extended_data
+            .iter()
+            .filter(|item| item.tag.eq_ignore_ascii_case("OLDNAME"))
+            .find_map(|item| match &item.value {
+                TaggedExtVal::Comp(comp) => match comp.as_slice() {
+                    [TaggedExtComp::Astring(name)] => Some(name.as_ref()),
+                    _ => None,
+                },
+                _ => None,
+            })
+    }
+}
+
+// RFC 3501, section 7.2.2, extended by RFC 5258 and RFC 6154
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NameAttribute<'a> {
+    NoInferiors,
+    NoSelect,
+    Marked,
+    Unmarked,
+    HasChildren,   // RFC 5258, section 4
+    HasNoChildren, // RFC 5258, section 4
+    NonExistent,   // RFC 5258, section 3.4
+    Subscribed,    // RFC 5258, section 3.4
+    Remote,        // RFC 5258, section 3.4
+    All,           // RFC 6154, section 2
+    Archive,       // RFC 6154, section 2
+    Drafts,        // RFC 6154, section 2
+    Flagged,       // RFC 6154, section 2
+    Junk,          // RFC 6154, section 2
+    Sent,          // RFC 6154, section 2
+    Trash,         // RFC 6154, section 2
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Extension(Cow<'a, str>),
+}
+
+// RFC 5257, section 3.4
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Annotation<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub entry: Cow<'a, str>,
+    /// Attribute names and values; empty if only the entry name was returned.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub attributes: Vec<AnnotationAttribute<'a>>,
+}
+
+// RFC 5258, section 6
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ListExtendedItem<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub tag: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub value: TaggedExtVal<'a>,
+}
+
+// RFC 4466, section 2.1
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TaggedExtVal<'a> {
+    Number(u64),
+    SequenceSet(SequenceSet),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Comp(Vec<TaggedExtComp<'a>>),
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TaggedExtComp<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Astring(Cow<'a, str>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    List(Vec<TaggedExtComp<'a>>),
+}
+
+// RFC 3501, section 2.3.2
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Flag<'a> {
+    Seen,
+    Answered,
+    Flagged,
+    Deleted,
+    Draft,
+    Recent,
+    /// `\*` in PERMANENTFLAGS: new keywords can be created.
+    MayCreate,
+    /// A keyword, without a leading backslash.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Keyword(Cow<'a, str>),
+    /// A flag extension other than the system flags, including the backslash.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Extension(Cow<'a, str>),
+}
+
+impl<'a> Flag<'a> {
+    /// Maps a backslash-prefixed flag to a system flag, or `Flag::Extension`
+    /// if it is not one of the flags defined by RFC 3501.
+    pub fn system(s: impl Into<Cow<'a, str>>) -> Self {
+        let s = s.into();
+        match s.to_ascii_lowercase().as_str() {
+            "\\seen" => Flag::Seen,
+            "\\answered" => Flag::Answered,
+            "\\flagged" => Flag::Flagged,
+            "\\deleted" => Flag::Deleted,
+            "\\draft" => Flag::Draft,
+            "\\recent" => Flag::Recent,
+            "\\*" => Flag::MayCreate,
+            _ => Flag::Extension(s),
+        }
+    }
+
+    /// The flag as it is written on the wire, e.g. for a STORE command.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Flag::Seen => "\\Seen",
+            Flag::Answered => "\\Answered",
+            Flag::Flagged => "\\Flagged",
+            Flag::Deleted => "\\Deleted",
+            Flag::Draft => "\\Draft",
+            Flag::Recent => "\\Recent",
+            Flag::MayCreate => "\\*",
+            Flag::Keyword(s) | Flag::Extension(s) => s,
+        }
+    }
+
+    /// Whether the flag can be sent in a flag list: keywords must be atoms,
+    /// and extensions a backslash followed by an atom. `\*` is only ever
+    /// sent by the server.
+    pub(crate) fn is_valid(&self) -> bool {
+        use crate::builders::encode::is_atom;
+        match self {
+            Flag::MayCreate => false,
+            Flag::Keyword(s) => is_atom(s.as_bytes()),
+            Flag::Extension(s) => s.starts_with('\\') && is_atom(s[1..].as_bytes()),
+            _ => true,
+        }
+    }
+}
+
+/// A set of flags, such as those of a message. Flags are compared ignoring
+/// case, as IMAP does, and iterate in the order they were inserted.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FlagSet<'a>(#[cfg_attr(feature = "serde", serde(borrow))] Vec<Flag<'a>>);
+
+/// The changes that turn one `FlagSet` into another, as returned by
+/// `FlagSet::diff`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FlagDiff<'a> {
+    pub add: FlagSet<'a>,
+    pub remove: FlagSet<'a>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Capability<'a> {
     Imap4rev1,
-    Auth(&'a str),
-    Atom(&'a str),
+    Imap4rev2, // RFC 9051
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Auth(Cow<'a, str>),
+    StartTls,
+    LoginDisabled,
+    Idle,                     // RFC 2177
+    Namespace,                // RFC 2342
+    Id,                       // RFC 2971
+    Enable,                   // RFC 5161
+    Condstore,                // RFC 7162
+    Qresync,                  // RFC 7162
+    UidPlus,                  // RFC 4315
+    Move,                     // RFC 6851
+    Unselect,                 // RFC 3691
+    Children,                 // RFC 3348
+    LiteralPlus,              // RFC 7888
+    LiteralMinus,             // RFC 7888
+    Binary,                   // RFC 3516
+    SpecialUse,               // RFC 6154
+    ListExtended,             // RFC 5258
+    Language,                 // RFC 5255
+    AppendLimit(Option<u64>), // RFC 7889
+    Replace,                  // RFC 8508
+    LoginReferrals,           // RFC 2221
+    MailboxReferrals,         // RFC 2193
+    ClientId,                 // draft-yu-imap-client-id
+    UidOnly,                  // RFC 9586
+    Partial,                  // RFC 9394
+    MultiAppend,              // RFC 3502
+    Catenate,                 // RFC 4469
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Compress(Cow<'a, str>), // RFC 4978
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Atom(Cow<'a, str>),
+}
+
+/// Formats the capability as servers advertise it, such as `AUTH=PLAIN`.
+impl fmt::Display for Capability<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Capability::Imap4rev1 => "IMAP4rev1",
+            Capability::Imap4rev2 => "IMAP4rev2",
+            Capability::Auth(mech) => return write!(f, "AUTH={}", mech),
+            Capability::StartTls => "STARTTLS",
+            Capability::LoginDisabled => "LOGINDISABLED",
+            Capability::Idle => "IDLE",
+            Capability::Namespace => "NAMESPACE",
+            Capability::Id => "ID",
+            Capability::Enable => "ENABLE",
+            Capability::Condstore => "CONDSTORE",
+            Capability::Qresync => "QRESYNC",
+            Capability::UidPlus => "UIDPLUS",
+            Capability::Move => "MOVE",
+            Capability::Unselect => "UNSELECT",
+            Capability::Children => "CHILDREN",
+            Capability::LiteralPlus => "LITERAL+",
+            Capability::LiteralMinus => "LITERAL-",
+            Capability::Binary => "BINARY",
+            Capability::SpecialUse => "SPECIAL-USE",
+            Capability::ListExtended => "LIST-EXTENDED",
+            Capability::Language => "LANGUAGE",
+            Capability::AppendLimit(Some(limit)) => return write!(f, "APPENDLIMIT={}", limit),
+            Capability::AppendLimit(None) => "APPENDLIMIT",
+            Capability::Replace => "REPLACE",
+            Capability::LoginReferrals => "LOGIN-REFERRALS",
+            Capability::MailboxReferrals => "MAILBOX-REFERRALS",
+            Capability::ClientId => "CLIENTID",
+            Capability::UidOnly => "UIDONLY",
+            Capability::Partial => "PARTIAL",
+            Capability::MultiAppend => "MULTIAPPEND",
+            Capability::Catenate => "CATENATE",
+            Capability::Compress(algorithm) => return write!(f, "COMPRESS={}", algorithm),
+            Capability::Atom(atom) => atom,
+        };
+        f.write_str(name)
+    }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Attribute {
     Body,
+    BodyStructure,
     Envelope,
     Flags,
     InternalDate,
@@ -115,142 +1161,546 @@ pub enum Attribute {
     Rfc822Size,
     Rfc822Text,
     Uid,
+    GmailLabels,
+    GmailMsgId,
+    GmailThrId,
+    EmailId,  // RFC 8474, section 5.1
+    ThreadId, // RFC 8474, section 5.2
+    Preview,  // RFC 8970, section 3
+    SaveDate, // RFC 8514, section 4.3
+}
+
+impl Attribute {
+    /// The data item as it is written in a FETCH command.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Attribute::Body => "BODY",
+            Attribute::BodyStructure => "BODYSTRUCTURE",
+            Attribute::Envelope => "ENVELOPE",
+            Attribute::Flags => "FLAGS",
+            Attribute::InternalDate => "INTERNALDATE",
+            Attribute::ModSeq => "MODSEQ",
+            Attribute::Rfc822 => "RFC822",
+            Attribute::Rfc822Size => "RFC822.SIZE",
+            Attribute::Rfc822Text => "RFC822.TEXT",
+            Attribute::Uid => "UID",
+            Attribute::GmailLabels => "X-GM-LABELS",
+            Attribute::GmailMsgId => "X-GM-MSGID",
+            Attribute::GmailThrId => "X-GM-THRID",
+            Attribute::EmailId => "EMAILID",
+            Attribute::ThreadId => "THREADID",
+            Attribute::Preview => "PREVIEW",
+            Attribute::SaveDate => "SAVEDATE",
+        }
+    }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-pub enum MessageSection {
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MessageSection<'a> {
     Header,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    HeaderFields(Vec<Cow<'a, str>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    HeaderFieldsNot(Vec<Cow<'a, str>>),
     Mime,
     Text,
 }
 
-#[derive(Debug, Eq, PartialEq)]
-pub enum SectionPath {
-    Full(MessageSection),
-    Part(Vec<u32>, Option<MessageSection>),
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SectionPath<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Full(MessageSection<'a>),
+    Part(
+        Vec<u32>,
+        #[cfg_attr(feature = "serde", serde(borrow))] Option<MessageSection<'a>>,
+    ),
 }
 
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AttributeValue<'a> {
     BodySection {
-        section: Option<SectionPath>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        section: Option<SectionPath<'a>>,
         index: Option<u32>,
-        data: Option<&'a [u8]>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        data: Option<Cow<'a, [u8]>>,
     },
+    #[cfg_attr(feature = "serde", serde(borrow))]
     BodyStructure(BodyStructure<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Envelope(Box<Envelope<'a>>),
-    Flags(Vec<&'a str>),
-    InternalDate(&'a str),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Flags(Vec<Flag<'a>>),
+    InternalDate(DateTime),
     ModSeq(u64), // RFC 4551, section 3.3.2
-    Rfc822(Option<&'a [u8]>),
-    Rfc822Header(Option<&'a [u8]>),
-    Rfc822Size(u32),
-    Rfc822Text(Option<&'a [u8]>),
-    Uid(u32),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Rfc822(Option<Cow<'a, [u8]>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Rfc822Header(Option<Cow<'a, [u8]>>),
+    Rfc822Size(u64), // RFC 9051, section 7.5.2
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Rfc822Text(Option<Cow<'a, [u8]>>),
+    Uid(Uid),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    GmailLabels(Vec<Cow<'a, str>>),
+    GmailMsgId(u64),
+    GmailThrId(u64),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    EmailId(Cow<'a, str>), // RFC 8474, section 5.1
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    ThreadId(Option<Cow<'a, str>>), // RFC 8474, section 5.2
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Preview(Option<Cow<'a, str>>), // RFC 8970, section 3
+    SaveDate(Option<DateTime>), // RFC 8514, section 4.3
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Annotation(Vec<Annotation<'a>>), // RFC 5257, section 3.4
+    // RFC 3516, section 4.2
+    Binary {
+        section: Vec<u32>,
+        index: Option<u32>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        data: Option<Cow<'a, [u8]>>,
+    },
+    // RFC 3516, section 4.2
+    BinarySize {
+        section: Vec<u32>,
+        size: u32,
+    },
 }
 
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BodyStructure<'a> {
     Basic {
+        #[cfg_attr(feature = "serde", serde(borrow))]
         common: BodyContentCommon<'a>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
         other: BodyContentSinglePart<'a>,
-        extension: Option<BodyExtension<'a>>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        extensions: Vec<BodyExtension<'a>>,
     },
     Text {
+        #[cfg_attr(feature = "serde", serde(borrow))]
         common: BodyContentCommon<'a>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
         other: BodyContentSinglePart<'a>,
         lines: u32,
-        extension: Option<BodyExtension<'a>>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        extensions: Vec<BodyExtension<'a>>,
     },
     Message {
+        #[cfg_attr(feature = "serde", serde(borrow))]
         common: BodyContentCommon<'a>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
         other: BodyContentSinglePart<'a>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
         envelope: Envelope<'a>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
         body: Box<BodyStructure<'a>>,
         lines: u32,
-        extension: Option<BodyExtension<'a>>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        extensions: Vec<BodyExtension<'a>>,
     },
     Multipart {
+        #[cfg_attr(feature = "serde", serde(borrow))]
         common: BodyContentCommon<'a>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
         bodies: Vec<BodyStructure<'a>>,
-        extension: Option<BodyExtension<'a>>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        extensions: Vec<BodyExtension<'a>>,
     },
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BodyContentCommon<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub ty: ContentType<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub disposition: Option<ContentDisposition<'a>>,
-    pub language: Option<Vec<&'a str>>,
-    pub location: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub language: Option<Vec<Cow<'a, str>>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub location: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BodyContentSinglePart<'a> {
-    pub id: Option<&'a str>,
-    pub md5: Option<&'a str>,
-    pub description: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub id: Option<Cow<'a, str>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub md5: Option<Cow<'a, str>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub description: Option<Cow<'a, str>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub transfer_encoding: ContentEncoding<'a>,
     pub octets: u32,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ContentType<'a> {
-    pub ty: &'a str,
-    pub subtype: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub ty: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub subtype: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub params: BodyParams<'a>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ContentDisposition<'a> {
-    pub ty: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub ty: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub params: BodyParams<'a>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ContentEncoding<'a> {
     SevenBit,
     EightBit,
     Binary,
     Base64,
     QuotedPrintable,
-    Other(&'a str),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Other(Cow<'a, str>),
 }
 
-#[derive(Debug, Eq, PartialEq)]
+/// 7BIT is the default when no Content-Transfer-Encoding is given (RFC 2045).
+impl Default for ContentEncoding<'_> {
+    fn default() -> Self {
+        ContentEncoding::SevenBit
+    }
+}
+
+/// Extension data that follows the location of a body, reserved for future
+/// extensions to BODYSTRUCTURE (RFC 3501, section 7.4.2).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BodyExtension<'a> {
     Num(u32),
-    Str(Option<&'a str>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Str(Option<Cow<'a, str>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     List(Vec<BodyExtension<'a>>),
 }
 
-pub type BodyParams<'a> = Option<Vec<(&'a str, &'a str)>>;
+/// The parameters of a content type or disposition. The values are kept as
+/// they were sent, since some servers send file names in Latin-1 or other
+/// charsets; the `param` accessors decode them.
+pub type BodyParams<'a> = Option<Vec<(Cow<'a, str>, Cow<'a, [u8]>)>>;
+
+/// A URL and the data it resolved to, or `None` if the server could not fetch it.
+pub type UrlFetchData<'a> = (Cow<'a, str>, Option<Cow<'a, [u8]>>);
 
-#[derive(Debug, Eq, PartialEq)]
+/// An annotation attribute name and its value, or `None` if the value is NIL.
+pub type AnnotationAttribute<'a> = (Cow<'a, str>, Option<Cow<'a, [u8]>>);
+
+/// A message sequence number (RFC 3501, section 2.3.1.2).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Seq(pub u32);
+
+/// A unique identifier of a message (RFC 3501, section 2.3.1.1). Unlike
+/// sequence numbers, UIDs don't change when other messages are expunged.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Uid(pub u32);
+
+/// A message sequence number or UID in a sequence set.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SeqNumber {
+    Value(u32),
+    /// `*`, the largest number in use in the mailbox.
+    Largest,
+}
+
+impl SeqNumber {
+    /// Resolves `*` to the given largest number in use.
+    pub fn resolve(self, largest: u32) -> u32 {
+        match self {
+            SeqNumber::Value(n) => n,
+            SeqNumber::Largest => largest,
+        }
+    }
+}
+
+impl From<u32> for SeqNumber {
+    fn from(n: u32) -> Self {
+        SeqNumber::Value(n)
+    }
+}
+
+/// An inclusive range in a sequence set, with `start <= end`.
+/// Single numbers are represented as a range of length one.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SeqRange {
+    pub start: SeqNumber,
+    pub end: SeqNumber,
+}
+
+impl SeqRange {
+    /// Creates a range from two endpoints in any order, as allowed for
+    /// `seq-range` by RFC 3501.
+    pub fn new(a: SeqNumber, b: SeqNumber) -> Self {
+        SeqRange {
+            start: a.min(b),
+            end: a.max(b),
+        }
+    }
+
+    /// Resolves `*` to the given largest number in use. Since `*` may be
+    /// smaller than the other endpoint, the result is normalized again.
+    pub fn to_range(self, largest: u32) -> RangeInclusive<u32> {
+        let (a, b) = (self.start.resolve(largest), self.end.resolve(largest));
+        a.min(b)..=a.max(b)
+    }
+}
+
+impl From<RangeInclusive<u32>> for SeqRange {
+    fn from(range: RangeInclusive<u32>) -> Self {
+        let (start, end) = range.into_inner();
+        SeqRange::new(start.into(), end.into())
+    }
+}
+
+/// A set of message sequence numbers or UIDs (RFC 3501, section 9).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SequenceSet {
+    /// A list of inclusive ranges in the order they were sent.
+    Set(Vec<SeqRange>),
+    /// `$`, the result of the last search saved with SEARCHRES (RFC 5182).
+    LastResult,
+}
+
+impl SequenceSet {
+    /// The ranges in this set; empty for `$`, which can only be resolved by
+    /// the server.
+    pub fn ranges(&self) -> &[SeqRange] {
+        match self {
+            SequenceSet::Set(ranges) => ranges,
+            SequenceSet::LastResult => &[],
+        }
+    }
+}
+
+impl Default for SequenceSet {
+    fn default() -> Self {
+        SequenceSet::Set(vec![])
+    }
+}
+
+impl From<Vec<RangeInclusive<u32>>> for SequenceSet {
+    fn from(ranges: Vec<RangeInclusive<u32>>) -> Self {
+        SequenceSet::Set(ranges.into_iter().map(SeqRange::from).collect())
+    }
+}
+
+/// A date-time value as used by INTERNALDATE (RFC 3501, section 9).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Offset from UTC in minutes east of Greenwich.
+    pub offset: i16,
+}
+
+impl DateTime {
+    /// The date in the time zone of the date-time.
+    pub fn date(&self) -> Date {
+        Date {
+            year: self.year,
+            month: self.month,
+            day: self.day,
+        }
+    }
+
+    /// Parses a date-time such as `17-Jul-1996 02:44:25 -0700`, without the
+    /// surrounding quotes.
+    pub fn parse(s: &str) -> Option<Self> {
+        match crate::parser::rfc3501::date_time::date_time(s.as_bytes()) {
+            Ok(([], dt)) => Some(dt),
+            _ => None,
+        }
+    }
+
+    /// Parses a date-time in the format of the RFC 5322 Date header, such
+    /// as `Wed, 17 Jul 1996 02:44:25 -0700`, including the obsolete forms
+    /// with two-digit years and named zones.
+    pub fn parse_rfc5322(s: &str) -> Option<Self> {
+        match crate::parser::rfc5322::date_time(s.as_bytes()) {
+            Ok(([], dt)) => Some(dt),
+            _ => None,
+        }
+    }
+
+    /// The number of seconds since the Unix epoch, for comparing dates with
+    /// different offsets. Leap seconds are counted as the next second.
+    pub fn timestamp(&self) -> i64 {
+        // Days from 1970-01-01 to the date, with years starting in March so
+        // that leap days come last (from Howard Hinnant's date algorithms).
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146_097 + doe - 719_468;
+
+        let seconds =
+            i64::from(self.hour) * 3600 + i64::from(self.minute) * 60 + i64::from(self.second);
+        days * 86_400 + seconds - i64::from(self.offset) * 60
+    }
+
+    /// Converts the date-time to a chrono date-time with the same offset.
+    /// Returns `None` for dates that don't exist, such as `31-Feb-2024`.
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        use chrono::TimeZone;
+        let offset = chrono::FixedOffset::east_opt(i32::from(self.offset) * 60)?;
+        // chrono represents a leap second as the 59th second plus one more.
+        let (second, milli) = match self.second {
+            60 => (59, 1000),
+            second => (u32::from(second), 0),
+        };
+        let date = chrono::NaiveDate::from_ymd_opt(
+            i32::from(self.year),
+            u32::from(self.month),
+            u32::from(self.day),
+        )?;
+        let time =
+            date.and_hms_milli_opt(u32::from(self.hour), u32::from(self.minute), second, milli)?;
+        offset.from_local_datetime(&time).single()
+    }
+}
+
+/// Formats the date-time as INTERNALDATE does, such as
+/// `17-Jul-1996 02:44:25 -0700`, without the surrounding quotes.
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let offset = self.offset.unsigned_abs();
+        write!(
+            f,
+            "{:2}-{}-{:04} {:02}:{:02}:{:02} {}{:02}{:02}",
+            self.day,
+            month_name(self.month),
+            self.year,
+            self.hour,
+            self.minute,
+            self.second,
+            sign,
+            offset / 60,
+            offset % 60
+        )
+    }
+}
+
+/// A date without a time, as used by the date search keys, written like
+/// `1-Feb-1994` (RFC 3501, section 9).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+// date-text = date-day "-" date-month "-" date-year
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}-{}", self.day, month_name(self.month), self.year)
+    }
+}
+
+fn month_name(month: u8) -> &'static str {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    MONTHS
+        .get(usize::from(month).wrapping_sub(1))
+        .unwrap_or(&"???")
+}
+
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Envelope<'a> {
-    pub date: Option<&'a [u8]>,
-    pub subject: Option<&'a [u8]>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub date: Option<Cow<'a, [u8]>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub subject: Option<Cow<'a, [u8]>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub from: Option<Vec<Address<'a>>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub sender: Option<Vec<Address<'a>>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub reply_to: Option<Vec<Address<'a>>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub to: Option<Vec<Address<'a>>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub cc: Option<Vec<Address<'a>>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub bcc: Option<Vec<Address<'a>>>,
-    pub in_reply_to: Option<&'a [u8]>,
-    pub message_id: Option<&'a [u8]>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub in_reply_to: Option<Cow<'a, [u8]>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub message_id: Option<Cow<'a, [u8]>>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Address<'a> {
-    pub name: Option<&'a [u8]>,
-    pub adl: Option<&'a [u8]>,
-    pub mailbox: Option<&'a [u8]>,
-    pub host: Option<&'a [u8]>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub name: Option<Cow<'a, [u8]>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub adl: Option<Cow<'a, [u8]>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub mailbox: Option<Cow<'a, [u8]>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub host: Option<Cow<'a, [u8]>>,
+}
+
+impl<'a> Address<'a> {
+    /// RFC 2822 group syntax is indicated by a special address with a NIL host.
+    /// The start of a group has the group name in the mailbox field.
+    pub fn is_group_start(&self) -> bool {
+        self.host.is_none() && self.mailbox.is_some()
+    }
+
+    /// The end of a group is indicated by an address with both mailbox and host NIL.
+    pub fn is_group_end(&self) -> bool {
+        self.host.is_none() && self.mailbox.is_none()
+    }
+
+    /// Returns the group name if this address marks the start of a group.
+    pub fn group_name(&self) -> Option<&[u8]> {
+        if self.is_group_start() {
+            self.mailbox.as_deref()
+        } else {
+            None
+        }
+    }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RequestId(pub String);
 
 impl RequestId {
@@ -259,7 +1709,7 @@ impl RequestId {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum State {
     NotAuthenticated,
     Authenticated,
@@ -271,24 +1721,24 @@ pub enum State {
 
 pub struct BodyFields<'a> {
     pub param: BodyParams<'a>,
-    pub id: Option<&'a str>,
-    pub description: Option<&'a str>,
+    pub id: Option<Cow<'a, str>>,
+    pub description: Option<Cow<'a, str>>,
     pub transfer_encoding: ContentEncoding<'a>,
     pub octets: u32,
 }
 
 pub struct BodyExt1Part<'a> {
-    pub md5: Option<&'a str>,
+    pub md5: Option<Cow<'a, str>>,
     pub disposition: Option<ContentDisposition<'a>>,
-    pub language: Option<Vec<&'a str>>,
-    pub location: Option<&'a str>,
-    pub extension: Option<BodyExtension<'a>>,
+    pub language: Option<Vec<Cow<'a, str>>>,
+    pub location: Option<Cow<'a, str>>,
+    pub extensions: Vec<BodyExtension<'a>>,
 }
 
 pub struct BodyExtMPart<'a> {
     pub param: BodyParams<'a>,
     pub disposition: Option<ContentDisposition<'a>>,
-    pub language: Option<Vec<&'a str>>,
-    pub location: Option<&'a str>,
-    pub extension: Option<BodyExtension<'a>>,
+    pub language: Option<Vec<Cow<'a, str>>>,
+    pub location: Option<Cow<'a, str>>,
+    pub extensions: Vec<BodyExtension<'a>>,
 }
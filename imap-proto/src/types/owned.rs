@@ -0,0 +1,840 @@
+// Conversions of borrowed responses into owned ones, which no longer depend
+// on the buffer they were parsed from.
+
+use std::borrow::Cow;
+
+use super::*;
+
+fn owned<T: ?Sized + ToOwned + 'static>(s: Cow<'_, T>) -> Cow<'static, T> {
+    Cow::Owned(s.into_owned())
+}
+
+fn owned_opt<T: ?Sized + ToOwned + 'static>(s: Option<Cow<'_, T>>) -> Option<Cow<'static, T>> {
+    s.map(owned)
+}
+
+fn owned_vec(v: Vec<Cow<'_, str>>) -> Vec<Cow<'static, str>> {
+    v.into_iter().map(owned).collect()
+}
+
+fn owned_params(params: BodyParams<'_>) -> BodyParams<'static> {
+    params.map(|params| {
+        params
+            .into_iter()
+            .map(|(key, val)| (owned(key), owned(val)))
+            .collect()
+    })
+}
+
+impl<'a> Response<'a> {
+    /// Copies all borrowed data, so that the response can outlive the input
+    /// it was parsed from.
+    pub fn into_owned(self) -> Response<'static> {
+        match self {
+            Response::Capabilities(caps) => {
+                Response::Capabilities(caps.into_iter().map(Capability::into_owned).collect())
+            }
+            Response::Enabled(caps) => {
+                Response::Enabled(caps.into_iter().map(Capability::into_owned).collect())
+            }
+            Response::Continue(req) => Response::Continue(req.into_owned()),
+            Response::Done {
+                tag,
+                status,
+                code,
+                information,
+            } => Response::Done {
+                tag,
+                status,
+                code: code.map(ResponseCode::into_owned),
+                information: owned_opt(information),
+            },
+            Response::Data {
+                status,
+                code,
+                information,
+            } => Response::Data {
+                status,
+                code: code.map(ResponseCode::into_owned),
+                information: owned_opt(information),
+            },
+            Response::Expunge(seq) => Response::Expunge(seq),
+            Response::Fetch(seq, attrs) => Response::Fetch(
+                seq,
+                attrs.into_iter().map(AttributeValue::into_owned).collect(),
+            ),
+            Response::UidFetch(uid, attrs) => Response::UidFetch(
+                uid,
+                attrs.into_iter().map(AttributeValue::into_owned).collect(),
+            ),
+            Response::MailboxData(datum) => Response::MailboxData(datum.into_owned()),
+            Response::IDs(ids) => Response::IDs(ids),
+            Response::Sort(ids) => Response::Sort(ids),
+            Response::Thread(threads) => Response::Thread(threads),
+            Response::ESearch(esearch) => Response::ESearch(esearch.into_owned()),
+            Response::Id(params) => Response::Id(params.map(|params| {
+                params
+                    .into_iter()
+                    .map(|(key, val)| (owned(key), owned_opt(val)))
+                    .collect()
+            })),
+            Response::Quota(quota) => Response::Quota(quota.into_owned()),
+            Response::QuotaRoot(root) => Response::QuotaRoot(root.into_owned()),
+            Response::Acl(acl) => Response::Acl(acl.into_owned()),
+            Response::ListRights(rights) => Response::ListRights(rights.into_owned()),
+            Response::MyRights(rights) => Response::MyRights(rights.into_owned()),
+            Response::Namespace {
+                personal,
+                other,
+                shared,
+            } => {
+                let owned_namespaces = |namespaces: Option<Vec<NamespaceDescription<'_>>>| {
+                    namespaces.map(|namespaces| {
+                        namespaces
+                            .into_iter()
+                            .map(NamespaceDescription::into_owned)
+                            .collect()
+                    })
+                };
+                Response::Namespace {
+                    personal: owned_namespaces(personal),
+                    other: owned_namespaces(other),
+                    shared: owned_namespaces(shared),
+                }
+            }
+            Response::Vanished { earlier, uids } => Response::Vanished { earlier, uids },
+            Response::Language(tags) => Response::Language(owned_vec(tags)),
+            Response::GenUrlAuth(urls) => Response::GenUrlAuth(owned_vec(urls)),
+            Response::UrlFetch(data) => Response::UrlFetch(
+                data.into_iter()
+                    .map(|(url, data)| (owned(url), owned_opt(data)))
+                    .collect(),
+            ),
+            Response::Annotation {
+                mailbox,
+                annotation,
+            } => Response::Annotation {
+                mailbox: owned(mailbox),
+                annotation: annotation.into_owned(),
+            },
+        }
+    }
+}
+
+impl<'a> MailboxUpdate<'a> {
+    pub fn into_owned(self) -> MailboxUpdate<'static> {
+        match self {
+            MailboxUpdate::Exists(n) => MailboxUpdate::Exists(n),
+            MailboxUpdate::Recent(n) => MailboxUpdate::Recent(n),
+            MailboxUpdate::Expunge(n) => MailboxUpdate::Expunge(n),
+            MailboxUpdate::Flags {
+                seq,
+                uid,
+                mod_seq,
+                flags,
+            } => MailboxUpdate::Flags {
+                seq,
+                uid,
+                mod_seq,
+                flags: flags.into_iter().map(Flag::into_owned).collect(),
+            },
+            MailboxUpdate::Vanished(uids) => MailboxUpdate::Vanished(uids),
+            MailboxUpdate::UidFlags {
+                uid,
+                mod_seq,
+                flags,
+            } => MailboxUpdate::UidFlags {
+                uid,
+                mod_seq,
+                flags: flags.into_iter().map(Flag::into_owned).collect(),
+            },
+        }
+    }
+}
+
+impl<'a> ContinueRequest<'a> {
+    pub fn into_owned(self) -> ContinueRequest<'static> {
+        ContinueRequest {
+            code: self.code.map(ResponseCode::into_owned),
+            information: owned_opt(self.information),
+        }
+    }
+}
+
+impl<'a> Greeting<'a> {
+    pub fn into_owned(self) -> Greeting<'static> {
+        Greeting {
+            status: self.status,
+            code: self.code.map(ResponseCode::into_owned),
+            information: owned_opt(self.information),
+        }
+    }
+}
+
+impl<'a> ResponseCode<'a> {
+    pub fn into_owned(self) -> ResponseCode<'static> {
+        match self {
+            ResponseCode::Alert => ResponseCode::Alert,
+            ResponseCode::AppendUid(uid_validity, uids) => {
+                ResponseCode::AppendUid(uid_validity, uids)
+            }
+            ResponseCode::CopyUid(uid_validity, source, dest) => {
+                ResponseCode::CopyUid(uid_validity, source, dest)
+            }
+            ResponseCode::UidNotSticky => ResponseCode::UidNotSticky,
+            ResponseCode::OverQuota => ResponseCode::OverQuota,
+            ResponseCode::UidRequired => ResponseCode::UidRequired,
+            ResponseCode::CompressionActive => ResponseCode::CompressionActive,
+            ResponseCode::UrlMech(mechs) => ResponseCode::UrlMech(
+                mechs
+                    .into_iter()
+                    .map(|(mech, data)| (owned(mech), owned_opt(data)))
+                    .collect(),
+            ),
+            ResponseCode::TooBig => ResponseCode::TooBig,
+            ResponseCode::BadUrl(url) => ResponseCode::BadUrl(owned(url)),
+            ResponseCode::Referral(urls) => ResponseCode::Referral(owned_vec(urls)),
+            ResponseCode::BadCharset(charsets) => ResponseCode::BadCharset(charsets.map(owned_vec)),
+            ResponseCode::Capabilities(caps) => {
+                ResponseCode::Capabilities(caps.into_iter().map(Capability::into_owned).collect())
+            }
+            ResponseCode::HighestModSeq(n) => ResponseCode::HighestModSeq(n),
+            ResponseCode::Modified(uids) => ResponseCode::Modified(uids),
+            ResponseCode::NoModSeq => ResponseCode::NoModSeq,
+            ResponseCode::MetadataLongEntries(n) => ResponseCode::MetadataLongEntries(n),
+            ResponseCode::MetadataMaxSize(n) => ResponseCode::MetadataMaxSize(n),
+            ResponseCode::MetadataTooMany => ResponseCode::MetadataTooMany,
+            ResponseCode::MetadataNoPrivate => ResponseCode::MetadataNoPrivate,
+            ResponseCode::MailboxId(id) => ResponseCode::MailboxId(owned(id)),
+            ResponseCode::NotificationOverflow => ResponseCode::NotificationOverflow,
+            ResponseCode::BadEvent(events) => ResponseCode::BadEvent(owned_vec(events)),
+            ResponseCode::Parse => ResponseCode::Parse,
+            ResponseCode::PermanentFlags(flags) => {
+                ResponseCode::PermanentFlags(flags.into_iter().map(Flag::into_owned).collect())
+            }
+            ResponseCode::ReadOnly => ResponseCode::ReadOnly,
+            ResponseCode::ReadWrite => ResponseCode::ReadWrite,
+            ResponseCode::TryCreate => ResponseCode::TryCreate,
+            ResponseCode::UidNext(n) => ResponseCode::UidNext(n),
+            ResponseCode::UidValidity(n) => ResponseCode::UidValidity(n),
+            ResponseCode::Unseen(n) => ResponseCode::Unseen(n),
+            ResponseCode::Other(name, value) => ResponseCode::Other(owned(name), owned_opt(value)),
+        }
+    }
+}
+
+impl<'a> StatusAttribute<'a> {
+    pub fn into_owned(self) -> StatusAttribute<'static> {
+        match self {
+            StatusAttribute::HighestModSeq(n) => StatusAttribute::HighestModSeq(n),
+            StatusAttribute::MailboxId(id) => StatusAttribute::MailboxId(owned(id)),
+            StatusAttribute::Size(n) => StatusAttribute::Size(n),
+            StatusAttribute::AppendLimit(n) => StatusAttribute::AppendLimit(n),
+            StatusAttribute::Deleted(n) => StatusAttribute::Deleted(n),
+            StatusAttribute::DeletedStorage(n) => StatusAttribute::DeletedStorage(n),
+            StatusAttribute::Messages(n) => StatusAttribute::Messages(n),
+            StatusAttribute::Recent(n) => StatusAttribute::Recent(n),
+            StatusAttribute::UidNext(n) => StatusAttribute::UidNext(n),
+            StatusAttribute::UidValidity(n) => StatusAttribute::UidValidity(n),
+            StatusAttribute::Unseen(n) => StatusAttribute::Unseen(n),
+        }
+    }
+}
+
+impl<'a> ESearch<'a> {
+    pub fn into_owned(self) -> ESearch<'static> {
+        ESearch {
+            tag: owned_opt(self.tag),
+            mailbox: owned_opt(self.mailbox),
+            uid_validity: self.uid_validity,
+            uid: self.uid,
+            data: self.data,
+        }
+    }
+}
+
+impl<'a> Quota<'a> {
+    pub fn into_owned(self) -> Quota<'static> {
+        Quota {
+            root_name: owned(self.root_name),
+            resources: self
+                .resources
+                .into_iter()
+                .map(QuotaResource::into_owned)
+                .collect(),
+        }
+    }
+}
+
+impl<'a> QuotaResource<'a> {
+    pub fn into_owned(self) -> QuotaResource<'static> {
+        QuotaResource {
+            name: self.name.into_owned(),
+            usage: self.usage,
+            limit: self.limit,
+        }
+    }
+}
+
+impl<'a> QuotaResourceName<'a> {
+    pub fn into_owned(self) -> QuotaResourceName<'static> {
+        match self {
+            QuotaResourceName::Storage => QuotaResourceName::Storage,
+            QuotaResourceName::Message => QuotaResourceName::Message,
+            QuotaResourceName::Mailbox => QuotaResourceName::Mailbox,
+            QuotaResourceName::AnnotationStorage => QuotaResourceName::AnnotationStorage,
+            QuotaResourceName::Atom(name) => QuotaResourceName::Atom(owned(name)),
+        }
+    }
+}
+
+impl<'a> QuotaRoot<'a> {
+    pub fn into_owned(self) -> QuotaRoot<'static> {
+        QuotaRoot {
+            mailbox_name: owned(self.mailbox_name),
+            quota_root_names: owned_vec(self.quota_root_names),
+        }
+    }
+}
+
+impl<'a> Acl<'a> {
+    pub fn into_owned(self) -> Acl<'static> {
+        Acl {
+            mailbox: owned(self.mailbox),
+            acls: self.acls.into_iter().map(AclEntry::into_owned).collect(),
+        }
+    }
+}
+
+impl<'a> AclEntry<'a> {
+    pub fn into_owned(self) -> AclEntry<'static> {
+        AclEntry {
+            identifier: owned(self.identifier),
+            rights: self.rights,
+        }
+    }
+}
+
+impl<'a> ListRights<'a> {
+    pub fn into_owned(self) -> ListRights<'static> {
+        ListRights {
+            mailbox: owned(self.mailbox),
+            identifier: owned(self.identifier),
+            required: self.required,
+            optional: self.optional,
+        }
+    }
+}
+
+impl<'a> MyRights<'a> {
+    pub fn into_owned(self) -> MyRights<'static> {
+        MyRights {
+            mailbox: owned(self.mailbox),
+            rights: self.rights,
+        }
+    }
+}
+
+impl<'a> NamespaceDescription<'a> {
+    pub fn into_owned(self) -> NamespaceDescription<'static> {
+        NamespaceDescription {
+            prefix: owned(self.prefix),
+            delimiter: owned_opt(self.delimiter),
+            extensions: self
+                .extensions
+                .into_iter()
+                .map(|(name, values)| (owned(name), owned_vec(values)))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> SearchCriteria<'a> {
+    pub fn into_owned(self) -> SearchCriteria<'static> {
+        match self {
+            SearchCriteria::All => SearchCriteria::All,
+            SearchCriteria::Answered => SearchCriteria::Answered,
+            SearchCriteria::Deleted => SearchCriteria::Deleted,
+            SearchCriteria::Draft => SearchCriteria::Draft,
+            SearchCriteria::Flagged => SearchCriteria::Flagged,
+            SearchCriteria::New => SearchCriteria::New,
+            SearchCriteria::Old => SearchCriteria::Old,
+            SearchCriteria::Recent => SearchCriteria::Recent,
+            SearchCriteria::Seen => SearchCriteria::Seen,
+            SearchCriteria::Unanswered => SearchCriteria::Unanswered,
+            SearchCriteria::Undeleted => SearchCriteria::Undeleted,
+            SearchCriteria::Undraft => SearchCriteria::Undraft,
+            SearchCriteria::Unflagged => SearchCriteria::Unflagged,
+            SearchCriteria::Unseen => SearchCriteria::Unseen,
+            SearchCriteria::Keyword(s) => SearchCriteria::Keyword(owned(s)),
+            SearchCriteria::Unkeyword(s) => SearchCriteria::Unkeyword(owned(s)),
+            SearchCriteria::Bcc(s) => SearchCriteria::Bcc(owned(s)),
+            SearchCriteria::Body(s) => SearchCriteria::Body(owned(s)),
+            SearchCriteria::Cc(s) => SearchCriteria::Cc(owned(s)),
+            SearchCriteria::From(s) => SearchCriteria::From(owned(s)),
+            SearchCriteria::Subject(s) => SearchCriteria::Subject(owned(s)),
+            SearchCriteria::Text(s) => SearchCriteria::Text(owned(s)),
+            SearchCriteria::To(s) => SearchCriteria::To(owned(s)),
+            SearchCriteria::Header(name, value) => {
+                SearchCriteria::Header(owned(name), owned(value))
+            }
+            SearchCriteria::Before(date) => SearchCriteria::Before(date),
+            SearchCriteria::On(date) => SearchCriteria::On(date),
+            SearchCriteria::Since(date) => SearchCriteria::Since(date),
+            SearchCriteria::SentBefore(date) => SearchCriteria::SentBefore(date),
+            SearchCriteria::SentOn(date) => SearchCriteria::SentOn(date),
+            SearchCriteria::SentSince(date) => SearchCriteria::SentSince(date),
+            SearchCriteria::Larger(n) => SearchCriteria::Larger(n),
+            SearchCriteria::Smaller(n) => SearchCriteria::Smaller(n),
+            SearchCriteria::SequenceSet(set) => SearchCriteria::SequenceSet(set),
+            SearchCriteria::Uid(set) => SearchCriteria::Uid(set),
+            SearchCriteria::Not(key) => SearchCriteria::Not(Box::new(key.into_owned())),
+            SearchCriteria::Or(a, b) => {
+                SearchCriteria::Or(Box::new(a.into_owned()), Box::new(b.into_owned()))
+            }
+            SearchCriteria::And(keys) => {
+                SearchCriteria::And(keys.into_iter().map(SearchCriteria::into_owned).collect())
+            }
+        }
+    }
+}
+
+impl<'a> MailboxName<'a> {
+    pub fn into_owned(self) -> MailboxName<'static> {
+        MailboxName(owned(self.0))
+    }
+}
+
+impl<'a> MailboxDatum<'a> {
+    pub fn into_owned(self) -> MailboxDatum<'static> {
+        match self {
+            MailboxDatum::Exists(n) => MailboxDatum::Exists(n),
+            MailboxDatum::Flags(flags) => {
+                MailboxDatum::Flags(flags.into_iter().map(Flag::into_owned).collect())
+            }
+            MailboxDatum::List {
+                flags,
+                delimiter,
+                name,
+                extended_data,
+            } => MailboxDatum::List {
+                flags: flags.into_iter().map(NameAttribute::into_owned).collect(),
+                delimiter: owned_opt(delimiter),
+                name: name.into_owned(),
+                extended_data: extended_data
+                    .into_iter()
+                    .map(ListExtendedItem::into_owned)
+                    .collect(),
+            },
+            MailboxDatum::Status { mailbox, status } => MailboxDatum::Status {
+                mailbox: mailbox.into_owned(),
+                status: status
+                    .into_iter()
+                    .map(StatusAttribute::into_owned)
+                    .collect(),
+            },
+            MailboxDatum::Recent(n) => MailboxDatum::Recent(n),
+            MailboxDatum::MetadataSolicited { mailbox, values } => {
+                MailboxDatum::MetadataSolicited {
+                    mailbox: owned(mailbox),
+                    values: values.into_iter().map(Metadata::into_owned).collect(),
+                }
+            }
+            MailboxDatum::MetadataUnsolicited { mailbox, values } => {
+                MailboxDatum::MetadataUnsolicited {
+                    mailbox: owned(mailbox),
+                    values: owned_vec(values),
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Metadata<'a> {
+    pub fn into_owned(self) -> Metadata<'static> {
+        Metadata {
+            entry: owned(self.entry),
+            value: owned_opt(self.value),
+        }
+    }
+}
+
+impl<'a> NameAttribute<'a> {
+    pub fn into_owned(self) -> NameAttribute<'static> {
+        match self {
+            NameAttribute::NoInferiors => NameAttribute::NoInferiors,
+            NameAttribute::NoSelect => NameAttribute::NoSelect,
+            NameAttribute::Marked => NameAttribute::Marked,
+            NameAttribute::Unmarked => NameAttribute::Unmarked,
+            NameAttribute::HasChildren => NameAttribute::HasChildren,
+            NameAttribute::HasNoChildren => NameAttribute::HasNoChildren,
+            NameAttribute::NonExistent => NameAttribute::NonExistent,
+            NameAttribute::Subscribed => NameAttribute::Subscribed,
+            NameAttribute::Remote => NameAttribute::Remote,
+            NameAttribute::All => NameAttribute::All,
+            NameAttribute::Archive => NameAttribute::Archive,
+            NameAttribute::Drafts => NameAttribute::Drafts,
+            NameAttribute::Flagged => NameAttribute::Flagged,
+            NameAttribute::Junk => NameAttribute::Junk,
+            NameAttribute::Sent => NameAttribute::Sent,
+            NameAttribute::Trash => NameAttribute::Trash,
+            NameAttribute::Extension(s) => NameAttribute::Extension(owned(s)),
+        }
+    }
+}
+
+impl<'a> Annotation<'a> {
+    pub fn into_owned(self) -> Annotation<'static> {
+        Annotation {
+            entry: owned(self.entry),
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(|(name, value)| (owned(name), owned_opt(value)))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> ListExtendedItem<'a> {
+    pub fn into_owned(self) -> ListExtendedItem<'static> {
+        ListExtendedItem {
+            tag: owned(self.tag),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+impl<'a> TaggedExtVal<'a> {
+    pub fn into_owned(self) -> TaggedExtVal<'static> {
+        match self {
+            TaggedExtVal::Number(n) => TaggedExtVal::Number(n),
+            TaggedExtVal::SequenceSet(set) => TaggedExtVal::SequenceSet(set),
+            TaggedExtVal::Comp(comp) => {
+                TaggedExtVal::Comp(comp.into_iter().map(TaggedExtComp::into_owned).collect())
+            }
+        }
+    }
+}
+
+impl<'a> TaggedExtComp<'a> {
+    pub fn into_owned(self) -> TaggedExtComp<'static> {
+        match self {
+            TaggedExtComp::Astring(s) => TaggedExtComp::Astring(owned(s)),
+            TaggedExtComp::List(comp) => {
+                TaggedExtComp::List(comp.into_iter().map(TaggedExtComp::into_owned).collect())
+            }
+        }
+    }
+}
+
+impl<'a> Flag<'a> {
+    pub fn into_owned(self) -> Flag<'static> {
+        match self {
+            Flag::Seen => Flag::Seen,
+            Flag::Answered => Flag::Answered,
+            Flag::Flagged => Flag::Flagged,
+            Flag::Deleted => Flag::Deleted,
+            Flag::Draft => Flag::Draft,
+            Flag::Recent => Flag::Recent,
+            Flag::MayCreate => Flag::MayCreate,
+            Flag::Keyword(s) => Flag::Keyword(owned(s)),
+            Flag::Extension(s) => Flag::Extension(owned(s)),
+        }
+    }
+}
+
+impl<'a> FlagSet<'a> {
+    pub fn into_owned(self) -> FlagSet<'static> {
+        FlagSet(self.0.into_iter().map(Flag::into_owned).collect())
+    }
+}
+
+impl<'a> FlagDiff<'a> {
+    pub fn into_owned(self) -> FlagDiff<'static> {
+        FlagDiff {
+            add: self.add.into_owned(),
+            remove: self.remove.into_owned(),
+        }
+    }
+}
+
+impl<'a> Capability<'a> {
+    pub fn into_owned(self) -> Capability<'static> {
+        match self {
+            Capability::Imap4rev1 => Capability::Imap4rev1,
+            Capability::Imap4rev2 => Capability::Imap4rev2,
+            Capability::Auth(mech) => Capability::Auth(owned(mech)),
+            Capability::StartTls => Capability::StartTls,
+            Capability::LoginDisabled => Capability::LoginDisabled,
+            Capability::Idle => Capability::Idle,
+            Capability::Namespace => Capability::Namespace,
+            Capability::Id => Capability::Id,
+            Capability::Enable => Capability::Enable,
+            Capability::Condstore => Capability::Condstore,
+            Capability::Qresync => Capability::Qresync,
+            Capability::UidPlus => Capability::UidPlus,
+            Capability::Move => Capability::Move,
+            Capability::Unselect => Capability::Unselect,
+            Capability::Children => Capability::Children,
+            Capability::LiteralPlus => Capability::LiteralPlus,
+            Capability::LiteralMinus => Capability::LiteralMinus,
+            Capability::Binary => Capability::Binary,
+            Capability::SpecialUse => Capability::SpecialUse,
+            Capability::ListExtended => Capability::ListExtended,
+            Capability::Language => Capability::Language,
+            Capability::AppendLimit(n) => Capability::AppendLimit(n),
+            Capability::Replace => Capability::Replace,
+            Capability::LoginReferrals => Capability::LoginReferrals,
+            Capability::MailboxReferrals => Capability::MailboxReferrals,
+            Capability::ClientId => Capability::ClientId,
+            Capability::UidOnly => Capability::UidOnly,
+            Capability::Partial => Capability::Partial,
+            Capability::MultiAppend => Capability::MultiAppend,
+            Capability::Catenate => Capability::Catenate,
+            Capability::Compress(algorithm) => Capability::Compress(owned(algorithm)),
+            Capability::Atom(s) => Capability::Atom(owned(s)),
+        }
+    }
+}
+
+impl<'a> MessageSection<'a> {
+    pub fn into_owned(self) -> MessageSection<'static> {
+        match self {
+            MessageSection::Header => MessageSection::Header,
+            MessageSection::HeaderFields(fields) => MessageSection::HeaderFields(owned_vec(fields)),
+            MessageSection::HeaderFieldsNot(fields) => {
+                MessageSection::HeaderFieldsNot(owned_vec(fields))
+            }
+            MessageSection::Mime => MessageSection::Mime,
+            MessageSection::Text => MessageSection::Text,
+        }
+    }
+}
+
+impl<'a> SectionPath<'a> {
+    pub fn into_owned(self) -> SectionPath<'static> {
+        match self {
+            SectionPath::Full(section) => SectionPath::Full(section.into_owned()),
+            SectionPath::Part(part, section) => {
+                SectionPath::Part(part, section.map(MessageSection::into_owned))
+            }
+        }
+    }
+}
+
+impl<'a> AttributeValue<'a> {
+    pub fn into_owned(self) -> AttributeValue<'static> {
+        match self {
+            AttributeValue::BodySection {
+                section,
+                index,
+                data,
+            } => AttributeValue::BodySection {
+                section: section.map(SectionPath::into_owned),
+                index,
+                data: owned_opt(data),
+            },
+            AttributeValue::BodyStructure(body) => AttributeValue::BodyStructure(body.into_owned()),
+            AttributeValue::Envelope(envelope) => {
+                AttributeValue::Envelope(Box::new(envelope.into_owned()))
+            }
+            AttributeValue::Flags(flags) => {
+                AttributeValue::Flags(flags.into_iter().map(Flag::into_owned).collect())
+            }
+            AttributeValue::InternalDate(date) => AttributeValue::InternalDate(date),
+            AttributeValue::ModSeq(n) => AttributeValue::ModSeq(n),
+            AttributeValue::Rfc822(raw) => AttributeValue::Rfc822(owned_opt(raw)),
+            AttributeValue::Rfc822Header(raw) => AttributeValue::Rfc822Header(owned_opt(raw)),
+            AttributeValue::Rfc822Size(n) => AttributeValue::Rfc822Size(n),
+            AttributeValue::Rfc822Text(raw) => AttributeValue::Rfc822Text(owned_opt(raw)),
+            AttributeValue::Uid(uid) => AttributeValue::Uid(uid),
+            AttributeValue::GmailLabels(labels) => AttributeValue::GmailLabels(owned_vec(labels)),
+            AttributeValue::GmailMsgId(id) => AttributeValue::GmailMsgId(id),
+            AttributeValue::GmailThrId(id) => AttributeValue::GmailThrId(id),
+            AttributeValue::EmailId(id) => AttributeValue::EmailId(owned(id)),
+            AttributeValue::ThreadId(id) => AttributeValue::ThreadId(owned_opt(id)),
+            AttributeValue::Preview(preview) => AttributeValue::Preview(owned_opt(preview)),
+            AttributeValue::SaveDate(date) => AttributeValue::SaveDate(date),
+            AttributeValue::Annotation(annotations) => AttributeValue::Annotation(
+                annotations
+                    .into_iter()
+                    .map(Annotation::into_owned)
+                    .collect(),
+            ),
+            AttributeValue::Binary {
+                section,
+                index,
+                data,
+            } => AttributeValue::Binary {
+                section,
+                index,
+                data: owned_opt(data),
+            },
+            AttributeValue::BinarySize { section, size } => {
+                AttributeValue::BinarySize { section, size }
+            }
+        }
+    }
+}
+
+impl<'a> BodyStructure<'a> {
+    pub fn into_owned(self) -> BodyStructure<'static> {
+        match self {
+            BodyStructure::Basic {
+                common,
+                other,
+                extensions,
+            } => BodyStructure::Basic {
+                common: common.into_owned(),
+                other: other.into_owned(),
+                extensions: extensions
+                    .into_iter()
+                    .map(BodyExtension::into_owned)
+                    .collect(),
+            },
+            BodyStructure::Text {
+                common,
+                other,
+                lines,
+                extensions,
+            } => BodyStructure::Text {
+                common: common.into_owned(),
+                other: other.into_owned(),
+                lines,
+                extensions: extensions
+                    .into_iter()
+                    .map(BodyExtension::into_owned)
+                    .collect(),
+            },
+            BodyStructure::Message {
+                common,
+                other,
+                envelope,
+                body,
+                lines,
+                extensions,
+            } => BodyStructure::Message {
+                common: common.into_owned(),
+                other: other.into_owned(),
+                envelope: envelope.into_owned(),
+                body: Box::new(body.into_owned()),
+                lines,
+                extensions: extensions
+                    .into_iter()
+                    .map(BodyExtension::into_owned)
+                    .collect(),
+            },
+            BodyStructure::Multipart {
+                common,
+                bodies,
+                extensions,
+            } => BodyStructure::Multipart {
+                common: common.into_owned(),
+                bodies: bodies.into_iter().map(BodyStructure::into_owned).collect(),
+                extensions: extensions
+                    .into_iter()
+                    .map(BodyExtension::into_owned)
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl<'a> BodyContentCommon<'a> {
+    pub fn into_owned(self) -> BodyContentCommon<'static> {
+        BodyContentCommon {
+            ty: self.ty.into_owned(),
+            disposition: self.disposition.map(ContentDisposition::into_owned),
+            language: self.language.map(owned_vec),
+            location: owned_opt(self.location),
+        }
+    }
+}
+
+impl<'a> BodyContentSinglePart<'a> {
+    pub fn into_owned(self) -> BodyContentSinglePart<'static> {
+        BodyContentSinglePart {
+            id: owned_opt(self.id),
+            md5: owned_opt(self.md5),
+            description: owned_opt(self.description),
+            transfer_encoding: self.transfer_encoding.into_owned(),
+            octets: self.octets,
+        }
+    }
+}
+
+impl<'a> ContentType<'a> {
+    pub fn into_owned(self) -> ContentType<'static> {
+        ContentType {
+            ty: owned(self.ty),
+            subtype: owned(self.subtype),
+            params: owned_params(self.params),
+        }
+    }
+}
+
+impl<'a> ContentDisposition<'a> {
+    pub fn into_owned(self) -> ContentDisposition<'static> {
+        ContentDisposition {
+            ty: owned(self.ty),
+            params: owned_params(self.params),
+        }
+    }
+}
+
+impl<'a> ContentEncoding<'a> {
+    pub fn into_owned(self) -> ContentEncoding<'static> {
+        match self {
+            ContentEncoding::SevenBit => ContentEncoding::SevenBit,
+            ContentEncoding::EightBit => ContentEncoding::EightBit,
+            ContentEncoding::Binary => ContentEncoding::Binary,
+            ContentEncoding::Base64 => ContentEncoding::Base64,
+            ContentEncoding::QuotedPrintable => ContentEncoding::QuotedPrintable,
+            ContentEncoding::Other(s) => ContentEncoding::Other(owned(s)),
+        }
+    }
+}
+
+impl<'a> BodyExtension<'a> {
+    pub fn into_owned(self) -> BodyExtension<'static> {
+        match self {
+            BodyExtension::Num(n) => BodyExtension::Num(n),
+            BodyExtension::Str(s) => BodyExtension::Str(owned_opt(s)),
+            BodyExtension::List(list) => {
+                BodyExtension::List(list.into_iter().map(BodyExtension::into_owned).collect())
+            }
+        }
+    }
+}
+
+impl<'a> Envelope<'a> {
+    pub fn into_owned(self) -> Envelope<'static> {
+        let owned_addresses = |addresses: Option<Vec<Address<'_>>>| {
+            addresses.map(|addresses| addresses.into_iter().map(Address::into_owned).collect())
+        };
+        Envelope {
+            date: owned_opt(self.date),
+            subject: owned_opt(self.subject),
+            from: owned_addresses(self.from),
+            sender: owned_addresses(self.sender),
+            reply_to: owned_addresses(self.reply_to),
+            to: owned_addresses(self.to),
+            cc: owned_addresses(self.cc),
+            bcc: owned_addresses(self.bcc),
+            in_reply_to: owned_opt(self.in_reply_to),
+            message_id: owned_opt(self.message_id),
+        }
+    }
+}
+
+impl<'a> Address<'a> {
+    pub fn into_owned(self) -> Address<'static> {
+        Address {
+            name: owned_opt(self.name),
+            adl: owned_opt(self.adl),
+            mailbox: owned_opt(self.mailbox),
+            host: owned_opt(self.host),
+        }
+    }
+}
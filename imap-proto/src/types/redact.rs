@@ -0,0 +1,222 @@
+// Formatting of requests and responses for logs, without credentials or
+// message content.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use super::request::next_literal;
+use super::{AttributeValue, Request, Response};
+
+impl Request {
+    /// Formats the request with the arguments of LOGIN and AUTHENTICATE
+    /// masked, and the contents of literals replaced by their length, so
+    /// that it can be logged.
+    pub fn redacted(&self) -> impl fmt::Display + '_ {
+        RedactedRequest(self)
+    }
+}
+
+impl<'a> Response<'a> {
+    /// Like the `Debug` output, but with message content such as body
+    /// sections, RFC822 data and previews replaced by their length.
+    pub fn redacted(&self) -> impl fmt::Debug + '_ {
+        RedactedResponse(self)
+    }
+}
+
+struct RedactedRequest<'r>(&'r Request);
+
+impl fmt::Display for RedactedRequest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Request(tag, args) = self.0;
+        write!(f, "{} {}", tag.0, RedactedArgs(args))
+    }
+}
+
+/// The arguments of a request as `Request::redacted` formats them, also
+/// used for the `Debug` output of `Command`.
+pub(crate) struct RedactedArgs<'r>(pub(crate) &'r [u8]);
+
+impl fmt::Display for RedactedArgs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self.0;
+        let mut words = args.splitn(3, |&c| c == b' ');
+        let command = words.next().unwrap_or_default();
+        if command.eq_ignore_ascii_case(b"LOGIN") {
+            write_escaped(f, command)?;
+            return write!(f, " <redacted>");
+        }
+        if command.eq_ignore_ascii_case(b"AUTHENTICATE") {
+            write_escaped(f, command)?;
+            if let Some(mechanism) = words.next() {
+                write!(f, " ")?;
+                write_escaped(f, mechanism)?;
+            }
+            if words.next().is_some() {
+                write!(f, " <redacted>")?;
+            }
+            return Ok(());
+        }
+
+        let mut rest = args;
+        while let Some((data, len, _)) = next_literal(rest) {
+            write_escaped(f, &rest[..data])?;
+            write!(f, "<{} bytes>", len)?;
+            rest = &rest[data + len..];
+        }
+        write_escaped(f, rest)
+    }
+}
+
+// Writes printable ASCII as it is, and escapes everything else.
+fn write_escaped(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for &b in bytes {
+        match b {
+            b' '..=b'~' => write!(f, "{}", b as char)?,
+            _ => write!(f, "{}", std::ascii::escape_default(b))?,
+        }
+    }
+    Ok(())
+}
+
+struct RedactedResponse<'r, 'a>(&'r Response<'a>);
+
+impl fmt::Debug for RedactedResponse<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Response::Fetch(seq, attrs) => f
+                .debug_tuple("Fetch")
+                .field(seq)
+                .field(&RedactedAttributes(attrs))
+                .finish(),
+            Response::UidFetch(uid, attrs) => f
+                .debug_tuple("UidFetch")
+                .field(uid)
+                .field(&RedactedAttributes(attrs))
+                .finish(),
+            Response::UrlFetch(urls) => f
+                .debug_tuple("UrlFetch")
+                .field(
+                    &urls
+                        .iter()
+                        .map(|(url, data)| (url, length(data)))
+                        .collect::<Vec<_>>(),
+                )
+                .finish(),
+            rsp => rsp.fmt(f),
+        }
+    }
+}
+
+struct RedactedAttributes<'r, 'a>(&'r [AttributeValue<'a>]);
+
+impl fmt::Debug for RedactedAttributes<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(RedactedAttribute))
+            .finish()
+    }
+}
+
+struct RedactedAttribute<'r, 'a>(&'r AttributeValue<'a>);
+
+impl fmt::Debug for RedactedAttribute<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            AttributeValue::BodySection {
+                section,
+                index,
+                data,
+            } => f
+                .debug_struct("BodySection")
+                .field("section", section)
+                .field("index", index)
+                .field("data", &length(data))
+                .finish(),
+            AttributeValue::Binary {
+                section,
+                index,
+                data,
+            } => f
+                .debug_struct("Binary")
+                .field("section", section)
+                .field("index", index)
+                .field("data", &length(data))
+                .finish(),
+            AttributeValue::Rfc822(data) => f.debug_tuple("Rfc822").field(&length(data)).finish(),
+            AttributeValue::Rfc822Header(data) => {
+                f.debug_tuple("Rfc822Header").field(&length(data)).finish()
+            }
+            AttributeValue::Rfc822Text(data) => {
+                f.debug_tuple("Rfc822Text").field(&length(data)).finish()
+            }
+            AttributeValue::Preview(text) => f
+                .debug_tuple("Preview")
+                .field(&text.as_ref().map(|text| Length(text.len())))
+                .finish(),
+            attr => attr.fmt(f),
+        }
+    }
+}
+
+// Stands in for redacted data.
+struct Length(usize);
+
+fn length(data: &Option<Cow<'_, [u8]>>) -> Option<Length> {
+    data.as_ref().map(|data| Length(data.len()))
+}
+
+impl fmt::Debug for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} bytes>", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::rfc3501::parse_response;
+    use crate::types::*;
+
+    fn request(args: &[u8]) -> String {
+        Request(RequestId("a1".to_string()), args.to_vec())
+            .redacted()
+            .to_string()
+    }
+
+    #[test]
+    fn test_redacted_request() {
+        assert_eq!(request(b"LOGIN \"djc\" \"s3cr3t\""), "a1 LOGIN <redacted>");
+        assert_eq!(
+            request(b"AUTHENTICATE PLAIN AGRqYwBzM2NyM3Q="),
+            "a1 AUTHENTICATE PLAIN <redacted>"
+        );
+        assert_eq!(request(b"AUTHENTICATE GSSAPI"), "a1 AUTHENTICATE GSSAPI");
+        assert_eq!(
+            request(b"APPEND \"Drafts\" {5+}\r\nhello"),
+            "a1 APPEND \"Drafts\" {5+}\\r\\n<5 bytes>"
+        );
+        assert_eq!(request(b"SELECT \"{1}\""), "a1 SELECT \"{1}\"");
+    }
+
+    #[test]
+    fn test_redacted_response() {
+        let rsp = b"* 1 FETCH (UID 4 BODY[TEXT] {6}\r\nsecret RFC822.HEADER NIL)\r\n";
+        match parse_response(rsp) {
+            Ok((_, rsp)) => {
+                let redacted = format!("{:?}", rsp.redacted());
+                assert_eq!(
+                    redacted,
+                    "Fetch(Seq(1), [Uid(Uid(4)), BodySection { section: Some(Full(Text)), \
+                     index: None, data: Some(<6 bytes>) }, Rfc822Header(None)])"
+                );
+            }
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+
+        let rsp = b"* OK done\r\n";
+        match parse_response(rsp) {
+            Ok((_, rsp)) => assert_eq!(format!("{:?}", rsp.redacted()), format!("{:?}", rsp)),
+            rsp => panic!("unexpected response {:?}", rsp),
+        }
+    }
+}
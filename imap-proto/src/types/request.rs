@@ -0,0 +1,167 @@
+// Splitting requests at synchronizing literals, after which the client has
+// to wait for the server's continuation request, and turning literals into
+// non-synchronizing ones where the server allows it.
+
+use super::{Capability, LiteralMode, Request};
+
+impl LiteralMode {
+    /// The mode the server's capabilities allow, preferring `LITERAL+`.
+    pub fn from_capabilities(caps: &[Capability<'_>]) -> Self {
+        if caps.contains(&Capability::LiteralPlus) {
+            LiteralMode::Plus
+        } else if caps.contains(&Capability::LiteralMinus) {
+            LiteralMode::Minus
+        } else {
+            LiteralMode::Sync
+        }
+    }
+
+    /// Whether a literal of `len` bytes can be sent without waiting.
+    pub fn is_non_sync(self, len: usize) -> bool {
+        match self {
+            LiteralMode::Sync => false,
+            LiteralMode::Plus => true,
+            LiteralMode::Minus => len <= 4096,
+        }
+    }
+}
+
+impl Request {
+    /// Rewrites the synchronizing literals that `mode` allows as
+    /// non-synchronizing ones, `{N+}`, so that they are sent without
+    /// waiting for a continuation request.
+    pub fn with_literal_mode(self, mode: LiteralMode) -> Request {
+        let Request(tag, args) = self;
+        let mut out = Vec::with_capacity(args.len());
+        let mut rest = &args[..];
+        while let Some((data, len, sync)) = next_literal(rest) {
+            if sync && mode.is_non_sync(len) {
+                // The header is "{" number "}" CRLF.
+                out.extend(&rest[..data - 3]);
+                out.extend(b"+}\r\n");
+            } else {
+                out.extend(&rest[..data]);
+            }
+            out.extend(&rest[data..data + len]);
+            rest = &rest[data + len..];
+        }
+        out.extend(rest);
+        Request(tag, out)
+    }
+
+    /// The request as it is sent on the wire, with the tag and the final
+    /// CRLF, split after each synchronizing literal's `{N}\r\n`. After
+    /// sending a part other than the last, the client must wait for a
+    /// continuation request before sending the next one.
+    ///
+    /// Requests without synchronizing literals have a single part.
+    pub fn segments(&self) -> Vec<Vec<u8>> {
+        let Request(tag, args) = self;
+        let mut segments = vec![];
+        let mut current = format!("{} ", tag.0).into_bytes();
+        let mut rest = &args[..];
+        while let Some((data, len, sync)) = next_literal(rest) {
+            current.extend(&rest[..data]);
+            if sync {
+                segments.push(current);
+                current = vec![];
+            }
+            current.extend(&rest[data..data + len]);
+            rest = &rest[data + len..];
+        }
+        current.extend(rest);
+        current.extend(b"\r\n");
+        segments.push(current);
+        segments
+    }
+}
+
+// literal = "{" number ["+"] "}" CRLF *CHAR8
+// Returns the offset and length of the data of the first literal, and
+// whether it is synchronizing. Quoted strings can't contain CRLF, so the
+// header of a literal can't be mistaken for one.
+pub(super) fn next_literal(args: &[u8]) -> Option<(usize, usize, bool)> {
+    let mut offset = 0;
+    while let Some(open) = args[offset..].iter().position(|&c| c == b'{') {
+        let start = offset + open + 1;
+        let digits = args[start..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        let mut end = start + digits;
+        let sync = args.get(end) != Some(&b'+');
+        if !sync {
+            end += 1;
+        }
+        if digits > 0 && args[end..].starts_with(b"}\r\n") {
+            let len = std::str::from_utf8(&args[start..start + digits]).ok()?;
+            let len: usize = len.parse().ok()?;
+            let data = end + 3;
+            if data + len <= args.len() {
+                return Some((data, len, sync));
+            }
+        }
+        offset = start;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::*;
+
+    fn segments(args: &[u8]) -> Vec<Vec<u8>> {
+        Request(RequestId("a1".to_string()), args.to_vec()).segments()
+    }
+
+    #[test]
+    fn test_segments() {
+        assert_eq!(segments(b"NOOP"), vec![b"a1 NOOP\r\n".to_vec()]);
+        assert_eq!(
+            segments(b"LOGIN {3}\r\ndjc {6}\r\n{1}\r\n\r"),
+            vec![
+                b"a1 LOGIN {3}\r\n".to_vec(),
+                b"djc {6}\r\n".to_vec(),
+                b"{1}\r\n\r\r\n".to_vec(),
+            ]
+        );
+        assert_eq!(
+            segments(b"APPEND Drafts {5+}\r\n{1}\r\n"),
+            vec![b"a1 APPEND Drafts {5+}\r\n{1}\r\n\r\n".to_vec()]
+        );
+        assert_eq!(
+            segments(b"SELECT \"{1}\""),
+            vec![b"a1 SELECT \"{1}\"\r\n".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_with_literal_mode() {
+        let big = [b'x'; 4097];
+        let mut args = b"APPEND Drafts {3}\r\nfoo {4097}\r\n".to_vec();
+        args.extend(&big[..]);
+        args.extend(b" {2+}\r\nhi");
+        let request = Request(RequestId("a1".to_string()), args.clone());
+
+        let sync = request.clone().with_literal_mode(LiteralMode::Sync);
+        assert_eq!(sync.1, args);
+        assert_eq!(sync.segments().len(), 3);
+
+        let minus = request.clone().with_literal_mode(LiteralMode::Minus);
+        assert!(minus
+            .1
+            .starts_with(b"APPEND Drafts {3+}\r\nfoo {4097}\r\nx"));
+        assert_eq!(minus.segments().len(), 2);
+
+        let plus = request.with_literal_mode(LiteralMode::Plus);
+        assert!(plus
+            .1
+            .starts_with(b"APPEND Drafts {3+}\r\nfoo {4097+}\r\nx"));
+        assert!(plus.1.ends_with(b" {2+}\r\nhi"));
+        assert_eq!(plus.segments().len(), 1);
+
+        let caps = [Capability::Imap4rev1, Capability::LiteralMinus];
+        assert_eq!(LiteralMode::from_capabilities(&caps), LiteralMode::Minus);
+        assert_eq!(LiteralMode::from_capabilities(&[]), LiteralMode::Sync);
+    }
+}
@@ -0,0 +1,157 @@
+// Search criteria: combining search keys and writing them in the syntax of
+// the SEARCH command.
+
+use std::ops::Not;
+
+use super::SearchCriteria;
+use crate::builders::encode::{is_atom, write_astring};
+
+impl<'a> SearchCriteria<'a> {
+    /// Messages that match both criteria. `And` lists are merged rather
+    /// than nested.
+    pub fn and(self, other: SearchCriteria<'a>) -> Self {
+        let mut keys = match self {
+            SearchCriteria::And(keys) => keys,
+            key => vec![key],
+        };
+        match other {
+            SearchCriteria::And(more) => keys.extend(more),
+            key => keys.push(key),
+        }
+        SearchCriteria::And(keys)
+    }
+
+    /// Messages that match either of the criteria.
+    pub fn or(self, other: SearchCriteria<'a>) -> Self {
+        SearchCriteria::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Whether all strings in the criteria are ASCII, so that no CHARSET
+    /// needs to be given.
+    pub(crate) fn is_ascii(&self) -> bool {
+        match self {
+            SearchCriteria::Keyword(s)
+            | SearchCriteria::Unkeyword(s)
+            | SearchCriteria::Bcc(s)
+            | SearchCriteria::Body(s)
+            | SearchCriteria::Cc(s)
+            | SearchCriteria::From(s)
+            | SearchCriteria::Subject(s)
+            | SearchCriteria::Text(s)
+            | SearchCriteria::To(s) => s.is_ascii(),
+            SearchCriteria::Header(name, value) => name.is_ascii() && value.is_ascii(),
+            SearchCriteria::Not(key) => key.is_ascii(),
+            SearchCriteria::Or(a, b) => a.is_ascii() && b.is_ascii(),
+            SearchCriteria::And(keys) => keys.iter().all(SearchCriteria::is_ascii),
+            _ => true,
+        }
+    }
+
+    /// Writes the criteria as the search keys of a SEARCH command, with
+    /// strings quoted or sent as literals as needed.
+    ///
+    /// ```text
+    /// search = "SEARCH" [SP "CHARSET" SP astring] 1*(SP search-key)
+    /// ```
+    pub(crate) fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            SearchCriteria::And(keys) if !keys.is_empty() => {
+                for (i, key) in keys.iter().enumerate() {
+                    if i > 0 {
+                        out.push(b' ');
+                    }
+                    key.encode_key(out);
+                }
+            }
+            key => key.encode_key(out),
+        }
+    }
+
+    // search-key = "ALL" / "ANSWERED" / "BCC" SP astring / ... /
+    //              "NOT" SP search-key / "OR" SP search-key SP search-key /
+    //              sequence-set / "(" search-key *(SP search-key) ")"
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        match self {
+            SearchCriteria::All => out.extend(b"ALL"),
+            SearchCriteria::Answered => out.extend(b"ANSWERED"),
+            SearchCriteria::Deleted => out.extend(b"DELETED"),
+            SearchCriteria::Draft => out.extend(b"DRAFT"),
+            SearchCriteria::Flagged => out.extend(b"FLAGGED"),
+            SearchCriteria::New => out.extend(b"NEW"),
+            SearchCriteria::Old => out.extend(b"OLD"),
+            SearchCriteria::Recent => out.extend(b"RECENT"),
+            SearchCriteria::Seen => out.extend(b"SEEN"),
+            SearchCriteria::Unanswered => out.extend(b"UNANSWERED"),
+            SearchCriteria::Undeleted => out.extend(b"UNDELETED"),
+            SearchCriteria::Undraft => out.extend(b"UNDRAFT"),
+            SearchCriteria::Unflagged => out.extend(b"UNFLAGGED"),
+            SearchCriteria::Unseen => out.extend(b"UNSEEN"),
+            // flag-keyword = atom
+            // No message can have a keyword that isn't an atom, so rather
+            // than sending it, those keys are written as keys that match no
+            // message or all of them.
+            SearchCriteria::Keyword(s) if !is_atom(s.as_bytes()) => out.extend(b"NOT ALL"),
+            SearchCriteria::Unkeyword(s) if !is_atom(s.as_bytes()) => out.extend(b"ALL"),
+            SearchCriteria::Keyword(s) => out.extend(format!("KEYWORD {}", s).as_bytes()),
+            SearchCriteria::Unkeyword(s) => out.extend(format!("UNKEYWORD {}", s).as_bytes()),
+            SearchCriteria::Bcc(s) => write_string_key(out, "BCC", s),
+            SearchCriteria::Body(s) => write_string_key(out, "BODY", s),
+            SearchCriteria::Cc(s) => write_string_key(out, "CC", s),
+            SearchCriteria::From(s) => write_string_key(out, "FROM", s),
+            SearchCriteria::Subject(s) => write_string_key(out, "SUBJECT", s),
+            SearchCriteria::Text(s) => write_string_key(out, "TEXT", s),
+            SearchCriteria::To(s) => write_string_key(out, "TO", s),
+            SearchCriteria::Header(name, value) => {
+                write_string_key(out, "HEADER", name);
+                out.push(b' ');
+                write_astring(out, value.as_bytes());
+            }
+            SearchCriteria::Before(date) => out.extend(format!("BEFORE {}", date).as_bytes()),
+            SearchCriteria::On(date) => out.extend(format!("ON {}", date).as_bytes()),
+            SearchCriteria::Since(date) => out.extend(format!("SINCE {}", date).as_bytes()),
+            SearchCriteria::SentBefore(date) => {
+                out.extend(format!("SENTBEFORE {}", date).as_bytes())
+            }
+            SearchCriteria::SentOn(date) => out.extend(format!("SENTON {}", date).as_bytes()),
+            SearchCriteria::SentSince(date) => out.extend(format!("SENTSINCE {}", date).as_bytes()),
+            SearchCriteria::Larger(n) => out.extend(format!("LARGER {}", n).as_bytes()),
+            SearchCriteria::Smaller(n) => out.extend(format!("SMALLER {}", n).as_bytes()),
+            SearchCriteria::SequenceSet(set) => out.extend(set.to_string().as_bytes()),
+            SearchCriteria::Uid(set) => out.extend(format!("UID {}", set).as_bytes()),
+            SearchCriteria::Not(key) => {
+                out.extend(b"NOT ");
+                key.encode_key(out);
+            }
+            SearchCriteria::Or(a, b) => {
+                out.extend(b"OR ");
+                a.encode_key(out);
+                out.push(b' ');
+                b.encode_key(out);
+            }
+            SearchCriteria::And(keys) => match keys.as_slice() {
+                [] => out.extend(b"ALL"),
+                [key] => key.encode_key(out),
+                _ => {
+                    out.push(b'(');
+                    self.encode(out);
+                    out.push(b')');
+                }
+            },
+        }
+    }
+}
+
+impl<'a> Not for SearchCriteria<'a> {
+    type Output = SearchCriteria<'a>;
+
+    /// Messages that don't match the criteria.
+    fn not(self) -> Self::Output {
+        SearchCriteria::Not(Box::new(self))
+    }
+}
+
+fn write_string_key(out: &mut Vec<u8>, name: &str, value: &str) {
+    out.extend(name.as_bytes());
+    out.push(b' ');
+    write_astring(out, value.as_bytes());
+}
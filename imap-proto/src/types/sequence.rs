@@ -0,0 +1,271 @@
+// Construction, set operations and wire serialization for sequence sets.
+//
+// The set operations treat `*` as larger than any number. Servers resolve
+// `*` to the largest number in use, so this is exact for every message that
+// exists in the mailbox, except that `n:*` also matches the largest message
+// if `n` is larger than it.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::RangeInclusive;
+
+use super::{Seq, SeqNumber, SeqRange, SequenceSet, Uid};
+
+impl SeqRange {
+    /// Whether `n` is in this range, resolving `*` to `largest`.
+    pub fn contains(self, n: u32, largest: u32) -> bool {
+        self.to_range(largest).contains(&n)
+    }
+}
+
+impl SequenceSet {
+    /// Whether the set has no ranges. `$` is never empty, since only the
+    /// server knows what it refers to.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            SequenceSet::Set(ranges) => ranges.is_empty(),
+            SequenceSet::LastResult => false,
+        }
+    }
+
+    /// Whether `n` is in the set, resolving `*` to `largest`. Always false
+    /// for `$`.
+    pub fn contains(&self, n: u32, largest: u32) -> bool {
+        self.ranges().iter().any(|r| r.contains(n, largest))
+    }
+
+    /// Iterates over the numbers in the set, resolving `*` to `largest`.
+    /// Numbers are yielded in the order of the ranges, so a set should be
+    /// normalized first to get every number once and in ascending order.
+    pub fn iter(&self, largest: u32) -> impl Iterator<Item = u32> + '_ {
+        self.ranges()
+            .iter()
+            .flat_map(move |range| range.to_range(largest))
+    }
+
+    /// Sorts the ranges and merges those that overlap or are adjacent, so
+    /// that `5,1:3,4` becomes `1:5`. Does nothing for `$`.
+    pub fn normalize(&mut self) {
+        if let SequenceSet::Set(ranges) = self {
+            *ranges = merge(std::mem::take(ranges));
+        }
+    }
+
+    /// Returns a normalized set of the numbers in either set. `$` is treated
+    /// as empty.
+    pub fn union(&self, other: &SequenceSet) -> SequenceSet {
+        let ranges = self.ranges().iter().chain(other.ranges()).copied();
+        SequenceSet::Set(merge(ranges.collect()))
+    }
+
+    /// Returns a normalized set of the numbers in both sets. `$` is treated
+    /// as empty.
+    pub fn intersection(&self, other: &SequenceSet) -> SequenceSet {
+        let (a, b) = (
+            merge(self.ranges().to_vec()),
+            merge(other.ranges().to_vec()),
+        );
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            let start = a[i].start.max(b[j].start);
+            let end = a[i].end.min(b[j].end);
+            if start <= end {
+                ranges.push(SeqRange { start, end });
+            }
+            if a[i].end < b[j].end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        SequenceSet::Set(ranges)
+    }
+}
+
+fn merge(mut ranges: Vec<SeqRange>) -> Vec<SeqRange> {
+    ranges.sort_by_key(|range| (range.start, range.end));
+    let mut merged: Vec<SeqRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last) = merged.last_mut() {
+            let adjacent = match (last.end, range.start) {
+                (SeqNumber::Largest, _) => true,
+                (SeqNumber::Value(end), SeqNumber::Value(start)) => start <= end.saturating_add(1),
+                (SeqNumber::Value(_), SeqNumber::Largest) => false,
+            };
+            if adjacent {
+                last.end = last.end.max(range.end);
+                continue;
+            }
+        }
+        merged.push(range);
+    }
+    merged
+}
+
+impl From<u32> for SequenceSet {
+    fn from(n: u32) -> Self {
+        SequenceSet::Set(vec![SeqRange::new(n.into(), n.into())])
+    }
+}
+
+impl From<RangeInclusive<u32>> for SequenceSet {
+    fn from(range: RangeInclusive<u32>) -> Self {
+        SequenceSet::Set(vec![range.into()])
+    }
+}
+
+impl From<SeqRange> for SequenceSet {
+    fn from(range: SeqRange) -> Self {
+        SequenceSet::Set(vec![range])
+    }
+}
+
+/// Collects numbers into a normalized set, so that `[3, 1, 2, 7]` becomes
+/// `1:3,7`.
+impl FromIterator<u32> for SequenceSet {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let ranges = iter.into_iter().map(|n| SeqRange::new(n.into(), n.into()));
+        SequenceSet::Set(merge(ranges.collect()))
+    }
+}
+
+/// Collects ranges in the given order, like the `Vec` conversion.
+impl FromIterator<SeqRange> for SequenceSet {
+    fn from_iter<I: IntoIterator<Item = SeqRange>>(iter: I) -> Self {
+        SequenceSet::Set(iter.into_iter().collect())
+    }
+}
+
+impl FromIterator<RangeInclusive<u32>> for SequenceSet {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<u32>>>(iter: I) -> Self {
+        iter.into_iter().map(SeqRange::from).collect()
+    }
+}
+
+impl fmt::Display for Seq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for Uid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for SeqNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeqNumber::Value(n) => write!(f, "{}", n),
+            SeqNumber::Largest => write!(f, "*"),
+        }
+    }
+}
+
+impl fmt::Display for SeqRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}:{}", self.start, self.end)
+        }
+    }
+}
+
+/// Formats the set as it is sent on the wire, e.g. `1:5,9,20:*`. An empty
+/// set formats as an empty string, which is not a valid sequence set.
+impl fmt::Display for SequenceSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceSet::Set(ranges) => {
+                for (i, range) in ranges.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", range)?;
+                }
+                Ok(())
+            }
+            SequenceSet::LastResult => write!(f, "$"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::core::sequence_set;
+    use crate::types::*;
+
+    fn set(s: &str) -> SequenceSet {
+        let input = format!("{} ", s);
+        sequence_set(input.as_bytes()).unwrap().1
+    }
+
+    #[test]
+    fn test_display() {
+        for s in &["1:5,9,20:*", "*", "3,1", "$", "4:*,1"] {
+            assert_eq!(set(s).to_string(), *s);
+        }
+        // Ranges are normalized to start <= end when parsed.
+        assert_eq!(set("5:2").to_string(), "2:5");
+        assert_eq!(set("*:4").to_string(), "4:*");
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut s = set("5,1:3,4,10:12,11,20:*,30,*");
+        s.normalize();
+        assert_eq!(s.to_string(), "1:5,10:12,20:*");
+
+        let mut s = set("7,*");
+        s.normalize();
+        assert_eq!(s.to_string(), "7,*");
+
+        let mut s = set("4294967295,1:4294967295");
+        s.normalize();
+        assert_eq!(s.to_string(), "1:4294967295");
+
+        let mut s = SequenceSet::LastResult;
+        s.normalize();
+        assert_eq!(s, SequenceSet::LastResult);
+    }
+
+    #[test]
+    fn test_membership_and_iteration() {
+        let s = set("2:4,9,7:*");
+        assert!(s.contains(3, 10));
+        assert!(!s.contains(5, 10));
+        assert!(s.contains(10, 10));
+        assert!(!s.contains(11, 10));
+        // `7:*` covers 5:7 if the largest number in use is 5.
+        assert!(s.contains(6, 5));
+        assert_eq!(s.iter(8).collect::<Vec<_>>(), vec![2, 3, 4, 9, 7, 8]);
+        assert!(!SequenceSet::LastResult.contains(1, 1));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        assert_eq!(set("1:3,8").union(&set("4,6:*")).to_string(), "1:4,6:*");
+        assert_eq!(
+            set("1:10,20:*").intersection(&set("5:25")).to_string(),
+            "5:10,20:25"
+        );
+        assert_eq!(set("1:3").intersection(&set("5:*")), SequenceSet::default());
+        assert_eq!(set("3:*").intersection(&set("*")).to_string(), "*");
+        assert_eq!(set("1").union(&SequenceSet::LastResult).to_string(), "1");
+    }
+
+    #[test]
+    fn test_construction() {
+        let s: SequenceSet = vec![3, 1, 2, 7, 8, 5].into_iter().collect();
+        assert_eq!(s.to_string(), "1:3,5,7:8");
+        let s: SequenceSet = vec![9..=10, 1..=1].into_iter().collect();
+        assert_eq!(s.to_string(), "9:10,1");
+        assert_eq!(SequenceSet::from(4).to_string(), "4");
+        assert_eq!(SequenceSet::from(4..=6).to_string(), "4:6");
+        assert!(SequenceSet::default().is_empty());
+        assert!(!SequenceSet::LastResult.is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//!
+//! https://tools.ietf.org/html/rfc3501#section-5.1.3
+//!
+//! Mailbox International Naming Convention (modified UTF-7)
+//!
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+,";
+
+fn value(c: u8) -> Option<u32> {
+    ALPHABET.iter().position(|&a| a == c).map(|v| v as u32)
+}
+
+/// Decodes a mailbox name from modified UTF-7, as used by servers for
+/// international mailbox names (`&APw-bermut` becomes `übermut`).
+///
+/// Characters outside of shifted sequences are passed through unchanged, so
+/// names sent as raw UTF-8 (e.g. after `ENABLE UTF8=ACCEPT`) also decode to
+/// themselves. Returns `None` if a shifted sequence is malformed.
+pub fn decode_utf7_imap(s: &str) -> Option<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let shifted = &rest[start + 1..];
+        let end = shifted.find('-')?;
+        if end == 0 {
+            out.push('&');
+        } else {
+            let (mut acc, mut bits, mut units) = (0u32, 0, Vec::new());
+            for &c in &shifted.as_bytes()[..end] {
+                acc = (acc << 6) | value(c)?;
+                bits += 6;
+                if bits >= 16 {
+                    bits -= 16;
+                    units.push((acc >> bits) as u16);
+                    acc &= (1 << bits) - 1;
+                }
+            }
+            if bits >= 6 || acc != 0 {
+                return None;
+            }
+            for c in std::char::decode_utf16(units) {
+                out.push(c.ok()?);
+            }
+        }
+        rest = &shifted[end + 1..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+/// Encodes a mailbox name into modified UTF-7, suitable for use in commands
+/// sent to servers that have not enabled `UTF8=ACCEPT`.
+pub fn encode_utf7_imap(s: &str) -> String {
+    fn flush(out: &mut String, units: &mut Vec<u16>) {
+        if units.is_empty() {
+            return;
+        }
+        out.push('&');
+        let (mut acc, mut bits) = (0u32, 0);
+        for unit in units.drain(..) {
+            acc = (acc << 16) | u32::from(unit);
+            bits += 16;
+            while bits >= 6 {
+                bits -= 6;
+                out.push(ALPHABET[(acc >> bits) as usize & 0x3f] as char);
+            }
+            acc &= (1 << bits) - 1;
+        }
+        if bits > 0 {
+            out.push(ALPHABET[(acc << (6 - bits)) as usize & 0x3f] as char);
+        }
+        out.push('-');
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut units = Vec::new();
+    let mut buf = [0; 2];
+    for c in s.chars() {
+        match c {
+            '\x20'..='\x7e' => {
+                flush(&mut out, &mut units);
+                match c {
+                    '&' => out.push_str("&-"),
+                    _ => out.push(c),
+                }
+            }
+            _ => units.extend_from_slice(c.encode_utf16(&mut buf)),
+        }
+    }
+    flush(&mut out, &mut units);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode_utf7_imap("INBOX").as_deref(), Some("INBOX"));
+        assert_eq!(decode_utf7_imap("&APw-bermut").as_deref(), Some("übermut"));
+        assert_eq!(
+            decode_utf7_imap("Tom &- Jerry").as_deref(),
+            Some("Tom & Jerry")
+        );
+        assert_eq!(
+            decode_utf7_imap("~peter/mail/&U,BTFw-/&ZeVnLIqe-").as_deref(),
+            Some("~peter/mail/台北/日本語")
+        );
+        assert_eq!(decode_utf7_imap("&2D3eAQ-").as_deref(), Some("😁"));
+        assert_eq!(decode_utf7_imap("übermut").as_deref(), Some("übermut"));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(decode_utf7_imap("&APw"), None);
+        assert_eq!(decode_utf7_imap("&AP/-"), None);
+        assert_eq!(decode_utf7_imap("&APx-"), None);
+        assert_eq!(decode_utf7_imap("&2D0-"), None);
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode_utf7_imap("INBOX"), "INBOX");
+        assert_eq!(encode_utf7_imap("übermut"), "&APw-bermut");
+        assert_eq!(encode_utf7_imap("Tom & Jerry"), "Tom &- Jerry");
+        assert_eq!(
+            encode_utf7_imap("~peter/mail/台北/日本語"),
+            "~peter/mail/&U,BTFw-/&ZeVnLIqe-"
+        );
+        assert_eq!(encode_utf7_imap("😁"), "&2D3eAQ-");
+    }
+}
@@ -47,14 +47,14 @@ async fn imap_fetch(
                 eprintln!("Login failed: {:?}", info);
             }
             return Err(ImapError::Login {
-                cause: io::Error::new(io::ErrorKind::Other, "login failed"),
+                cause: io::Error::other("login failed"),
             });
         }
         _ => unimplemented!(),
     }
 
     let _ = tls_client
-        .call(CommandBuilder::select(&mailbox))
+        .call(CommandBuilder::select(mailbox.as_str()))
         .try_collect()
         .await
         .map_err(|e| ImapError::Select { cause: e })?;
@@ -86,7 +86,7 @@ async fn process_email(response_data: ResponseData) -> Result<(), io::Error> {
                 AttributeValue::Uid(u) => {
                     eprintln!("Message UID: {}", u);
                 }
-                AttributeValue::Rfc822(Some(src)) => {
+                AttributeValue::Rfc822(Some(ref src)) => {
                     eprintln!("Message length: {}", src.to_vec().len());
                 }
                 _ => (),
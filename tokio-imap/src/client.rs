@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::future::Future;
 use std::io;
 use std::net::ToSocketAddrs;
@@ -11,15 +12,20 @@ use tokio_rustls::webpki::DNSNameRef;
 use tokio_rustls::{client::TlsStream, TlsConnector};
 use tokio_util::codec::Decoder;
 
-use crate::proto::{ImapCodec, ImapTransport, ResponseData};
-use imap_proto::builders::command::Command;
-use imap_proto::{Request, RequestId, State};
+use crate::proto::{GreetingData, ImapCodec, ImapTransport, ResponseData};
+use imap_proto::builders::command::{Command, IDLE_DONE};
+use imap_proto::{
+    Capability, GreetingStatus, LiteralMode, Request, RequestId, Response, ResponseCode, State,
+};
 
 pub mod builder {
     pub use imap_proto::builders::command::{
-        CommandBuilder, FetchBuilderAttributes, FetchBuilderMessages, FetchBuilderModifiers,
-        FetchCommand, FetchCommandAttributes, FetchCommandMessages,
+        AppendCommand, CommandBuilder, FetchBuilderAttributes, FetchBuilderMessages,
+        FetchBuilderModifiers, FetchCommand, FetchCommandAttributes, FetchCommandMessages,
+        ListCommand, SearchCommand, StoreCommand,
     };
+    pub use imap_proto::builders::fetch::Fetch;
+    pub use imap_proto::builders::search::SearchBuilder;
 }
 
 pub type TlsClient = Client<TlsStream<TcpStream>>;
@@ -30,13 +36,13 @@ pub struct Client<T> {
 }
 
 impl TlsClient {
-    pub async fn connect(server: &str) -> io::Result<(ResponseData, Self)> {
-        let addr = (server, 993).to_socket_addrs()?.next().ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("no IP addresses found for {}", server),
-            )
-        })?;
+    /// Connects to the server and reads its greeting. After a PREAUTH
+    /// greeting the client is already in the authenticated state.
+    pub async fn connect(server: &str) -> io::Result<(GreetingData, Self)> {
+        let addr = (server, 993)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::other(format!("no IP addresses found for {}", server)))?;
 
         let mut tls_config = ClientConfig::new();
         tls_config
@@ -51,26 +57,36 @@ impl TlsClient {
         let mut transport = ImapCodec::default().framed(stream);
 
         let greeting = match transport.next().await {
-            Some(greeting) => Ok(greeting),
-            None => Err(io::Error::new(io::ErrorKind::Other, "no greeting found")),
-        }?;
-        let client = Client {
+            Some(greeting) => greeting?.into_greeting()?,
+            None => return Err(io::Error::other("no greeting found")),
+        };
+        let mut client = Client {
             transport,
             state: ClientState::new(),
         };
+        if greeting.parsed().status == GreetingStatus::PreAuth {
+            client.state.state = State::Authenticated;
+        }
+        if let Some(caps) = greeting.parsed().capabilities() {
+            client.state.update_capabilities(caps);
+        }
 
-        greeting.map(|greeting| (greeting, client))
+        Ok((greeting, client))
     }
 
-    pub fn call(&mut self, cmd: Command) -> ResponseStream<TlsStream<TcpStream>> {
+    pub fn call(&mut self, cmd: Command) -> ResponseStream<'_, TlsStream<TcpStream>> {
         ResponseStream::new(self, cmd)
     }
 }
 
 pub struct ResponseStream<'a, T> {
     client: &'a mut Client<T>,
-    request: Request,
+    request_id: RequestId,
+    // The parts of the request still to be sent, each after a
+    // continuation request except for the first.
+    segments: VecDeque<Vec<u8>>,
     next_state: Option<State>,
+    idle: bool,
     sending: bool,
     done: bool,
 }
@@ -81,13 +97,23 @@ where
 {
     pub fn new(client: &mut Client<T>, cmd: Command) -> ResponseStream<'_, T> {
         let request_id = client.state.request_ids.next().unwrap(); // safe: never returns Err
+        let idle = cmd.is_idle();
         let (cmd_bytes, next_state) = cmd.into_parts();
-        let request = Request(request_id, cmd_bytes);
+        let request = Request(request_id, cmd_bytes).with_literal_mode(client.state.literal_mode);
+        let mut segments: VecDeque<_> = request.segments().into();
+        if client.state.idling {
+            // An earlier IDLE was left running; no command may be sent
+            // before it is ended.
+            segments[0].splice(0..0, IDLE_DONE.iter().copied());
+            client.state.idling = false;
+        }
 
         ResponseStream {
             client,
-            request,
+            request_id: request.0,
+            segments,
             next_state,
+            idle,
             sending: true,
             done: false,
         }
@@ -100,21 +126,51 @@ where
         }
 
         if self.sending {
-            match self.client.transport.send(&self.request).await {
-                Ok(()) => {
-                    self.sending = false;
+            if let Err(e) = self.send_segment().await {
+                return Some(Err(e));
+            }
+            self.sending = false;
+            self.client.state.idling = self.idle;
+        }
+
+        let mut next = self.client.transport.next().await;
+        // Continuation requests for the literals of this command are
+        // answered here rather than returned.
+        while let Some(Ok(rsp)) = &next {
+            match rsp.parsed() {
+                Response::Continue(_) if !self.segments.is_empty() => {
+                    if let Err(e) = self.send_segment().await {
+                        return Some(Err(e));
+                    }
+                    next = self.client.transport.next().await;
                 }
-                Err(e) => return Some(Err(e)),
+                _ => break,
             }
         }
 
-        match self.client.transport.next().await {
+        match next {
             Some(Ok(rsp)) => {
+                match rsp.parsed() {
+                    Response::Capabilities(caps)
+                    | Response::Data {
+                        code: Some(ResponseCode::Capabilities(caps)),
+                        ..
+                    }
+                    | Response::Done {
+                        code: Some(ResponseCode::Capabilities(caps)),
+                        ..
+                    } => self.client.state.update_capabilities(caps),
+                    _ => {}
+                }
+
                 if let Some(req_id) = rsp.request_id() {
-                    self.done = *req_id == self.request.0;
+                    self.done = *req_id == self.request_id;
                 }
 
                 if self.done {
+                    if self.idle {
+                        self.client.state.idling = false;
+                    }
                     if let Some(next_state) = self.next_state.take() {
                         self.client.state.state = next_state;
                     }
@@ -123,13 +179,29 @@ where
                 Some(Ok(rsp))
             }
             Some(Err(e)) => Some(Err(e)),
-            None => Some(Err(io::Error::new(
-                io::ErrorKind::Other,
+            None => Some(Err(io::Error::other(
                 "stream ended before command completion",
             ))),
         }
     }
 
+    /// Ends an IDLE command by sending `DONE`. The server then completes
+    /// the command, which `next` returns as usual.
+    pub async fn done(&mut self) -> Result<(), io::Error> {
+        if !self.idle || !self.client.state.idling {
+            return Ok(());
+        }
+        self.client.state.idling = false;
+        self.client.transport.send(IDLE_DONE).await
+    }
+
+    async fn send_segment(&mut self) -> Result<(), io::Error> {
+        match self.segments.pop_front() {
+            Some(segment) => self.client.transport.send(&segment[..]).await,
+            None => Ok(()),
+        }
+    }
+
     pub async fn try_collect(&mut self) -> Result<Vec<ResponseData>, io::Error> {
         let mut data = vec![];
         loop {
@@ -180,6 +252,11 @@ where
 pub struct ClientState {
     state: State,
     request_ids: IdGenerator,
+    // How literals are sent, from the capabilities the server last
+    // advertised.
+    literal_mode: LiteralMode,
+    // Whether an IDLE command was sent and not yet ended with DONE.
+    idling: bool,
 }
 
 impl ClientState {
@@ -187,8 +264,14 @@ impl ClientState {
         Self {
             state: State::NotAuthenticated,
             request_ids: IdGenerator::new(),
+            literal_mode: LiteralMode::Sync,
+            idling: false,
         }
     }
+
+    fn update_capabilities(&mut self, caps: &[Capability<'_>]) {
+        self.literal_mode = LiteralMode::from_capabilities(caps);
+    }
 }
 
 impl Default for ClientState {
@@ -1,62 +1,86 @@
+use std::convert::TryFrom;
 use std::io;
 use std::mem;
 
 use bytes::{BufMut, Bytes, BytesMut};
-use nom::{self, Needed};
+use nom::Needed;
 use tokio_util::codec::{Decoder, Encoder, Framed};
 
 use imap_proto;
-use imap_proto::types::{Request, RequestId, Response};
+use imap_proto::types::{Greeting, MailboxUpdate, Request, RequestId, Response};
+use imap_proto::{Options, Parsed};
 
+#[derive(Default)]
 pub struct ImapCodec {
     decode_need_message_bytes: usize,
+    options: Options,
 }
 
-impl Default for ImapCodec {
-    fn default() -> Self {
-        Self {
-            decode_need_message_bytes: 0,
+impl ImapCodec {
+    /// A codec that parses responses with the grammar changes selected by
+    /// `options`.
+    pub fn with_options(options: Options) -> Self {
+        ImapCodec {
+            options,
+            ..ImapCodec::default()
         }
     }
+
+    /// Changes the options for the responses that follow, such as turning
+    /// on `utf8_accept` once the server has enabled UTF8=ACCEPT.
+    pub fn set_options(&mut self, options: Options) {
+        self.options = options;
+    }
 }
 
-impl<'a> Decoder for ImapCodec {
+impl Decoder for ImapCodec {
     type Item = ResponseData;
     type Error = io::Error;
     fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, io::Error> {
         if self.decode_need_message_bytes > buf.len() {
             return Ok(None);
         }
-        let (response, rsp_len) = match imap_proto::parse_response(buf) {
-            Ok((remaining, response)) => {
-                // This SHOULD be acceptable/safe: BytesMut storage memory is
-                // allocated on the heap and should not move. It will not be
-                // freed as long as we keep a reference alive, which we do
-                // by retaining a reference to the split buffer, below.
-                let response = unsafe { mem::transmute(response) };
-                (response, buf.len() - remaining.len())
-            }
-            Err(nom::Err::Incomplete(Needed::Size(min))) => {
-                self.decode_need_message_bytes = min;
-                return Ok(None);
-            }
-            Err(nom::Err::Incomplete(_)) => {
-                return Ok(None);
-            }
-            Err(nom::Err::Error((_input, err_kind)))
-            | Err(nom::Err::Failure((_input, err_kind))) => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("{:?} during parsing of {:?}", err_kind, buf),
-                ));
-            }
-        };
+        let (response, rsp_len) =
+            match imap_proto::parse_response_incremental_with(buf, self.options) {
+                Parsed::Complete(remaining, response) => {
+                    // This SHOULD be acceptable/safe: BytesMut storage memory is
+                    // allocated on the heap and should not move. It will not be
+                    // freed as long as we keep a reference alive, which we do
+                    // by retaining a reference to the split buffer, below.
+                    let response =
+                        unsafe { mem::transmute::<Response<'_>, Response<'static>>(response) };
+                    (response, buf.len() - remaining.len())
+                }
+                Parsed::Incomplete(Needed::Size(more)) => {
+                    self.decode_need_message_bytes = buf.len() + more;
+                    return Ok(None);
+                }
+                Parsed::Incomplete(_) => {
+                    return Ok(None);
+                }
+                Parsed::Error(err) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        imap_proto::Error::from(err),
+                    ));
+                }
+            };
         let raw = buf.split_to(rsp_len).freeze();
         self.decode_need_message_bytes = 0;
         Ok(Some(ResponseData { raw, response }))
     }
 }
 
+/// Writes part of a request as it is, such as one returned by
+/// `Request::segments`.
+impl Encoder<&[u8]> for ImapCodec {
+    type Error = io::Error;
+    fn encode(&mut self, msg: &[u8], dst: &mut BytesMut) -> Result<(), io::Error> {
+        dst.put_slice(msg);
+        Ok(())
+    }
+}
+
 impl Encoder<&Request> for ImapCodec {
     type Error = io::Error;
     fn encode(&mut self, msg: &Request, dst: &mut BytesMut) -> Result<(), io::Error> {
@@ -70,16 +94,20 @@ impl Encoder<&Request> for ImapCodec {
 
 #[derive(Debug)]
 pub struct ResponseData {
-    raw: Bytes,
     // This reference is really scoped to the lifetime of the `raw`
     // member, but unfortunately Rust does not allow that yet. It
     // is transmuted to `'static` by the `Decoder`, instead, and
     // references returned to callers of `ResponseData` are limited
     // to the lifetime of the `ResponseData` struct.
     //
-    // `raw` is never mutated during the lifetime of `ResponseData`,
-    // and `Response` does not not implement any specific drop glue.
+    // `raw` is never mutated during the lifetime of `ResponseData`.
+    // `Response` does own heap data, such as its `Vec`s, but dropping
+    // the `Cow::Borrowed` and `&str` values that point into `raw` never
+    // reads them. `response` is declared before `raw` so that it is
+    // dropped first all the same.
     response: Response<'static>,
+    #[allow(dead_code)]
+    raw: Bytes,
 }
 
 impl ResponseData {
@@ -89,9 +117,40 @@ impl ResponseData {
             _ => None,
         }
     }
-    pub fn parsed<'a>(&'a self) -> &'a Response {
+    pub fn parsed(&self) -> &Response<'_> {
         &self.response
     }
+
+    /// Returns the change to the selected mailbox reported by this response,
+    /// if any. Servers may send these at any time, including in the middle
+    /// of the responses to an unrelated command.
+    pub fn mailbox_update(&self) -> Option<MailboxUpdate<'_>> {
+        self.response.mailbox_update()
+    }
+
+    /// Converts the response into a server greeting, failing if it is not
+    /// an untagged OK, PREAUTH or BYE response.
+    pub fn into_greeting(self) -> io::Result<GreetingData> {
+        let ResponseData { raw, response } = self;
+        match Greeting::try_from(response) {
+            Ok(greeting) => Ok(GreetingData { raw, greeting }),
+            Err(rsp) => Err(io::Error::other(format!("unexpected greeting {:?}", rsp))),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GreetingData {
+    #[allow(dead_code)]
+    raw: Bytes,
+    // Borrows from `raw`, like `ResponseData::response`.
+    greeting: Greeting<'static>,
+}
+
+impl GreetingData {
+    pub fn parsed(&self) -> &Greeting<'_> {
+        &self.greeting
+    }
 }
 
 pub type ImapTransport<T> = Framed<T, ImapCodec>;